@@ -1,4 +1,4 @@
-use clap::Parser;
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -8,6 +8,31 @@ use std::path::PathBuf;
 )]
 #[clap(version = "1.0")]
 #[clap(about = "Fuel Dynosite Profiler Site Generator", long_about = None)]
+pub struct Cli {
+    #[clap(subcommand)]
+    pub command: Command,
+}
+
+/// The subcommands of dynosite
+#[derive(Subcommand)]
+pub enum Command {
+    /// Add an execution comparing two runs of a benchmarks folder and regenerate the site
+    Add(Box<Options>),
+    /// Regenerate the stats, plots and pages of every stored execution from `site.json`
+    Rebuild(RebuildOptions),
+    /// List the executions stored in the site
+    List(SiteOptions),
+    /// Remove an execution and its data from the site and regenerate the site
+    Remove(RemoveOptions),
+    /// Compare two runs and print the changes without writing anything to the site
+    Compare(CompareOptions),
+    /// Check that the executions of the site and the files they reference are consistent
+    Validate(SiteOptions),
+}
+
+/// The options of the `add` subcommand
+#[derive(Parser)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct Options {
     #[clap(short, long)]
     /// The target folder containing the benchmarks
@@ -32,4 +57,193 @@ pub struct Options {
     #[clap(short = 'l', long)]
     /// The PR link (Optional)
     pub pr_link: Option<String>,
+
+    #[clap(long)]
+    /// Exit with a non-zero code when a benchmark crosses a regression threshold
+    pub fail_on_regression: bool,
+
+    #[clap(long, value_name = "METRIC=PERCENT")]
+    /// A regression threshold for a metric, e.g. `time=10` (Repeatable)
+    pub threshold: Vec<String>,
+
+    #[clap(long)]
+    /// A JSON file with the regression thresholds per metric (Optional)
+    pub thresholds_file: Option<PathBuf>,
+
+    #[clap(long)]
+    /// Write a Markdown summary of the execution to the given file (Optional)
+    pub report_markdown: Option<PathBuf>,
+
+    #[clap(long)]
+    /// Write a machine readable JSON report of the execution to the given file (Optional)
+    pub report_json: Option<PathBuf>,
+
+    #[clap(long)]
+    /// The public URL of the site, used for links in the reports (Optional)
+    pub site_url: Option<String>,
+
+    #[clap(long, value_name = "FILE|ID")]
+    /// The run to use as the previous benchmarks, a file or the file name, stem or timestamp of a run (Optional)
+    pub baseline: Option<String>,
+
+    #[clap(long, value_name = "FILE|ID")]
+    /// The run to use as the current benchmarks, a file or the file name, stem or timestamp of a run (Optional)
+    pub candidate: Option<String>,
+
+    #[clap(long, value_name = "FILE|ID")]
+    /// Pin a run stored in the site as the baseline of this and later executions (Optional)
+    pub pin_baseline: Option<String>,
+
+    #[clap(long, conflicts_with = "pin_baseline")]
+    /// Pin the current run of this execution as the baseline of later executions
+    pub pin_candidate: bool,
+
+    #[clap(long, conflicts_with_all = ["pin_baseline", "pin_candidate"])]
+    /// Remove the pinned baseline of the site
+    pub unpin_baseline: bool,
+
+    #[clap(long)]
+    /// Replace the execution of the site with the same identifier instead of failing
+    pub force: bool,
+
+    #[clap(long, value_enum, default_value_t = RunOrder::FileName)]
+    /// How runs are ordered when selecting the latest ones
+    pub order_by: RunOrder,
+
+    #[clap(long)]
+    /// A folder with templates overriding the built-in `layout.html`, `error.html` and `styles.css` (Optional)
+    pub templates: Option<PathBuf>,
+
+    #[clap(long)]
+    /// Copy the stylesheet and script into the site instead of loading them from CDNs
+    pub offline: bool,
+
+    #[clap(long)]
+    /// Render the plots in the browser from the frames of the benchmarks instead of as PNG images
+    pub interactive_charts: bool,
+
+    #[clap(long)]
+    /// Draw the previous and current runs on a single plot per metric with shared axes
+    pub overlay: bool,
+
+    #[clap(long)]
+    /// Draw the difference between the current and previous runs on the overlay plots, implies `--overlay`
+    pub overlay_delta: bool,
+
+    #[clap(long, value_enum, default_value_t = PlotFormat::Png)]
+    /// The image format of the plots
+    pub plot_format: PlotFormat,
+
+    #[clap(long)]
+    /// A JSON file mapping phase names to `#rrggbb` colours for the plots and charts (Optional)
+    pub phase_colors: Option<PathBuf>,
+
+    #[clap(long, value_name = "COUNT", default_value_t = crate::site::flamegraph::DEFAULT_HOT_FUNCTIONS)]
+    /// The number of functions by self and by inclusive samples in the hot functions table of each benchmark
+    pub hot_functions: usize,
+}
+
+/// The site to work on
+#[derive(Args)]
+pub struct SiteOptions {
+    #[clap(short, long)]
+    /// The site name (Optional)
+    pub site_name: Option<String>,
+}
+
+/// The options used to regenerate the pages of a site
+#[derive(Args)]
+pub struct GenerateOptions {
+    #[clap(short, long)]
+    /// Data only mode
+    pub data_only: bool,
+
+    #[clap(long)]
+    /// A folder with templates overriding the built-in `layout.html`, `error.html` and `styles.css` (Optional)
+    pub templates: Option<PathBuf>,
+
+    #[clap(long)]
+    /// Copy the stylesheet and script into the site instead of loading them from CDNs
+    pub offline: bool,
+
+    #[clap(long)]
+    /// A JSON file mapping phase names to `#rrggbb` colours for the plots and charts (Optional)
+    pub phase_colors: Option<PathBuf>,
+}
+
+/// The options of the `rebuild` subcommand
+#[derive(Args)]
+pub struct RebuildOptions {
+    #[clap(flatten)]
+    pub site: SiteOptions,
+
+    #[clap(flatten)]
+    pub generate: GenerateOptions,
+}
+
+/// The options of the `remove` subcommand
+#[derive(Args)]
+pub struct RemoveOptions {
+    /// The identifier of the execution to remove, as listed by `list`
+    pub execution: String,
+
+    #[clap(flatten)]
+    pub site: SiteOptions,
+
+    #[clap(flatten)]
+    pub generate: GenerateOptions,
+}
+
+/// The options of the `compare` subcommand
+#[derive(Args)]
+pub struct CompareOptions {
+    #[clap(value_name = "PREVIOUS")]
+    /// The previous run, a file or the file name, stem or timestamp of a run stored in the site
+    pub previous: String,
+
+    #[clap(value_name = "CURRENT")]
+    /// The current run, a file or the file name, stem or timestamp of a run stored in the site
+    pub current: String,
+
+    #[clap(flatten)]
+    pub site: SiteOptions,
+
+    #[clap(long)]
+    /// Print the comparison as JSON instead of text
+    pub json: bool,
+}
+
+/// The image format of the plots
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum PlotFormat {
+    /// Raster images
+    #[default]
+    Png,
+    /// Vector images, crisp at any zoom and diffable in git
+    Svg,
+}
+
+impl PlotFormat {
+    /// The file extension of the plots
+    #[must_use]
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Svg => "svg",
+        }
+    }
+}
+
+/// The ordering used to find the latest runs and flamegraphs
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum RunOrder {
+    /// The file system modification time
+    Modified,
+    /// The timestamp embedded in the file name
+    FileName,
+    /// The `benchmarks_datetime` stored in the run
+    Datetime,
 }
@@ -1,7 +1,44 @@
-pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+use std::fmt::Write;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The errors of dynosite
+pub enum Error {
+    /// Reading or writing a file or folder failed.
+    Io(std::io::Error),
+    /// Reading or writing JSON failed.
+    Json(serde_json::Error),
+    /// A value could not be parsed or converted.
+    Parse(String),
+    /// A benchmark has no asm information.
+    MissingAsmInfo { benchmark: String },
+    /// The asm information of a benchmark lacks a value.
+    MissingAsmValue { benchmark: String, value: String },
+    /// A benchmark has no frames, or its frames could not be read.
+    MissingFrames { benchmark: String },
+    /// A benchmark has no start or end time.
+    MissingTimings { benchmark: String },
+    /// Phases of a benchmark have no start or end time.
+    MissingPhaseTimings {
+        benchmark: String,
+        phases: Vec<String>,
+    },
+    /// No flamegraph folder belongs to a run.
+    MissingFlamegraphs { run: String },
+    /// Drawing a plot failed.
+    Plot(String),
+    /// Rendering a flamegraph failed.
+    Flamegraph(String),
+    /// Rendering a template failed.
+    Template(minijinja::Error),
+    /// Any other failure, described by its message.
+    Message(String),
+    /// An error with the file and line where it was wrapped.
+    Context(Box<Dynosite>),
+}
 
 pub struct Dynosite {
-    pub source: Box<dyn std::error::Error>,
+    pub source: Error,
     pub file: &'static str,
     pub line: u32,
 }
@@ -17,13 +54,161 @@ macro_rules! wrap {
     };
 }
 
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "I/O error: {e}"),
+            Self::Json(e) => write!(f, "JSON error: {e}"),
+            Self::Parse(message) => write!(f, "Parse error: {message}"),
+            Self::MissingAsmInfo { benchmark } => {
+                write!(f, "The benchmark `{benchmark}` has no asm information.")
+            }
+            Self::MissingAsmValue { benchmark, value } => write!(
+                f,
+                "The asm information of the benchmark `{benchmark}` has no `{value}`."
+            ),
+            Self::MissingFrames { benchmark } => {
+                write!(f, "The frames of the benchmark `{benchmark}` are missing.")
+            }
+            Self::MissingTimings { benchmark } => {
+                write!(f, "The benchmark `{benchmark}` has no start or end time.")
+            }
+            Self::MissingPhaseTimings { benchmark, phases } => write!(
+                f,
+                "The phases `{}` of the benchmark `{benchmark}` have no start or end time.",
+                phases.join("`, `")
+            ),
+            Self::MissingFlamegraphs { run } => {
+                write!(f, "No flamegraph folder matches the run `{run}`.")
+            }
+            Self::Plot(message) => write!(f, "Plot error: {message}"),
+            Self::Flamegraph(message) => write!(f, "Flamegraph error: {message}"),
+            Self::Template(e) => write!(f, "Template error: {e:#}"),
+            Self::Message(message) => write!(f, "{message}"),
+            Self::Context(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::fmt::Debug for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Json(e) => Some(e),
+            Self::Template(e) => Some(e),
+            Self::Context(e) => Some(&e.source),
+            _ => None,
+        }
+    }
+}
+
+impl Error {
+    /// Get the error without the file and line context it was wrapped in
+    #[must_use]
+    pub fn root_cause(&self) -> &Self {
+        match self {
+            Self::Context(e) => e.source.root_cause(),
+            _ => self,
+        }
+    }
+}
+
+/// Report an error that dynosite recovers from, like a single malformed benchmark
+pub fn warn(context: &str, error: &Error) {
+    eprintln!("Warning: {context}: {}", error.root_cause());
+}
+
+impl From<Dynosite> for Error {
+    fn from(e: Dynosite) -> Self {
+        Self::Context(Box::new(e))
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+impl From<minijinja::Error> for Error {
+    fn from(e: minijinja::Error) -> Self {
+        Self::Template(e)
+    }
+}
+
+impl From<chrono::ParseError> for Error {
+    fn from(e: chrono::ParseError) -> Self {
+        Self::Parse(e.to_string())
+    }
+}
+
+impl From<std::num::TryFromIntError> for Error {
+    fn from(e: std::num::TryFromIntError) -> Self {
+        Self::Parse(e.to_string())
+    }
+}
+
+impl From<std::num::ParseFloatError> for Error {
+    fn from(e: std::num::ParseFloatError) -> Self {
+        Self::Parse(e.to_string())
+    }
+}
+
+impl From<std::num::ParseIntError> for Error {
+    fn from(e: std::num::ParseIntError) -> Self {
+        Self::Parse(e.to_string())
+    }
+}
+
+impl From<std::string::FromUtf8Error> for Error {
+    fn from(e: std::string::FromUtf8Error) -> Self {
+        Self::Parse(e.to_string())
+    }
+}
+
+impl<E: std::error::Error + Send + Sync> From<plotters::drawing::DrawingAreaErrorKind<E>>
+    for Error
+{
+    fn from(e: plotters::drawing::DrawingAreaErrorKind<E>) -> Self {
+        Self::Plot(e.to_string())
+    }
+}
+
+impl From<&str> for Error {
+    fn from(message: &str) -> Self {
+        Self::Message(message.to_string())
+    }
+}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Self::Message(message)
+    }
+}
+
 impl std::fmt::Display for Dynosite {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.format_error_stack())
     }
 }
 
-impl std::error::Error for Dynosite {}
+impl std::error::Error for Dynosite {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
 
 impl std::fmt::Debug for Dynosite {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -35,14 +220,114 @@ impl Dynosite {
     fn format_error_stack(&self) -> String {
         let mut result = format!("Error in file and line -> {}:{}\n", self.file, self.line);
 
-        let mut current_error: &dyn std::error::Error = &*self.source;
-        while let Some(source) = current_error.downcast_ref::<Dynosite>() {
-            result.push_str(&format!(
-                "\nCaused by:\n  Error in file and line -> {}:{}\n      source: {}",
-                source.file, source.line, source.source
-            ));
-            current_error = &*source.source;
+        let mut current_error = &self.source;
+        while let Error::Context(source) = current_error {
+            let _ = write!(
+                result,
+                "\nCaused by:\n  Error in file and line -> {}:{}",
+                source.file, source.line
+            );
+            current_error = &source.source;
         }
+        let _ = write!(result, "\n      source: {current_error}");
         result
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(path: &str) -> Result<String> {
+        Ok(std::fs::read_to_string(path).map_err(|e| wrap!(e.into()))?)
+    }
+
+    fn parse(value: &str) -> Result<u64> {
+        Ok(value.parse::<u64>().map_err(|e| wrap!(e.into()))?)
+    }
+
+    #[test]
+    fn errors_are_converted_to_their_variant() {
+        assert!(matches!(
+            Error::from(std::io::Error::from(std::io::ErrorKind::NotFound)),
+            Error::Io(_)
+        ));
+        assert!(matches!(
+            Error::from(serde_json::from_str::<u64>("{").unwrap_err()),
+            Error::Json(_)
+        ));
+        assert!(matches!(
+            Error::from("1.5".parse::<u64>().unwrap_err()),
+            Error::Parse(_)
+        ));
+        assert!(matches!(
+            Error::from(u8::try_from(300_u64).unwrap_err()),
+            Error::Parse(_)
+        ));
+        assert!(matches!(Error::from("failed"), Error::Message(_)));
+    }
+
+    #[test]
+    fn errors_describe_their_cause() {
+        assert_eq!(
+            Error::MissingAsmInfo {
+                benchmark: "hashing".to_string()
+            }
+            .to_string(),
+            "The benchmark `hashing` has no asm information."
+        );
+        assert_eq!(
+            Error::MissingFrames {
+                benchmark: "hashing".to_string()
+            }
+            .to_string(),
+            "The frames of the benchmark `hashing` are missing."
+        );
+        assert_eq!(
+            Error::Plot("no area".to_string()).to_string(),
+            "Plot error: no area"
+        );
+        assert_eq!(Error::from("failed").to_string(), "failed");
+    }
+
+    #[test]
+    fn wrapped_errors_keep_their_root_cause() {
+        let error = Error::from(wrap!(Error::from(wrap!(parse("x").unwrap_err()))));
+
+        assert!(matches!(error, Error::Context(_)));
+        assert!(matches!(error.root_cause(), Error::Parse(_)));
+        assert_eq!(
+            error.root_cause().to_string(),
+            "Parse error: invalid digit found in string"
+        );
+        assert!(matches!(
+            read("/missing/dynosite").unwrap_err().root_cause(),
+            Error::Io(_)
+        ));
+        // The root cause of an unwrapped error is the error itself
+        assert!(matches!(
+            Error::from("failed").root_cause(),
+            Error::Message(_)
+        ));
+    }
+
+    #[test]
+    fn wrapped_errors_print_every_file_and_line() {
+        let error = Error::from(wrap!(parse("x").unwrap_err()));
+
+        let message = error.to_string();
+
+        assert!(message.starts_with("Error in file and line -> src/error.rs:"));
+        assert_eq!(message.matches("Caused by:").count(), 1);
+        assert!(message.ends_with("\n      source: Parse error: invalid digit found in string"));
+        // The chain of sources leads to the root cause
+        let mut source: &dyn std::error::Error = &error;
+        while let Some(next) = source.source() {
+            source = next;
+        }
+        assert_eq!(
+            source.to_string(),
+            "Parse error: invalid digit found in string"
+        );
+    }
+}
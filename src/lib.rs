@@ -0,0 +1,51 @@
+//! Generate the Fuel dynosite, a static site comparing the performance of two runs of the `forc`
+//! benchmarks, from Rust instead of the `dynosite` command line tool.
+//!
+//! The entry points are:
+//!
+//! - [`DynoSite`], the site on disk with its stored executions, which regenerates every page when
+//!   an execution is added or removed, and can rebuild and validate them.
+//! - [`Execution`], the comparison of a previous and a current run of the benchmarks, with its
+//!   stats, plots and flamegraphs.
+//! - [`Benchmarks`], the runs written by dyno, shared with the tools producing them.
+//!
+//! The generators behind the site, like [`site::html::generate`], [`site::plot::generate_plots`],
+//! [`site::markdown::generate`], [`site::report::Report`] and [`site::compare::Comparison`], are
+//! public as well for tools that only need a part of it.
+//!
+//! ```no_run
+//! use clap::Parser;
+//! use dynosite::{cli::Options, site::palette::PhaseColors, site::template::Templates};
+//! use dynosite::{DynoSite, Execution};
+//!
+//! # fn main() -> dynosite::Result<()> {
+//! let options = Options::parse_from(["dynosite", "-b", "benchmarks"]);
+//! let templates = Templates::new(None, false)?;
+//! let colors = PhaseColors::load(None)?;
+//!
+//! let mut site = DynoSite::init("site")?;
+//! let execution = Execution::new(&site.data.root_folder.clone(), &options, None, &colors)?;
+//! site.add_execution(&execution, options.force, options.data_only, &templates, &colors)?;
+//! site.store()?;
+//! # Ok(())
+//! # }
+//! ```
+
+#![warn(clippy::all, clippy::pedantic)]
+#![allow(clippy::cast_possible_wrap)]
+#![allow(clippy::cast_possible_truncation)]
+#![allow(clippy::type_complexity)]
+#![allow(clippy::too_many_lines)]
+
+/// The command line options, also used to configure an [`Execution`]
+pub mod cli;
+/// The errors of dynosite and the [`wrap!`] macro adding the file and line to them
+pub mod error;
+/// The site and its generators
+pub mod site;
+/// The benchmark data written by dyno
+pub mod types;
+
+pub use error::{Error, Result};
+pub use site::{dynosite::DynoSite, execution::Execution};
+pub use types::Benchmarks;
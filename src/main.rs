@@ -1,44 +1,278 @@
 #![warn(clippy::all, clippy::pedantic)]
-#![allow(clippy::struct_field_names)]
-#![allow(clippy::cast_possible_wrap)]
-#![allow(clippy::cast_possible_truncation)]
-#![allow(clippy::type_complexity)]
-#![allow(clippy::too_many_lines)]
 
 use clap::Parser;
 
-mod cli;
-mod error;
-mod site;
-mod types;
+use std::{path::PathBuf, process::ExitCode};
 
-pub use error::Result;
-use site::execution::Execution;
+use dynosite::{
+    cli::{self, Command},
+    site::{
+        self,
+        compare::Comparison,
+        palette::PhaseColors,
+        regression::{self, Thresholds},
+        template::Templates,
+    },
+    wrap, DynoSite, Execution, Result,
+};
 
-pub fn main() -> Result<()> {
-    init().map_err(|e| wrap!(e))?;
-    Ok(())
+/// The name of the site when none is given
+const DEFAULT_SITE_NAME: &str = "site";
+
+/// Run dynosite
+///
+/// # Errors
+///
+/// Returns an error with the file and line it was raised at if the subcommand fails.
+pub fn main() -> Result<ExitCode> {
+    Ok(run().map_err(|e| wrap!(e))?)
 }
 
-/// Initialize the site
-fn init() -> Result<()> {
-    // Parse the command line arguments
-    let options = cli::Options::parse();
+/// Run the subcommand given on the command line, returning the exit code of the process
+fn run() -> Result<ExitCode> {
+    match cli::Cli::parse().command {
+        Command::Add(options) => return Ok(add(&options).map_err(|e| wrap!(e))?),
+        Command::Rebuild(options) => rebuild(&options).map_err(|e| wrap!(e))?,
+        Command::List(options) => list(&options).map_err(|e| wrap!(e))?,
+        Command::Remove(options) => remove(&options).map_err(|e| wrap!(e))?,
+        Command::Compare(options) => compare(&options).map_err(|e| wrap!(e))?,
+        Command::Validate(options) => return Ok(validate(&options).map_err(|e| wrap!(e))?),
+    }
+    Ok(ExitCode::SUCCESS)
+}
 
+/// Add an execution to the site, failing the process when a regression crosses its threshold
+fn add(options: &cli::Options) -> Result<ExitCode> {
     // Initialize the site
-    let mut site =
-        site::dynosite::DynoSite::init(&options.site_name.clone().unwrap_or("site".to_string()))
-            .map_err(|e| wrap!(e))?;
+    let mut site = DynoSite::init(options.site_name.as_deref().unwrap_or(DEFAULT_SITE_NAME))
+        .map_err(|e| wrap!(e))?;
+
+    // Load the regression thresholds before doing any work so a bad config fails early
+    let thresholds = if options.fail_on_regression {
+        Some(
+            Thresholds::load(options.thresholds_file.as_deref(), &options.threshold)
+                .map_err(|e| wrap!(e))?,
+        )
+    } else {
+        None
+    };
+
+    // Load the templates before doing any work so a bad folder fails early
+    let templates =
+        Templates::new(options.templates.as_deref(), options.offline).map_err(|e| wrap!(e))?;
+
+    // Load the phase colours before doing any work so a bad config fails early
+    let phase_colors = PhaseColors::load(options.phase_colors.as_deref()).map_err(|e| wrap!(e))?;
+
+    // Update the pinned baseline before selecting the runs
+    if let Some(pin_baseline) = &options.pin_baseline {
+        site.data.pinned_baseline = Some(site.data.find_run(pin_baseline).ok_or_else(|| {
+            wrap!(format!("Failed to find the stored run `{pin_baseline}`").into())
+        })?);
+    }
+
+    if options.unpin_baseline {
+        site.data.pinned_baseline = None;
+    }
+
+    let execution = Execution::new(
+        &site.data.root_folder.clone(),
+        options,
+        site.data.pinned_baseline.as_deref(),
+        &phase_colors,
+    )
+    .map_err(|e| wrap!(e))?;
+
+    if options.pin_candidate {
+        site.data.pinned_baseline = Some(execution.current_benchmarks.clone());
+    }
 
     // Add the execution to the site
     site.add_execution(
-        &Execution::new(&site.data.root_folder.clone(), &options).map_err(|e| wrap!(e))?,
+        &execution,
+        options.force,
         options.data_only,
+        &templates,
+        &phase_colors,
     )
     .map_err(|e| wrap!(e))?;
 
     // Store the site locally
     site.store().map_err(|e| wrap!(e))?;
 
+    // Write the Markdown summary
+    if let Some(report_markdown) = &options.report_markdown {
+        let markdown = site::markdown::generate(&execution, options.site_url.as_deref())
+            .map_err(|e| wrap!(e))?;
+        std::fs::write(report_markdown, markdown).map_err(|e| wrap!(e.into()))?;
+    }
+
+    // Write the JSON report
+    if let Some(report_json) = &options.report_json {
+        let report = site::report::Report::new(&execution).map_err(|e| wrap!(e))?;
+        std::fs::write(
+            report_json,
+            serde_json::to_string_pretty(&report).map_err(|e| wrap!(e.into()))?,
+        )
+        .map_err(|e| wrap!(e.into()))?;
+    }
+
+    // Gate on regressions after the site has been stored
+    if let Some(thresholds) = thresholds {
+        let regressions = regression::check(
+            &execution.stats_collection().map_err(|e| wrap!(e))?,
+            &thresholds,
+        );
+
+        if !regressions.is_empty() {
+            eprint!("{}", regression::summary(&regressions));
+            return Ok(ExitCode::FAILURE);
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Load the templates and the phase colours used to regenerate the site
+fn load_generators(options: &cli::GenerateOptions) -> Result<(Templates, PhaseColors)> {
+    let templates =
+        Templates::new(options.templates.as_deref(), options.offline).map_err(|e| wrap!(e))?;
+    let phase_colors = PhaseColors::load(options.phase_colors.as_deref()).map_err(|e| wrap!(e))?;
+    Ok((templates, phase_colors))
+}
+
+/// Load an existing site
+fn load_site(options: &cli::SiteOptions) -> Result<DynoSite> {
+    Ok(
+        DynoSite::load(options.site_name.as_deref().unwrap_or(DEFAULT_SITE_NAME))
+            .map_err(|e| wrap!(e))?,
+    )
+}
+
+/// Regenerate the stats, plots and pages of the site from its stored executions
+fn rebuild(options: &cli::RebuildOptions) -> Result<()> {
+    let mut site = load_site(&options.site).map_err(|e| wrap!(e))?;
+    let (templates, phase_colors) = load_generators(&options.generate).map_err(|e| wrap!(e))?;
+
+    site.rebuild(options.generate.data_only, &templates, &phase_colors)
+        .map_err(|e| wrap!(e))?;
+    site.store().map_err(|e| wrap!(e))?;
+
+    println!(
+        "Rebuilt {} execution(s) in `{}`",
+        site.data.executions.len(),
+        site.root_folder.display()
+    );
     Ok(())
 }
+
+/// Print the executions stored in the site
+fn list(options: &cli::SiteOptions) -> Result<()> {
+    let site = load_site(options).map_err(|e| wrap!(e))?;
+
+    if site.data.executions.is_empty() {
+        println!("The site has no executions.");
+    }
+
+    let file_name = |path: &std::path::Path| {
+        path.file_name().map_or_else(
+            || path.display().to_string(),
+            |name| name.to_string_lossy().to_string(),
+        )
+    };
+
+    for execution in &site.data.executions {
+        println!(
+            "{}\t{} -> {}\t{}{}",
+            execution.identifier().map_err(|e| wrap!(e))?,
+            file_name(&execution.previous_benchmarks),
+            file_name(&execution.current_benchmarks),
+            execution.github_information.title,
+            if execution.pinned_baseline {
+                " (pinned baseline)"
+            } else {
+                ""
+            }
+        );
+    }
+
+    if let Some(pinned_baseline) = &site.data.pinned_baseline {
+        println!("Pinned baseline: {}", file_name(pinned_baseline));
+    }
+
+    Ok(())
+}
+
+/// Remove an execution from the site
+fn remove(options: &cli::RemoveOptions) -> Result<()> {
+    let mut site = load_site(&options.site).map_err(|e| wrap!(e))?;
+    let (templates, phase_colors) = load_generators(&options.generate).map_err(|e| wrap!(e))?;
+
+    site.remove_execution(
+        &options.execution,
+        options.generate.data_only,
+        &templates,
+        &phase_colors,
+    )
+    .map_err(|e| wrap!(e))?;
+    site.store().map_err(|e| wrap!(e))?;
+
+    println!("Removed the execution `{}`", options.execution);
+    Ok(())
+}
+
+/// Compare two runs without writing anything to the site
+fn compare(options: &cli::CompareOptions) -> Result<()> {
+    // Runs that aren't files are looked up in the site
+    let mut site = None;
+    let mut resolve = |run: &str| -> Result<PathBuf> {
+        let path = PathBuf::from(run);
+        if path.is_file() {
+            return Ok(path);
+        }
+
+        if site.is_none() {
+            site = Some(load_site(&options.site).map_err(|e| wrap!(e))?);
+        }
+        Ok(site
+            .as_ref()
+            .and_then(|site| site.data.find_run(run))
+            .ok_or_else(|| wrap!(format!("Failed to find the run `{run}`").into()))?)
+    };
+
+    let previous = resolve(&options.previous).map_err(|e| wrap!(e))?;
+    let current = resolve(&options.current).map_err(|e| wrap!(e))?;
+
+    let comparison = Comparison::new(&previous, &current).map_err(|e| wrap!(e))?;
+
+    if options.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&comparison).map_err(|e| wrap!(e.into()))?
+        );
+    } else {
+        print!("{}", comparison.summary());
+    }
+
+    Ok(())
+}
+
+/// Check the site for missing or unreadable files, failing the process when any are found
+fn validate(options: &cli::SiteOptions) -> Result<ExitCode> {
+    let site = load_site(options).map_err(|e| wrap!(e))?;
+
+    let problems = site.validate();
+    if !problems.is_empty() {
+        for problem in &problems {
+            eprintln!("{problem}");
+        }
+        eprintln!("Found {} problem(s) in the site", problems.len());
+        return Ok(ExitCode::FAILURE);
+    }
+
+    println!(
+        "The site is valid: {} execution(s)",
+        site.data.executions.len()
+    );
+    Ok(ExitCode::SUCCESS)
+}
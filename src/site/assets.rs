@@ -0,0 +1,50 @@
+use std::path::Path;
+
+use crate::{error::Result, wrap};
+
+/// The assets of the site: the stylesheet and script used by offline sites in place of the CDN
+/// assets, the renderer of the interactive charts and the sorting of the tables
+pub const ASSETS: [(&str, &str); 4] = [
+    ("dynosite.css", include_str!("assets/dynosite.css")),
+    ("dynosite.js", include_str!("assets/dynosite.js")),
+    ("charts.js", include_str!("assets/charts.js")),
+    ("tables.js", include_str!("assets/tables.js")),
+];
+
+/// Write the assets to the `assets` folder of the site
+///
+/// # Errors
+///
+/// Returns an error if the `assets` folder or one of its files can't be written.
+pub fn write(root_folder: &Path) -> Result<()> {
+    let assets_folder = root_folder.join("assets");
+
+    std::fs::create_dir_all(&assets_folder).map_err(|e| wrap!(e.into()))?;
+
+    for (name, source) in ASSETS {
+        std::fs::write(assets_folder.join(name), source).map_err(|e| wrap!(e.into()))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_copies_the_assets_to_the_site() {
+        let folder = tempfile::tempdir().unwrap();
+
+        write(folder.path()).unwrap();
+        // Writing again replaces the assets of a previous generation
+        write(folder.path()).unwrap();
+
+        for (name, source) in ASSETS {
+            assert_eq!(
+                std::fs::read_to_string(folder.path().join("assets").join(name)).unwrap(),
+                source
+            );
+        }
+    }
+}
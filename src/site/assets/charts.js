@@ -0,0 +1,384 @@
+// Interactive charts of the benchmark frames, rendered as SVG
+(function () {
+  var SVG = 'http://www.w3.org/2000/svg';
+  var HEIGHT = 320;
+  var MARGIN = { top: 20, right: 20, bottom: 40, left: 80 };
+  var PHASE_COLORS = ['#e6194b', '#3cb44b', '#4363d8', '#f58231', '#911eb4', '#42d4f4', '#f032e6', '#bfef45'];
+
+  function element(name, attributes, parent) {
+    var el = document.createElementNS(SVG, name);
+    Object.keys(attributes).forEach(function (key) {
+      el.setAttribute(key, attributes[key]);
+    });
+    if (parent) {
+      parent.appendChild(el);
+    }
+    return el;
+  }
+
+  function format(value) {
+    var abs = Math.abs(value);
+    var units = [[1e12, 'T'], [1e9, 'G'], [1e6, 'M'], [1e3, 'k']];
+    for (var i = 0; i < units.length; i++) {
+      if (abs >= units[i][0]) {
+        return (value / units[i][0]).toFixed(2).replace(/\.?0+$/, '') + units[i][1];
+      }
+    }
+    return (Math.round(value * 100) / 100).toString();
+  }
+
+  function ticks(min, max, count) {
+    var span = max - min;
+    if (span <= 0) {
+      return [min];
+    }
+    var step = Math.pow(10, Math.floor(Math.log10(span / count)));
+    var error = span / count / step;
+    if (error >= 7.5) {
+      step *= 10;
+    } else if (error >= 3.5) {
+      step *= 5;
+    } else if (error >= 1.5) {
+      step *= 2;
+    }
+    var result = [];
+    for (var tick = Math.ceil(min / step) * step; tick <= max + step * 1e-9; tick += step) {
+      result.push(tick);
+    }
+    return result;
+  }
+
+  // The colour of a phase as configured when the series was written, or one from the palette
+  function phaseColor(chart, name) {
+    return chart.phaseColors[name] || PHASE_COLORS[chart.phaseNames.indexOf(name) % PHASE_COLORS.length];
+  }
+
+  // The value of a series at the frame closest to the given time
+  function closest(series, time) {
+    var best = null;
+    series.points.forEach(function (point) {
+      if (best === null || Math.abs(point[0] - time) < Math.abs(best[0] - time)) {
+        best = point;
+      }
+    });
+    return best;
+  }
+
+  function Chart(container, spec) {
+    this.container = container;
+    this.spec = spec;
+    this.hidden = {};
+    this.phaseNames = [];
+    this.phaseColors = {};
+    var self = this;
+    spec.phases.forEach(function (phase) {
+      if (self.phaseNames.indexOf(phase.name) < 0) {
+        self.phaseNames.push(phase.name);
+      }
+      if (phase.color) {
+        self.phaseColors[phase.name] = phase.color;
+      }
+    });
+    this.full = this.extent();
+    this.domain = this.full.slice();
+    this.build();
+    this.render();
+  }
+
+  Chart.prototype.extent = function () {
+    var max = 0;
+    this.spec.series.forEach(function (series) {
+      series.points.forEach(function (point) {
+        max = Math.max(max, point[0]);
+      });
+    });
+    this.spec.phases.forEach(function (phase) {
+      max = Math.max(max, phase.end);
+    });
+    return [0, max || 1];
+  };
+
+  Chart.prototype.build = function () {
+    var self = this;
+    this.container.classList.add('chart');
+    this.container.innerHTML = '';
+
+    var title = document.createElement('h5');
+    title.textContent = this.spec.title;
+    this.container.appendChild(title);
+
+    var controls = document.createElement('div');
+    controls.className = 'chart-controls';
+    this.container.appendChild(controls);
+
+    this.spec.series.forEach(function (series) {
+      var item = document.createElement('span');
+      item.className = 'chart-legend';
+      item.innerHTML = '<span class="chart-swatch"></span>';
+      item.firstChild.style.backgroundColor = series.color;
+      item.appendChild(document.createTextNode(series.name));
+      controls.appendChild(item);
+    });
+
+    this.phaseNames.forEach(function (name) {
+      var label = document.createElement('label');
+      label.className = 'chart-legend';
+      var checkbox = document.createElement('input');
+      checkbox.type = 'checkbox';
+      checkbox.checked = true;
+      checkbox.addEventListener('change', function () {
+        self.hidden[name] = !checkbox.checked;
+        self.render();
+      });
+      var swatch = document.createElement('span');
+      swatch.className = 'chart-swatch';
+      swatch.style.backgroundColor = phaseColor(self, name);
+      swatch.style.opacity = 0.4;
+      label.appendChild(checkbox);
+      label.appendChild(swatch);
+      label.appendChild(document.createTextNode(name));
+      controls.appendChild(label);
+    });
+
+    var reset = document.createElement('button');
+    reset.type = 'button';
+    reset.className = 'chart-reset';
+    reset.textContent = 'Reset zoom';
+    reset.addEventListener('click', function () {
+      self.domain = self.full.slice();
+      self.render();
+    });
+    controls.appendChild(reset);
+
+    this.plot = document.createElement('div');
+    this.plot.className = 'chart-plot';
+    this.container.appendChild(this.plot);
+
+    this.tooltip = document.createElement('div');
+    this.tooltip.className = 'chart-tooltip';
+    this.plot.appendChild(this.tooltip);
+
+    window.addEventListener('resize', function () {
+      self.render();
+    });
+  };
+
+  Chart.prototype.render = function () {
+    var self = this;
+    var width = Math.max(this.plot.clientWidth || this.container.clientWidth, 300);
+    var innerWidth = width - MARGIN.left - MARGIN.right;
+    var innerHeight = HEIGHT - MARGIN.top - MARGIN.bottom;
+    var x0 = this.domain[0];
+    var x1 = this.domain[1];
+
+    var yMin = 0;
+    var yMax = 0;
+    this.spec.series.forEach(function (series) {
+      series.points.forEach(function (point) {
+        if (point[0] >= x0 && point[0] <= x1) {
+          yMin = Math.min(yMin, point[1]);
+          yMax = Math.max(yMax, point[1]);
+        }
+      });
+    });
+    if (this.spec.max !== undefined && this.spec.max !== null) {
+      yMax = Math.max(yMax, this.spec.max);
+    }
+    if (yMax === yMin) {
+      yMax = yMin + 1;
+    }
+    yMax += (yMax - yMin) * 0.05;
+
+    var x = function (time) {
+      return MARGIN.left + ((time - x0) / (x1 - x0)) * innerWidth;
+    };
+    var y = function (value) {
+      return MARGIN.top + innerHeight - ((value - yMin) / (yMax - yMin)) * innerHeight;
+    };
+
+    if (this.svg) {
+      this.plot.removeChild(this.svg);
+    }
+    var svg = element('svg', { width: width, height: HEIGHT, class: 'chart-svg' });
+    this.svg = svg;
+    this.plot.insertBefore(svg, this.tooltip);
+
+    var clip = 'clip-' + Math.random().toString(36).slice(2);
+    var defs = element('defs', {}, svg);
+    var clipPath = element('clipPath', { id: clip }, defs);
+    element('rect', { x: MARGIN.left, y: MARGIN.top, width: innerWidth, height: innerHeight }, clipPath);
+
+    var area = element('g', { 'clip-path': 'url(#' + clip + ')' }, svg);
+
+    // Phase overlays
+    this.spec.phases.forEach(function (phase) {
+      if (self.hidden[phase.name] || phase.end < x0 || phase.start > x1) {
+        return;
+      }
+      var rect = element('rect', {
+        x: x(phase.start),
+        y: MARGIN.top,
+        width: Math.max(x(phase.end) - x(phase.start), 1),
+        height: innerHeight,
+        fill: phaseColor(self, phase.name),
+        'fill-opacity': 0.25
+      }, area);
+      element('title', {}, rect).textContent = phase.name;
+    });
+
+    // Axes and grid
+    ticks(yMin, yMax, 6).forEach(function (tick) {
+      element('line', { x1: MARGIN.left, x2: MARGIN.left + innerWidth, y1: y(tick), y2: y(tick), class: 'chart-grid' }, svg);
+      var label = element('text', { x: MARGIN.left - 8, y: y(tick) + 4, 'text-anchor': 'end', class: 'chart-label' }, svg);
+      label.textContent = format(tick);
+    });
+    ticks(x0, x1, 8).forEach(function (tick) {
+      element('line', { x1: x(tick), x2: x(tick), y1: MARGIN.top + innerHeight, y2: MARGIN.top + innerHeight + 5, class: 'chart-axis' }, svg);
+      var label = element('text', { x: x(tick), y: MARGIN.top + innerHeight + 20, 'text-anchor': 'middle', class: 'chart-label' }, svg);
+      label.textContent = format(tick);
+    });
+    element('line', { x1: MARGIN.left, x2: MARGIN.left + innerWidth, y1: MARGIN.top + innerHeight, y2: MARGIN.top + innerHeight, class: 'chart-axis' }, svg);
+    element('line', { x1: MARGIN.left, x2: MARGIN.left, y1: MARGIN.top, y2: MARGIN.top + innerHeight, class: 'chart-axis' }, svg);
+    var axisLabel = element('text', { x: MARGIN.left + innerWidth / 2, y: HEIGHT - 4, 'text-anchor': 'middle', class: 'chart-label' }, svg);
+    axisLabel.textContent = 'Time (ms)';
+
+    // Series
+    this.spec.series.forEach(function (series) {
+      var points = series.points.map(function (point) {
+        return x(point[0]).toFixed(1) + ',' + y(point[1]).toFixed(1);
+      });
+      element('polyline', {
+        points: points.join(' '),
+        fill: 'none',
+        stroke: series.color,
+        'stroke-width': 1.5,
+        'stroke-dasharray': series.dashed ? '6 3' : 'none'
+      }, area);
+    });
+
+    // Hover and zoom
+    var cursor = element('line', { y1: MARGIN.top, y2: MARGIN.top + innerHeight, class: 'chart-cursor', visibility: 'hidden' }, svg);
+    var selection = element('rect', { y: MARGIN.top, height: innerHeight, class: 'chart-selection', visibility: 'hidden' }, svg);
+    var overlay = element('rect', { x: MARGIN.left, y: MARGIN.top, width: innerWidth, height: innerHeight, fill: 'transparent' }, svg);
+    var dragStart = null;
+
+    var timeAt = function (event) {
+      var bounds = svg.getBoundingClientRect();
+      var position = Math.min(Math.max(event.clientX - bounds.left, MARGIN.left), MARGIN.left + innerWidth);
+      return { position: position, time: x0 + ((position - MARGIN.left) / innerWidth) * (x1 - x0) };
+    };
+
+    overlay.addEventListener('mousemove', function (event) {
+      var at = timeAt(event);
+      cursor.setAttribute('x1', at.position);
+      cursor.setAttribute('x2', at.position);
+      cursor.setAttribute('visibility', 'visible');
+
+      var lines = [];
+      var phase = self.spec.phases.filter(function (phase) {
+        return at.time >= phase.start && at.time <= phase.end;
+      })[0];
+      self.spec.series.forEach(function (series) {
+        var point = closest(series, at.time);
+        if (point) {
+          lines.push({ color: series.color, text: series.name + ': ' + point[1].toLocaleString() + ' @ ' + point[0] + ' ms' });
+        }
+      });
+      if (phase) {
+        lines.push({ color: phaseColor(self, phase.name), text: phase.name });
+      }
+
+      self.tooltip.innerHTML = '';
+      lines.forEach(function (line) {
+        var row = document.createElement('div');
+        var swatch = document.createElement('span');
+        swatch.className = 'chart-swatch';
+        swatch.style.backgroundColor = line.color;
+        row.appendChild(swatch);
+        row.appendChild(document.createTextNode(line.text));
+        self.tooltip.appendChild(row);
+      });
+      self.tooltip.style.display = lines.length ? 'block' : 'none';
+      self.tooltip.style.left = (at.position + 12 > width - 260 ? at.position - 272 : at.position + 12) + 'px';
+      self.tooltip.style.top = MARGIN.top + 'px';
+
+      if (dragStart !== null) {
+        selection.setAttribute('x', Math.min(dragStart.position, at.position));
+        selection.setAttribute('width', Math.abs(at.position - dragStart.position));
+        selection.setAttribute('visibility', 'visible');
+      }
+    });
+
+    overlay.addEventListener('mouseleave', function () {
+      cursor.setAttribute('visibility', 'hidden');
+      selection.setAttribute('visibility', 'hidden');
+      self.tooltip.style.display = 'none';
+      dragStart = null;
+    });
+
+    overlay.addEventListener('mousedown', function (event) {
+      event.preventDefault();
+      dragStart = timeAt(event);
+    });
+
+    overlay.addEventListener('mouseup', function (event) {
+      if (dragStart === null) {
+        return;
+      }
+      var at = timeAt(event);
+      var start = Math.min(dragStart.time, at.time);
+      var end = Math.max(dragStart.time, at.time);
+      dragStart = null;
+      if (end - start > (x1 - x0) / 200) {
+        self.domain = [start, end];
+        self.render();
+      } else {
+        selection.setAttribute('visibility', 'hidden');
+      }
+    });
+
+    overlay.addEventListener('dblclick', function () {
+      self.domain = self.full.slice();
+      self.render();
+    });
+  };
+
+  // Render every chart of the page from the embedded series
+  document.addEventListener('DOMContentLoaded', function () {
+    var data = {};
+    Array.prototype.forEach.call(document.querySelectorAll('script[data-chart-series]'), function (script) {
+      data[script.getAttribute('data-chart-series')] = JSON.parse(script.textContent);
+    });
+
+    Array.prototype.forEach.call(document.querySelectorAll('[data-chart]'), function (container) {
+      var spec = JSON.parse(container.getAttribute('data-chart'));
+      var phases = [];
+      var series = spec.series.map(function (item) {
+        var source = data[item.source];
+        var metric = source.metrics[spec.metric] || [];
+        if (item.phases) {
+          phases = phases.concat(source.phases);
+        }
+        var points = source.time.map(function (time, i) {
+          return [time, metric[i]];
+        });
+        // The difference to another run, taking the frame of that run closest in time
+        if (item.delta) {
+          var base = data[item.delta];
+          var baseMetric = base.metrics[spec.metric] || [];
+          var basePoints = {
+            points: base.time.map(function (time, i) {
+              return [time, baseMetric[i]];
+            })
+          };
+          points = points.map(function (point) {
+            var other = closest(basePoints, point[0]);
+            return [point[0], other ? point[1] - other[1] : point[1]];
+          });
+        }
+        return { name: item.name, color: item.color, dashed: item.dashed, points: points };
+      });
+      new Chart(container, { title: spec.title, max: spec.max, series: series, phases: phases });
+    });
+  });
+})();
@@ -0,0 +1,81 @@
+// The subset of Materialize used by the site, for offline use
+(function () {
+  // Glyphs standing in for the Material Icons font
+  var icons = {
+    compare_arrows: '\u{21C4}',
+    folder: '\u{1F4C1}',
+    insert_chart: '\u{1F4CA}',
+    memory: '\u{1F4BE}',
+    push_pin: '\u{1F4CC}',
+    settings: '\u{2699}',
+    timeline: '\u{1F4C8}',
+    warning: '\u{26A0}',
+    whatshot: '\u{1F525}'
+  };
+
+  function Collapsible(el) {
+    var self = this;
+    this.el = el;
+    el.dynositeCollapsible = this;
+    Array.prototype.forEach.call(el.children, function (li, index) {
+      var header = li.querySelector('.collapsible-header');
+      if (header) {
+        header.addEventListener('click', function () {
+          if (li.classList.contains('active')) {
+            self.close(index);
+          } else {
+            self.open(index);
+          }
+        });
+      }
+    });
+  }
+
+  Collapsible.prototype.open = function (index) {
+    var self = this;
+    Array.prototype.forEach.call(this.el.children, function (li, i) {
+      if (i !== index) {
+        self.close(i);
+      }
+    });
+    this.setActive(index, true);
+  };
+
+  Collapsible.prototype.close = function (index) {
+    this.setActive(index, false);
+  };
+
+  Collapsible.prototype.setActive = function (index, active) {
+    var li = this.el.children[index];
+    if (!li) {
+      return;
+    }
+    li.classList.toggle('active', active);
+    var header = li.querySelector('.collapsible-header');
+    if (header) {
+      header.classList.toggle('active', active);
+    }
+  };
+
+  window.M = {
+    Collapsible: {
+      init: function (elems) {
+        return Array.prototype.map.call(elems, function (el) {
+          return new Collapsible(el);
+        });
+      },
+      getInstance: function (el) {
+        return el.dynositeCollapsible;
+      }
+    }
+  };
+
+  document.addEventListener('DOMContentLoaded', function () {
+    Array.prototype.forEach.call(document.querySelectorAll('.material-icons'), function (el) {
+      var icon = icons[el.textContent.trim()];
+      if (icon) {
+        el.textContent = icon;
+      }
+    });
+  });
+})();
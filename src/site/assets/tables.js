@@ -0,0 +1,68 @@
+// Sorting of the tables with the `sortable` class by clicking their headers.
+// Cells are compared by their `data-value` attribute when present, and by their text otherwise.
+(function () {
+  function value(row, index) {
+    var cell = row.cells[index];
+    if (!cell) {
+      return '';
+    }
+    var data = cell.getAttribute('data-value');
+    if (data !== null && data !== '' && !isNaN(Number(data))) {
+      return Number(data);
+    }
+    return cell.textContent.trim();
+  }
+
+  function compare(a, b) {
+    if (typeof a === 'number' && typeof b === 'number') {
+      return a - b;
+    }
+    return String(a).localeCompare(String(b));
+  }
+
+  function sort(table, index, descending) {
+    var body = table.tBodies[0];
+    var rows = Array.prototype.slice.call(body.rows);
+    rows.sort(function (a, b) {
+      var order = compare(value(a, index), value(b, index));
+      return descending ? -order : order;
+    });
+    rows.forEach(function (row) {
+      body.appendChild(row);
+    });
+  }
+
+  function init(table) {
+    if (table.getAttribute('data-sortable-ready') || !table.tHead || !table.tBodies.length) {
+      return;
+    }
+    table.setAttribute('data-sortable-ready', 'true');
+
+    var headers = Array.prototype.slice.call(table.tHead.rows[0].cells);
+    headers.forEach(function (header, index) {
+      header.addEventListener('click', function () {
+        // Numbers are sorted largest first on the first click, text alphabetically
+        var current = header.getAttribute('aria-sort');
+        var descending = current
+          ? current === 'ascending'
+          : typeof value(table.tBodies[0].rows[0] || { cells: [] }, index) === 'number';
+
+        headers.forEach(function (other) {
+          other.removeAttribute('aria-sort');
+        });
+        header.setAttribute('aria-sort', descending ? 'descending' : 'ascending');
+        sort(table, index, descending);
+      });
+    });
+  }
+
+  function initAll() {
+    Array.prototype.forEach.call(document.querySelectorAll('table.sortable'), init);
+  }
+
+  if (document.readyState === 'loading') {
+    document.addEventListener('DOMContentLoaded', initAll);
+  } else {
+    initAll();
+  }
+})();
@@ -0,0 +1,235 @@
+use std::{collections::BTreeMap, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{warn, Error, Result},
+    site::palette::PhaseColors,
+    types::{Benchmark, BenchmarkFrame, Benchmarks},
+    wrap,
+};
+
+/// The metrics of the frames rendered by the interactive charts, with their titles
+#[allow(clippy::cast_precision_loss)]
+pub const CHART_METRICS: [(&str, &str, fn(&BenchmarkFrame) -> f64); 7] = [
+    ("cpu_usage", "CPU Usage", |frame| f64::from(frame.cpu_usage)),
+    ("memory_usage", "Memory Usage", |frame| {
+        frame.memory_usage as f64
+    }),
+    ("virtual_memory_usage", "Virtual Memory Usage", |frame| {
+        frame.virtual_memory_usage as f64
+    }),
+    (
+        "disk_total_written_bytes",
+        "Disk Total Written Bytes",
+        |frame| frame.disk_total_written_bytes as f64,
+    ),
+    ("disk_written_bytes", "Disk Written Bytes", |frame| {
+        frame.disk_written_bytes as f64
+    }),
+    ("disk_total_read_bytes", "Disk Total Read Bytes", |frame| {
+        frame.disk_total_read_bytes as f64
+    }),
+    ("disk_read_bytes", "Disk Read Bytes", |frame| {
+        frame.disk_read_bytes as f64
+    }),
+];
+
+/// The frames of a benchmark as columns, written next to the plots and embedded in the benchmark page
+#[derive(Serialize, Deserialize)]
+pub struct Series {
+    /// The time of every frame in milliseconds since the start of the benchmark
+    pub time: Vec<u64>,
+    /// The value of every frame per metric
+    pub metrics: BTreeMap<String, Vec<f64>>,
+    /// The phases of the benchmark
+    pub phases: Vec<Phase>,
+}
+
+/// A phase of a benchmark in milliseconds since the start of the first phase
+#[derive(Serialize, Deserialize)]
+pub struct Phase {
+    pub name: String,
+    pub start: u64,
+    pub end: u64,
+    /// The `#rrggbb` colour of the phase
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
+
+impl Series {
+    /// Collect the series of a benchmark
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the frames of the benchmark can't be read or their timestamps can't be
+    /// converted.
+    pub fn new(benchmark: &Benchmark, colors: &PhaseColors) -> Result<Self> {
+        let frames = benchmark
+            .frames
+            .lock()
+            .map_err(|_| {
+                wrap!(Error::MissingFrames {
+                    benchmark: benchmark.name.clone(),
+                })
+            })?
+            .clone();
+
+        let time = frames
+            .iter()
+            .map(|frame| u64::try_from(frame.relative_timestamp.as_millis()))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| wrap!(e.into()))?;
+
+        let metrics = CHART_METRICS
+            .iter()
+            .map(|(metric, _, value)| ((*metric).to_string(), frames.iter().map(value).collect()))
+            .collect();
+
+        // Phases are normalized to the start of the first phase, like in the plots
+        let min_start_time = benchmark
+            .phases
+            .iter()
+            .filter_map(|phase| phase.start_time)
+            .min()
+            .unwrap_or_default();
+
+        let colors = colors.for_phases(benchmark.phases.iter().map(|phase| phase.name.as_str()));
+
+        let mut phases = vec![];
+        for phase in &benchmark.phases {
+            let (Some(start_time), Some(end_time)) = (phase.start_time, phase.end_time) else {
+                continue;
+            };
+
+            phases.push(Phase {
+                name: phase.name.clone(),
+                start: u64::try_from(start_time.saturating_sub(min_start_time).as_millis())
+                    .map_err(|e| wrap!(e.into()))?,
+                end: u64::try_from(end_time.saturating_sub(min_start_time).as_millis())
+                    .map_err(|e| wrap!(e.into()))?,
+                color: Some(colors.hex(&phase.name)),
+            });
+        }
+
+        Ok(Self {
+            time,
+            metrics,
+            phases,
+        })
+    }
+}
+
+/// Write the series of every benchmark to `<output_file>_<benchmark name>.json`, skipping the
+/// benchmarks whose series can't be collected with a warning
+///
+/// # Errors
+///
+/// Returns an error if a series file can't be written.
+pub fn write_series(
+    benchmarks: &Benchmarks,
+    output_file: &str,
+    colors: &PhaseColors,
+) -> Result<Vec<std::path::PathBuf>> {
+    let mut files = vec![];
+
+    for benchmark in &benchmarks.benchmarks {
+        let file = std::path::PathBuf::from(format!("{output_file}_{}.json", benchmark.name));
+        let series = match Series::new(benchmark, colors) {
+            Ok(series) => series,
+            Err(e) => {
+                warn(&format!("Skipping the charts of `{}`", benchmark.name), &e);
+                continue;
+            }
+        };
+        std::fs::write(
+            &file,
+            serde_json::to_string(&series).map_err(|e| wrap!(e.into()))?,
+        )
+        .map_err(|e| wrap!(e.into()))?;
+        files.push(file);
+    }
+
+    Ok(files)
+}
+
+/// Read the series written by `write_series`, as JSON that can be embedded in a `<script>` element
+///
+/// # Errors
+///
+/// Returns an error if the series file can't be read.
+pub fn read_embeddable(file: &Path) -> Result<String> {
+    let series = std::fs::read_to_string(file).map_err(|e| wrap!(e.into()))?;
+
+    // The series is plain JSON, so only a closing tag can end the script element early
+    Ok(series.replace("</", "<\\/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, time::Duration};
+
+    use super::*;
+    use crate::{site::fixtures, types::BenchmarkPhase};
+
+    #[test]
+    fn write_series_writes_the_frames_of_every_benchmark() {
+        let folder = tempfile::tempdir().unwrap();
+        let output_file = folder.path().join("plots").to_string_lossy().to_string();
+        let mut benchmark = fixtures::benchmark("hashing");
+        benchmark.phases[0].start_time = Some(Duration::from_millis(200));
+        benchmark.phases.push(BenchmarkPhase {
+            name: "unfinished".to_string(),
+            start_time: Some(Duration::from_millis(300)),
+            end_time: None,
+        });
+
+        let files = write_series(
+            &fixtures::benchmarks(vec![benchmark, fixtures::benchmark("sorting")]),
+            &output_file,
+            &PhaseColors::load(None).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            files,
+            vec![
+                PathBuf::from(format!("{output_file}_hashing.json")),
+                PathBuf::from(format!("{output_file}_sorting.json")),
+            ]
+        );
+        let series: Series =
+            serde_json::from_str(&std::fs::read_to_string(&files[0]).unwrap()).unwrap();
+        assert_eq!(series.time, vec![0, 500]);
+        assert_eq!(series.metrics.len(), CHART_METRICS.len());
+        assert_eq!(series.metrics["cpu_usage"], vec![50.0, 50.0]);
+        assert_eq!(series.metrics["memory_usage"], vec![1000.0, 1000.0]);
+        // The unfinished phase is left out and the others start from the first phase
+        assert_eq!(series.phases.len(), 1);
+        assert_eq!(series.phases[0].name, "compile to ast");
+        assert_eq!((series.phases[0].start, series.phases[0].end), (0, 800));
+        assert_eq!(series.phases[0].color.as_deref(), Some("#ff0000"));
+    }
+
+    #[test]
+    fn read_embeddable_escapes_the_closing_tags() {
+        let folder = tempfile::tempdir().unwrap();
+        let output_file = folder.path().join("plots").to_string_lossy().to_string();
+        let mut benchmark = fixtures::benchmark("hashing");
+        benchmark.phases[0].name = "</script><script>alert(1)</script>".to_string();
+
+        let files = write_series(
+            &fixtures::benchmarks(vec![benchmark]),
+            &output_file,
+            &PhaseColors::load(None).unwrap(),
+        )
+        .unwrap();
+        let embeddable = read_embeddable(&files[0]).unwrap();
+
+        assert!(!embeddable.contains("</"));
+        assert!(embeddable.contains("<\\/script><script>alert(1)<\\/script>"));
+        // The escaped JSON still holds the same series
+        let series: Series = serde_json::from_str(&embeddable).unwrap();
+        assert_eq!(series.phases[0].name, "</script><script>alert(1)</script>");
+    }
+}
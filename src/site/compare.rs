@@ -0,0 +1,224 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::site::{
+    diagnostics::Diagnostic,
+    execution::parse_json_benchmarks,
+    hyperfine::Significance,
+    regression::METRICS,
+    report::{metrics, MetricReport},
+    stats::{self, added_and_removed, find_by_key, find_match},
+};
+use crate::{error::Result, wrap};
+use serde::{Deserialize, Serialize};
+
+/// An ad-hoc comparison of two runs, without an execution in the site
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Comparison {
+    pub previous: String,
+    pub current: String,
+    pub benchmarks: Vec<BenchmarkComparison>,
+    /// The names of the benchmarks that only ran in the current run
+    pub new_benchmarks: Vec<String>,
+    /// The names of the benchmarks that only ran in the previous run
+    pub removed_benchmarks: Vec<String>,
+}
+
+/// The comparison of a single benchmark between the two runs
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BenchmarkComparison {
+    pub name: String,
+    pub path: String,
+    pub metrics: Vec<MetricReport>,
+    pub hyperfine: Option<Significance>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Comparison {
+    /// Compare two run files
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a run file can't be read or parsed.
+    pub fn new(previous: &Path, current: &Path) -> Result<Self> {
+        let previous_benchmarks = parse_json_benchmarks(previous).map_err(|e| wrap!(e))?;
+        let current_benchmarks = parse_json_benchmarks(current).map_err(|e| wrap!(e))?;
+
+        let mut benchmarks = Vec::new();
+        for (path, stats) in stats::compare(&previous_benchmarks, &current_benchmarks).0 {
+            let Some(current) = find_by_key(&current_benchmarks, &path) else {
+                continue;
+            };
+            let Some(previous) = find_match(&previous_benchmarks, current) else {
+                continue;
+            };
+
+            benchmarks.push(BenchmarkComparison {
+                name: current.name.clone(),
+                metrics: metrics(previous, current, &stats.diagnostics),
+                hyperfine: stats.hyperfine,
+                diagnostics: stats.diagnostics,
+                path,
+            });
+        }
+
+        let (added, removed) = added_and_removed(&previous_benchmarks, &current_benchmarks);
+        let names = |benchmarks: Vec<&crate::types::Benchmark>| {
+            benchmarks
+                .iter()
+                .map(|benchmark| benchmark.name.clone())
+                .collect()
+        };
+
+        Ok(Self {
+            previous: previous.display().to_string(),
+            current: current.display().to_string(),
+            benchmarks,
+            new_benchmarks: names(added),
+            removed_benchmarks: names(removed),
+        })
+    }
+
+    /// Format the comparison as a human readable summary
+    #[must_use]
+    pub fn summary(&self) -> String {
+        let mut summary = format!("Comparing {} with {}\n", self.previous, self.current);
+
+        for benchmark in &self.benchmarks {
+            let _ = write!(summary, "\n{}\n", benchmark.name);
+
+            for (metric, title) in &METRICS {
+                let Some(report) = benchmark.metrics.iter().find(|m| m.metric == *metric) else {
+                    let _ = writeln!(summary, "  {title}: n/a");
+                    continue;
+                };
+
+                // Time changes that hyperfine can't tell apart from noise are called out
+                let note = match benchmark.hyperfine.as_ref().filter(|_| *metric == "time") {
+                    Some(significance) if !significance.significant => {
+                        format!(" (not significant, p = {:.3})", significance.p_value)
+                    }
+                    Some(significance) => format!(" (p = {:.3})", significance.p_value),
+                    None => String::new(),
+                };
+
+                let _ = writeln!(
+                    summary,
+                    "  {title}: {:.2} -> {:.2} ({:+.2}%){note}",
+                    report.previous, report.current, report.percentage
+                );
+            }
+
+            for diagnostic in &benchmark.diagnostics {
+                let _ = writeln!(summary, "  Warning: {diagnostic}");
+            }
+        }
+
+        for (title, names) in [
+            ("New benchmarks", &self.new_benchmarks),
+            ("Removed benchmarks", &self.removed_benchmarks),
+        ] {
+            if !names.is_empty() {
+                let _ = write!(summary, "\n{title}: {}\n", names.join(", "));
+            }
+        }
+
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site::fixtures;
+    use serde_json::json;
+    use std::time::Duration;
+
+    /// Compare a run of `hashing` and `sorting` with a run where `hashing` took 10% longer,
+    /// `sorting` lost its asm information and `parsing` was added
+    fn comparison(folder: &Path) -> Comparison {
+        let previous = fixtures::write_run(
+            folder,
+            "previous.json",
+            &fixtures::benchmarks(vec![
+                fixtures::benchmark("hashing"),
+                fixtures::benchmark("sorting"),
+            ]),
+        );
+        let mut hashing = fixtures::benchmark("hashing");
+        hashing.end_time = Some(Duration::from_millis(1100));
+        let mut sorting = fixtures::benchmark("sorting");
+        sorting.asm_information = None;
+        let current = fixtures::write_run(
+            folder,
+            "current.json",
+            &fixtures::benchmarks(vec![hashing, sorting, fixtures::benchmark("parsing")]),
+        );
+
+        Comparison::new(&previous, &current).unwrap()
+    }
+
+    #[test]
+    fn summary_lists_the_changes_of_every_benchmark() {
+        let folder = tempfile::tempdir().unwrap();
+        let comparison = comparison(folder.path());
+
+        let summary = comparison.summary();
+
+        assert!(summary.starts_with(&format!(
+            "Comparing {} with {}\n",
+            folder.path().join("previous.json").display(),
+            folder.path().join("current.json").display()
+        )));
+        assert!(summary.contains(
+            "\nhashing\n  CPU Usage: 50.00 -> 50.00 (+0.00%)\n  Memory Usage: 1000.00 -> 1000.00 (+0.00%)\n"
+        ));
+        assert!(summary.contains("  Time: 1000.00 -> 1100.00 (+10.00%)\n"));
+        assert!(summary.contains("\nsorting\n"));
+        assert!(summary.contains("  Bytecode Size: n/a\n  Data Section Size: n/a\n"));
+        assert!(summary
+            .contains("  Warning: current run: The benchmark `sorting` has no asm information.\n"));
+        assert!(summary.ends_with("\nNew benchmarks: parsing\n"));
+        assert!(!summary.contains("Removed benchmarks"));
+    }
+
+    #[test]
+    fn comparison_serialises_to_json() {
+        let folder = tempfile::tempdir().unwrap();
+
+        let comparison = serde_json::to_value(comparison(folder.path())).unwrap();
+
+        assert_eq!(
+            comparison["previous"],
+            folder.path().join("previous.json").display().to_string()
+        );
+        assert_eq!(comparison["new_benchmarks"], json!(["parsing"]));
+        assert_eq!(comparison["removed_benchmarks"], json!([]));
+
+        let benchmarks = comparison["benchmarks"].as_array().unwrap();
+        assert_eq!(benchmarks.len(), 2);
+        assert_eq!(benchmarks[0]["name"], "hashing");
+        assert_eq!(benchmarks[0]["path"], "/projects/hashing");
+        assert_eq!(benchmarks[0]["hyperfine"], json!(null));
+        assert_eq!(benchmarks[0]["diagnostics"], json!([]));
+        assert!(benchmarks[0]["metrics"]
+            .as_array()
+            .unwrap()
+            .contains(&json!({
+                "metric": "time",
+                "previous": 1000.0,
+                "current": 1100.0,
+                "difference": 100.0,
+                "percentage": 10.0
+            })));
+
+        // The metrics of the missing asm information are left out
+        assert_eq!(benchmarks[1]["name"], "sorting");
+        assert_eq!(benchmarks[1]["diagnostics"][0]["run"], "current");
+        assert!(!benchmarks[1]["metrics"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|metric| metric["metric"] == "bytecode_size"));
+    }
+}
@@ -1,20 +1,97 @@
+use crate::{error::Result, wrap};
+
 /// Represents the data folder in the site
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct Data {
     pub root_folder: std::path::PathBuf,
     pub executions: Vec<crate::site::execution::Execution>,
+    /// The stored run that later executions are compared against
+    #[serde(default)]
+    pub pinned_baseline: Option<std::path::PathBuf>,
 }
 
 impl Data {
     /// Creates a new data folder in the site
-    pub fn new(root_folder: std::path::PathBuf) -> Self {
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data folders can't be created.
+    pub fn new(root_folder: std::path::PathBuf) -> Result<Self> {
         if !root_folder.exists() {
-            std::fs::create_dir_all(&root_folder).expect("Failed to create data folder");
+            std::fs::create_dir_all(&root_folder).map_err(|e| wrap!(e.into()))?;
         }
 
-        Self {
+        Ok(Self {
             root_folder,
             executions: Vec::new(),
+            pinned_baseline: None,
+        })
+    }
+
+    /// Find a stored run from its path or from its file name, stem or timestamp
+    #[must_use]
+    pub fn find_run(&self, run: &str) -> Option<std::path::PathBuf> {
+        let runs: Vec<_> = self
+            .executions
+            .iter()
+            .flat_map(|e| [&e.previous_benchmarks, &e.current_benchmarks])
+            .collect();
+
+        let matches = |path: &std::path::Path| {
+            path == std::path::Path::new(run)
+                || path.file_name().and_then(|n| n.to_str()) == Some(run)
+                || path.file_stem().and_then(|n| n.to_str()) == Some(run)
+        };
+
+        runs.iter()
+            .find(|path| matches(path))
+            .or_else(|| {
+                runs.iter().find(|path| {
+                    path.file_stem()
+                        .and_then(|n| n.to_str())
+                        .is_some_and(|stem| stem.ends_with(run))
+                })
+            })
+            .map(|path| (*path).clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site::fixtures;
+    use crate::types::PRInformation;
+
+    #[test]
+    fn find_run_resolves_the_stored_runs() {
+        let folder = tempfile::tempdir().unwrap();
+        let mut data = Data::new(folder.path().join("data")).unwrap();
+        let run = fixtures::benchmarks(vec![fixtures::benchmark("hashing")]);
+        let execution = fixtures::execution(
+            folder.path(),
+            "benchmarks_2024-10-22_10:00:00",
+            &run,
+            &run,
+            PRInformation::default(),
+        );
+        data.executions.push(execution.clone());
+
+        for run in [
+            "benchmarks_2024-10-22_10:00:00.json",
+            "benchmarks_2024-10-22_10:00:00",
+            "2024-10-22_10:00:00",
+            execution.current_benchmarks.to_str().unwrap(),
+        ] {
+            assert_eq!(
+                data.find_run(run).as_ref(),
+                Some(&execution.current_benchmarks),
+                "{run}"
+            );
         }
+        assert_eq!(
+            data.find_run("previous").as_ref(),
+            Some(&execution.previous_benchmarks)
+        );
+        assert_eq!(data.find_run("2024-10-23_10:00:00"), None);
     }
 }
@@ -0,0 +1,186 @@
+use crate::error::Error;
+use crate::types::Benchmark;
+use serde::{Deserialize, Serialize};
+
+/// The metrics of `regression::METRICS` that are computed from the frames of a benchmark
+const FRAME_METRICS: [&str; 8] = [
+    "cpu_usage",
+    "memory_usage",
+    "mean_memory_usage",
+    "virtual_memory_usage",
+    "disk_total_written_bytes",
+    "disk_written_bytes",
+    "disk_total_read_bytes",
+    "disk_read_bytes",
+];
+
+/// The run of the diagnostics of problems comparing both runs, like their flamegraphs
+pub const BOTH_RUNS: &str = "previous and current";
+
+/// A problem with the data of one run of a benchmark, shown on its page instead of failing the site
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    /// The run with the problem, `previous`, `current` or `BOTH_RUNS`.
+    pub run: String,
+    /// The metrics of `regression::METRICS` that can't be compared because of the problem.
+    pub metrics: Vec<String>,
+    /// A description of the problem.
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Describe an error with the data of a run and the metrics it affects
+    pub fn new(run: &str, metrics: &[&str], error: &Error) -> Self {
+        Self {
+            run: run.to_string(),
+            metrics: metrics.iter().map(ToString::to_string).collect(),
+            message: error.root_cause().to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} run: {}", self.run, self.message)
+    }
+}
+
+/// Check both runs of a benchmark for missing asm information, asm values, frames and timings
+#[must_use]
+pub fn diagnose(previous: &Benchmark, current: &Benchmark) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for (run, benchmark) in [("previous", previous), ("current", current)] {
+        let name = || benchmark.name.clone();
+
+        match &benchmark.asm_information {
+            None => diagnostics.push(Diagnostic::new(
+                run,
+                &["bytecode_size", "data_section_size"],
+                &Error::MissingAsmInfo { benchmark: name() },
+            )),
+            Some(asm_information) => {
+                for (metric, value) in [
+                    ("bytecode_size", &asm_information["bytecode_size"]),
+                    (
+                        "data_section_size",
+                        &asm_information["data_section"]["size"],
+                    ),
+                ] {
+                    if value.as_f64().is_none() {
+                        diagnostics.push(Diagnostic::new(
+                            run,
+                            &[metric],
+                            &Error::MissingAsmValue {
+                                benchmark: name(),
+                                value: metric.to_string(),
+                            },
+                        ));
+                    }
+                }
+            }
+        }
+
+        if benchmark
+            .frames
+            .lock()
+            .map_or(true, |frames| frames.is_empty())
+        {
+            diagnostics.push(Diagnostic::new(
+                run,
+                &FRAME_METRICS,
+                &Error::MissingFrames { benchmark: name() },
+            ));
+        }
+
+        if benchmark.start_time.is_none() || benchmark.end_time.is_none() {
+            diagnostics.push(Diagnostic::new(
+                run,
+                &["time"],
+                &Error::MissingTimings { benchmark: name() },
+            ));
+        }
+
+        let phases = benchmark
+            .phases
+            .iter()
+            .filter(|phase| phase.start_time.is_none() || phase.end_time.is_none())
+            .map(|phase| phase.name.clone())
+            .collect::<Vec<_>>();
+        if !phases.is_empty() {
+            diagnostics.push(Diagnostic::new(
+                run,
+                &[],
+                &Error::MissingPhaseTimings {
+                    benchmark: name(),
+                    phases,
+                },
+            ));
+        }
+    }
+
+    diagnostics
+}
+
+/// Check whether a metric can be compared despite the diagnostics of a benchmark
+#[must_use]
+pub fn is_available(diagnostics: &[Diagnostic], metric: &str) -> bool {
+    !diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.metrics.iter().any(|m| m == metric))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site::fixtures;
+
+    #[test]
+    fn complete_benchmarks_have_no_diagnostics() {
+        let hashing = fixtures::benchmark("hashing");
+
+        assert!(diagnose(&hashing, &hashing).is_empty());
+    }
+
+    #[test]
+    fn diagnose_reports_the_missing_data_of_each_run() {
+        let previous = fixtures::benchmark("hashing");
+        previous.frames.lock().unwrap().clear();
+        let mut current = fixtures::benchmark("hashing");
+        current.asm_information = None;
+        current.end_time = None;
+        current.phases[0].start_time = None;
+
+        let diagnostics = diagnose(&previous, &current);
+
+        let messages: Vec<_> = diagnostics.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            messages,
+            [
+                "previous run: The frames of the benchmark `hashing` are missing.",
+                "current run: The benchmark `hashing` has no asm information.",
+                "current run: The benchmark `hashing` has no start or end time.",
+                "current run: The phases `compile to ast` of the benchmark `hashing` have no start or end time.",
+            ]
+        );
+        assert_eq!(diagnostics[0].metrics.len(), FRAME_METRICS.len());
+        assert_eq!(
+            diagnostics[1].metrics,
+            ["bytecode_size", "data_section_size"]
+        );
+        assert_eq!(diagnostics[2].metrics, ["time"]);
+        assert!(diagnostics[3].metrics.is_empty());
+    }
+
+    #[test]
+    fn metrics_affected_by_a_diagnostic_are_unavailable() {
+        let mut current = fixtures::benchmark("hashing");
+        current.asm_information = None;
+        let diagnostics = diagnose(&fixtures::benchmark("hashing"), &current);
+
+        assert!(!is_available(&diagnostics, "bytecode_size"));
+        assert!(!is_available(&diagnostics, "data_section_size"));
+        assert!(is_available(&diagnostics, "time"));
+        assert!(is_available(&[], "bytecode_size"));
+    }
+}
@@ -1,11 +1,23 @@
 use std::path::PathBuf;
 
 use super::{
+    assets,
     data::Data,
-    execution::Execution,
-    html::{generate, generate_error_page},
+    execution::{parse_json_benchmarks, Execution},
+    html::{benchmark_page, execution_page, generate},
+    palette::PhaseColors,
+    stats::{find_by_key, find_match},
+    template::Templates,
+    trend,
 };
-use crate::{error::Result, wrap};
+use crate::{
+    cli::PlotFormat,
+    error::{warn, Result},
+    wrap,
+};
+
+/// The file in the root folder of a site that stores the site
+const SITE_JSON: &str = "site.json";
 
 /// Represents a `DynoSite`
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -19,65 +31,347 @@ pub struct DynoSite {
 
 impl DynoSite {
     /// Initialize a new site either from a file or from scratch
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the existing `site.json` can't be read or parsed, or if the site folder
+    /// can't be created.
     pub fn init(site_name: &str) -> crate::error::Result<Self> {
-        let site_json = PathBuf::from(site_name).with_extension("json");
-        let site = Self::new(site_name);
-
-        if site.root_folder.join(&site_json).exists() {
-            Ok(serde_json::from_str(
-                std::fs::read_to_string(
-                    site.root_folder
-                        .join(site_json)
-                        .display()
-                        .to_string()
-                        .as_str(),
-                )
-                .map_err(|e| wrap!(e.into()))?
-                .as_str(),
-            )
-            .map_err(|e| wrap!(e.into()))?)
+        if PathBuf::from(site_name).join(SITE_JSON).exists() {
+            Ok(Self::load(site_name).map_err(|e| wrap!(e))?)
         } else {
-            Ok(site)
+            Ok(Self::new(site_name).map_err(|e| wrap!(e))?)
+        }
+    }
+
+    /// Load an existing site from its `site.json`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `site.json` of the site can't be read or parsed.
+    pub fn load(site_name: &str) -> Result<Self> {
+        let site_json = PathBuf::from(site_name).join(SITE_JSON);
+        if !site_json.exists() {
+            return Err(wrap!(format!(
+                "The site `{site_name}` has no `{SITE_JSON}`, add an execution first"
+            )
+            .into())
+            .into());
         }
+
+        Ok(
+            serde_json::from_str(&std::fs::read_to_string(site_json).map_err(|e| wrap!(e.into()))?)
+                .map_err(|e| wrap!(e.into()))?,
+        )
     }
 
     /// Create a new site with default values
-    pub fn new(site_name: &str) -> Self {
-        Self {
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the site folder or its data folders can't be created.
+    pub fn new(site_name: &str) -> Result<Self> {
+        Ok(Self {
             root_folder: PathBuf::from(site_name),
             data_folder: PathBuf::from(site_name).join("data"),
             index_html: PathBuf::from(site_name).join("index.html"),
             error_html: PathBuf::from(site_name).join("error.html"),
-            data: Data::new(PathBuf::from(site_name).join("data")),
-        }
+            data: Data::new(PathBuf::from(site_name).join("data")).map_err(|e| wrap!(e))?,
+        })
     }
 
     /// Add an execution to the site
-    pub fn add_execution(&mut self, execution: &Execution, data_only: bool) -> Result<()> {
-        self.data.executions.push(execution.clone());
+    /// An execution with the same identifier is only replaced, in its place, when forced
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the site has an execution with the same identifier and the addition
+    /// isn't forced, or if the pages of the site can't be generated or written.
+    pub fn add_execution(
+        &mut self,
+        execution: &Execution,
+        force: bool,
+        data_only: bool,
+        templates: &Templates,
+        colors: &PhaseColors,
+    ) -> Result<()> {
+        let identifier = execution.identifier().map_err(|e| wrap!(e))?;
+        let existing = self
+            .data
+            .executions
+            .iter()
+            .position(|execution| execution.identifier().is_ok_and(|id| id == identifier));
 
-        Self::generate_html(self, data_only).map_err(|e| wrap!(e))?;
+        match existing {
+            Some(_) if !force => {
+                return Err(wrap!(format!(
+                    "The execution `{identifier}` already exists in the site, pass `--force` to replace it"
+                )
+                .into())
+                .into());
+            }
+            Some(index) => self.data.executions[index] = execution.clone(),
+            None => self.data.executions.push(execution.clone()),
+        }
+
+        Self::generate_html(self, data_only, execution.plot_format, templates, colors)
+            .map_err(|e| wrap!(e))?;
 
         Ok(())
     }
 
+    /// Recompute the stats and regenerate the plots of every execution, then regenerate the site
+    /// Executions that fail to rebuild are skipped with a warning and keep their previous files
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pages of the site can't be generated or written.
+    pub fn rebuild(
+        &mut self,
+        data_only: bool,
+        templates: &Templates,
+        colors: &PhaseColors,
+    ) -> Result<()> {
+        for execution in &mut self.data.executions {
+            if let Err(e) = execution.rebuild(data_only, colors) {
+                warn(
+                    &format!(
+                        "Failed to rebuild the execution `{}`",
+                        execution.root_folder.display()
+                    ),
+                    &e,
+                );
+            }
+        }
+
+        Self::generate_html(self, data_only, self.plot_format(), templates, colors)
+            .map_err(|e| wrap!(e))?;
+
+        Ok(())
+    }
+
+    /// Remove an execution with its data and pages from the site, then regenerate the site
+    /// A pinned baseline stored with the execution moves to the copy of its run kept by a remaining
+    /// execution, and is only unpinned when no remaining execution uses the run
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no execution has the identifier, if its files can't be removed, or if the
+    /// pages of the site can't be generated or written.
+    pub fn remove_execution(
+        &mut self,
+        identifier: &str,
+        data_only: bool,
+        templates: &Templates,
+        colors: &PhaseColors,
+    ) -> Result<Execution> {
+        let index = self
+            .data
+            .executions
+            .iter()
+            .position(|execution| execution.identifier().is_ok_and(|id| id == identifier))
+            .ok_or_else(|| {
+                wrap!(format!("Failed to find the execution `{identifier}` in the site").into())
+            })?;
+        let execution = self.data.executions.remove(index);
+
+        if let Some(pinned) = self
+            .data
+            .pinned_baseline
+            .take_if(|pinned| pinned.starts_with(&execution.root_folder))
+        {
+            // Every execution keeps a copy of its runs under the same file name
+            self.data.pinned_baseline = self
+                .data
+                .executions
+                .iter()
+                .flat_map(|execution| {
+                    [
+                        &execution.previous_benchmarks,
+                        &execution.current_benchmarks,
+                    ]
+                })
+                .find(|run| run.file_name() == pinned.file_name())
+                .cloned();
+
+            if self.data.pinned_baseline.is_none() {
+                warn(
+                    "Unpinning the baseline",
+                    &format!("No remaining execution uses the run `{}`", pinned.display()).into(),
+                );
+            }
+        }
+
+        let page_folder = self.root_folder.join(execution_page(identifier));
+        for folder in [
+            execution.root_folder.as_path(),
+            page_folder.parent().unwrap_or(&page_folder),
+        ] {
+            if folder.exists() {
+                std::fs::remove_dir_all(folder).map_err(|e| wrap!(e.into()))?;
+            }
+        }
+
+        Self::generate_html(self, data_only, self.plot_format(), templates, colors)
+            .map_err(|e| wrap!(e))?;
+
+        Ok(execution)
+    }
+
+    /// Check that the executions of the site and their files are consistent, returning the
+    /// problems found
+    #[must_use]
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mut identifiers = Vec::new();
+
+        for execution in &self.data.executions {
+            let identifier = match execution.identifier() {
+                Ok(identifier) => identifier,
+                Err(e) => {
+                    problems.push(e.root_cause().to_string());
+                    continue;
+                }
+            };
+
+            if identifiers.contains(&identifier) {
+                problems.push(format!(
+                    "{identifier}: The execution is stored more than once"
+                ));
+            }
+            identifiers.push(identifier);
+
+            if !self.root_folder.join(execution_page(identifier)).is_file() {
+                problems.push(format!(
+                    "{identifier}: The page of the execution is missing, run `rebuild`"
+                ));
+            }
+
+            problems.extend(
+                execution
+                    .validate()
+                    .into_iter()
+                    .chain(self.validate_benchmark_pages(execution, identifier))
+                    .map(|problem| format!("{identifier}: {problem}")),
+            );
+        }
+
+        if let Some(pinned_baseline) = &self.data.pinned_baseline {
+            if !pinned_baseline.is_file() {
+                problems.push(format!(
+                    "The pinned baseline `{}` is missing",
+                    pinned_baseline.display()
+                ));
+            }
+        }
+
+        problems
+    }
+
+    /// Check that every benchmark of the stats of an execution has a page, the stats and runs that
+    /// can't be read are reported by `Execution::validate`
+    fn validate_benchmark_pages(&self, execution: &Execution, identifier: &str) -> Vec<String> {
+        let (Ok(stats_collection), Ok(previous_benchmarks), Ok(current_benchmarks)) = (
+            execution.stats_collection(),
+            parse_json_benchmarks(&execution.previous_benchmarks),
+            parse_json_benchmarks(&execution.current_benchmarks),
+        ) else {
+            return vec![];
+        };
+
+        let mut problems = Vec::new();
+
+        // The pages of the benchmarks that don't match both runs are not generated
+        for (key, _) in &stats_collection.0 {
+            let Some(current_benchmark) = find_by_key(&current_benchmarks, key) else {
+                problems.push(format!(
+                    "The stats of `{key}` match no benchmark of the current run"
+                ));
+                continue;
+            };
+            if find_match(&previous_benchmarks, current_benchmark).is_none() {
+                problems.push(format!(
+                    "The stats of `{key}` match no benchmark of the previous run"
+                ));
+                continue;
+            }
+
+            let page = self
+                .root_folder
+                .join(benchmark_page(identifier, &current_benchmark.name));
+            if !page.is_file() {
+                problems.push(format!(
+                    "The page of the benchmark `{}` is missing, run `rebuild`",
+                    current_benchmark.name
+                ));
+            }
+        }
+
+        problems
+    }
+
     /// Write the site to a file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the site can't be serialized or written.
     pub fn store(&self) -> Result<()> {
         let site = serde_json::to_string(&self).map_err(|e| wrap!(e.into()))?;
-        std::fs::write(self.root_folder.join("site.json"), site).map_err(|e| wrap!(e.into()))?;
+        std::fs::write(self.root_folder.join(SITE_JSON), site).map_err(|e| wrap!(e.into()))?;
         Ok(())
     }
 
+    /// The plot format of the latest execution, used for the trend plots
+    fn plot_format(&self) -> PlotFormat {
+        self.data
+            .executions
+            .last()
+            .map_or(PlotFormat::default(), |execution| execution.plot_format)
+    }
+
     /// Generate the HTML for the site and write the files to disk
-    pub fn generate_html(site: &DynoSite, data_only: bool) -> Result<()> {
-        // Generate the index HTML
-        let index_html = generate(site, data_only).map_err(|e| wrap!(e))?;
+    /// The trend plots are rendered in the plot format of the latest execution
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a page can't be rendered or written.
+    pub fn generate_html(
+        site: &DynoSite,
+        data_only: bool,
+        plot_format: PlotFormat,
+        templates: &Templates,
+        colors: &PhaseColors,
+    ) -> Result<()> {
+        // Collect the history of every benchmark across all executions and generate its plots, a
+        // failure leaves the trend history out of the site instead of failing the whole site
+        let mut trends = trend::collect(site);
+        if !data_only {
+            if let Err(e) =
+                trend::generate_plots(&trends, &site.root_folder.join("trend"), plot_format)
+            {
+                warn("Skipping the trend history", &e);
+                trends.clear();
+            }
+        }
 
-        // Generate the HTML for the error page
-        let error_html = generate_error_page();
+        // Copy the assets referenced by the pages
+        assets::write(&site.root_folder).map_err(|e| wrap!(e))?;
+
+        // Generate the index, execution and benchmark pages and write them to disk
+        for page in generate(site, &trends, data_only, plot_format, colors).map_err(|e| wrap!(e))? {
+            let html = templates
+                .render_page(&page.title, &page.content, &page.path)
+                .map_err(|e| wrap!(e))?;
+
+            let path = site.root_folder.join(&page.path);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| wrap!(e.into()))?;
+            }
+            std::fs::write(path, html).map_err(|e| wrap!(e.into()))?;
+        }
 
-        // Write the index HTML to a file
-        std::fs::write(&site.index_html, &index_html).map_err(|e| wrap!(e.into()))?;
+        // Generate the HTML for the error page
+        let error_html = templates.render_error().map_err(|e| wrap!(e))?;
 
         // Write the error HTML to a file
         std::fs::write(&site.error_html, &error_html).map_err(|e| wrap!(e.into()))?;
@@ -85,3 +379,199 @@ impl DynoSite {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site::fixtures;
+    use crate::types::PRInformation;
+
+    const IDENTIFIERS: [&str; 2] = [
+        "benchmarks_2024-10-22_10:00:00",
+        "benchmarks_2024-10-23_10:00:00",
+    ];
+
+    fn execution(folder: &std::path::Path, identifier: &str) -> Execution {
+        let run = fixtures::benchmarks(vec![fixtures::benchmark("hashing")]);
+        fixtures::execution(folder, identifier, &run, &run, PRInformation::default())
+    }
+
+    fn generators() -> (Templates, PhaseColors) {
+        (
+            Templates::new(None, false).unwrap(),
+            PhaseColors::load(None).unwrap(),
+        )
+    }
+
+    #[test]
+    fn failed_trends_leave_the_trend_history_out() {
+        let folder = tempfile::tempdir().unwrap();
+        let mut site = DynoSite::new(folder.path().to_str().unwrap()).unwrap();
+        let (templates, colors) = generators();
+
+        // The trend plots can't be written when a file takes the place of their folder
+        std::fs::write(folder.path().join("trend"), "").unwrap();
+        site.add_execution(
+            &execution(folder.path(), IDENTIFIERS[0]),
+            false,
+            false,
+            &templates,
+            &colors,
+        )
+        .unwrap();
+
+        let index = std::fs::read_to_string(&site.index_html).unwrap();
+        assert!(!index.contains("Trend History"));
+        assert!(index.contains("Run Collection"));
+    }
+
+    #[test]
+    fn remove_execution_deletes_its_data_and_pages() {
+        let folder = tempfile::tempdir().unwrap();
+        let mut site = DynoSite::new(folder.path().to_str().unwrap()).unwrap();
+        let (templates, colors) = generators();
+        let executions = IDENTIFIERS.map(|identifier| {
+            let execution = execution(folder.path(), identifier);
+            site.add_execution(&execution, false, true, &templates, &colors)
+                .unwrap();
+            execution
+        });
+        let page = site.root_folder.join(execution_page(IDENTIFIERS[0]));
+        assert!(page.is_file());
+
+        let removed = site
+            .remove_execution(IDENTIFIERS[0], true, &templates, &colors)
+            .unwrap();
+
+        assert_eq!(removed.root_folder, executions[0].root_folder);
+        assert_eq!(site.data.executions.len(), 1);
+        assert_eq!(
+            site.data.executions[0].identifier().unwrap(),
+            IDENTIFIERS[1]
+        );
+        assert!(!executions[0].root_folder.exists());
+        assert!(!page.parent().unwrap().exists());
+        assert!(executions[1].root_folder.exists());
+        assert_eq!(site.data.pinned_baseline, None);
+        assert!(!std::fs::read_to_string(&site.index_html)
+            .unwrap()
+            .contains(IDENTIFIERS[0]));
+
+        assert!(site
+            .remove_execution(IDENTIFIERS[0], true, &templates, &colors)
+            .is_err());
+    }
+
+    #[test]
+    fn remove_execution_unpins_its_runs() {
+        let folder = tempfile::tempdir().unwrap();
+        let mut site = DynoSite::new(folder.path().to_str().unwrap()).unwrap();
+        let (templates, colors) = generators();
+        let execution = execution(folder.path(), IDENTIFIERS[0]);
+        site.add_execution(&execution, false, true, &templates, &colors)
+            .unwrap();
+        site.data.pinned_baseline = Some(execution.previous_benchmarks.clone());
+
+        site.remove_execution(IDENTIFIERS[0], true, &templates, &colors)
+            .unwrap();
+
+        assert!(site.data.executions.is_empty());
+        assert_eq!(site.data.pinned_baseline, None);
+    }
+
+    #[test]
+    fn rebuild_restores_a_deleted_stats_file() {
+        let folder = tempfile::tempdir().unwrap();
+        let mut site = DynoSite::new(folder.path().to_str().unwrap()).unwrap();
+        let (templates, colors) = generators();
+        let execution = execution(folder.path(), IDENTIFIERS[0]);
+        site.add_execution(&execution, false, true, &templates, &colors)
+            .unwrap();
+        let stats_file = execution.stats_file().unwrap();
+        let stats = std::fs::read_to_string(&stats_file).unwrap();
+
+        std::fs::remove_file(&stats_file).unwrap();
+        assert_eq!(site.validate().len(), 1);
+        site.rebuild(true, &templates, &colors).unwrap();
+
+        assert_eq!(std::fs::read_to_string(&stats_file).unwrap(), stats);
+        assert_eq!(site.validate(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn add_execution_only_replaces_an_execution_when_forced() {
+        let folder = tempfile::tempdir().unwrap();
+        let mut site = DynoSite::new(folder.path().to_str().unwrap()).unwrap();
+        let (templates, colors) = generators();
+        let execution = execution(folder.path(), IDENTIFIERS[0]);
+
+        site.add_execution(&execution, false, true, &templates, &colors)
+            .unwrap();
+        let error = site
+            .add_execution(&execution, false, true, &templates, &colors)
+            .unwrap_err();
+        assert!(error.root_cause().to_string().contains("pass `--force`"));
+        assert_eq!(site.data.executions.len(), 1);
+
+        site.add_execution(&execution, true, true, &templates, &colors)
+            .unwrap();
+        assert_eq!(site.data.executions.len(), 1);
+    }
+
+    #[test]
+    fn remove_execution_keeps_the_pinned_run_of_a_remaining_execution() {
+        let folder = tempfile::tempdir().unwrap();
+        let mut site = DynoSite::new(folder.path().to_str().unwrap()).unwrap();
+        let (templates, colors) = generators();
+
+        // Both executions keep a copy of the same previous run
+        let executions = IDENTIFIERS.map(|identifier| {
+            let execution = execution(folder.path(), identifier);
+            site.add_execution(&execution, false, true, &templates, &colors)
+                .unwrap();
+            execution
+        });
+        site.data.pinned_baseline = Some(executions[0].previous_benchmarks.clone());
+
+        site.remove_execution(IDENTIFIERS[0], true, &templates, &colors)
+            .unwrap();
+        assert_eq!(
+            site.data.pinned_baseline.as_ref(),
+            Some(&executions[1].previous_benchmarks)
+        );
+
+        site.remove_execution(IDENTIFIERS[1], true, &templates, &colors)
+            .unwrap();
+        assert_eq!(site.data.pinned_baseline, None);
+    }
+
+    #[test]
+    fn validate_reports_missing_benchmark_pages() {
+        let folder = tempfile::tempdir().unwrap();
+        let mut site = DynoSite::new(folder.path().to_str().unwrap()).unwrap();
+        let (templates, colors) = generators();
+
+        site.add_execution(
+            &execution(folder.path(), IDENTIFIERS[0]),
+            false,
+            true,
+            &templates,
+            &colors,
+        )
+        .unwrap();
+        assert_eq!(site.validate(), Vec::<String>::new());
+
+        std::fs::remove_file(
+            site.root_folder
+                .join(benchmark_page(IDENTIFIERS[0], "hashing")),
+        )
+        .unwrap();
+        assert_eq!(
+            site.validate(),
+            [format!(
+                "{}: The page of the benchmark `hashing` is missing, run `rebuild`",
+                IDENTIFIERS[0]
+            )]
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use std::fmt::{self, Write};
+
+/// A value that is HTML escaped when it is displayed
+pub struct Escaped<T>(T);
+
+/// Escape a value for use in HTML text and in quoted attribute values
+pub fn escape<T: fmt::Display>(value: T) -> Escaped<T> {
+    Escaped(value)
+}
+
+/// The URL schemes that are safe to link to
+const SAFE_SCHEMES: [&str; 3] = ["http", "https", "mailto"];
+
+/// Escape a URL for use in `href` and `src` attributes
+/// URLs with a scheme other than http, https or mailto are replaced, so `javascript:` links can't run
+#[must_use]
+pub fn escape_url(url: &str) -> Escaped<&str> {
+    if is_safe_url(url) {
+        Escaped(url)
+    } else {
+        Escaped("#")
+    }
+}
+
+/// Check whether a URL is relative or has one of the safe schemes
+#[must_use]
+pub fn is_safe_url(url: &str) -> bool {
+    // Browsers ignore ASCII whitespace and control characters in a URL, so `java\tscript:` and
+    // ` javascript:` still run as `javascript:`
+    let url = url
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && !c.is_ascii_control())
+        .collect::<String>();
+
+    // Relative paths like the timestamped data folders contain colons but have no valid scheme
+    let scheme = url
+        .split_once(':')
+        .map(|(scheme, _)| scheme)
+        .filter(|scheme| {
+            scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        });
+
+    scheme.is_none_or(|scheme| {
+        SAFE_SCHEMES
+            .iter()
+            .any(|safe| scheme.eq_ignore_ascii_case(safe))
+    })
+}
+
+impl<T: fmt::Display> fmt::Display for Escaped<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(Escaper(f), "{}", self.0)
+    }
+}
+
+/// Writes everything through to a formatter, escaping the HTML special characters
+struct Escaper<'a, 'b>(&'a mut fmt::Formatter<'b>);
+
+impl Write for Escaper<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut last = 0;
+        for (index, character) in s.char_indices() {
+            let replacement = match character {
+                '&' => "&amp;",
+                '<' => "&lt;",
+                '>' => "&gt;",
+                '"' => "&quot;",
+                '\'' => "&#39;",
+                _ => continue,
+            };
+            self.0.write_str(&s[last..index])?;
+            self.0.write_str(replacement)?;
+            last = index + character.len_utf8();
+        }
+        self.0.write_str(&s[last..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_replaces_the_html_special_characters() {
+        assert_eq!(
+            escape("<script>alert('x' & \"y\")</script>").to_string(),
+            "&lt;script&gt;alert(&#39;x&#39; &amp; &quot;y&quot;)&lt;/script&gt;"
+        );
+        assert_eq!(escape("plain text").to_string(), "plain text");
+        assert_eq!(escape("héllo → wörld").to_string(), "héllo → wörld");
+    }
+
+    #[test]
+    fn escape_url_keeps_safe_and_relative_urls() {
+        for url in [
+            "https://github.com/FuelLabs/sway/pull/1",
+            "HTTP://example.com",
+            "mailto:someone@example.com",
+            "benchmarks_2024-10-22_10:00:00/index.html",
+            "../../assets/charts.js",
+            "plot.png",
+        ] {
+            assert_eq!(escape_url(url).to_string(), url);
+        }
+
+        assert_eq!(
+            escape_url("https://example.com/?a=1&b=\"2\"").to_string(),
+            "https://example.com/?a=1&amp;b=&quot;2&quot;"
+        );
+    }
+
+    #[test]
+    fn escape_url_replaces_unsafe_schemes() {
+        for url in [
+            "javascript:alert(1)",
+            "JavaScript:alert(1)",
+            "java\tscript:alert(1)",
+            "java\nscript:alert(1)",
+            " javascript:alert(1)",
+            "\u{1}javascript:alert(1)",
+            "data:text/html,<script>alert(1)</script>",
+            "vbscript:msgbox(1)",
+        ] {
+            assert_eq!(escape_url(url).to_string(), "#", "{url:?}");
+        }
+    }
+}
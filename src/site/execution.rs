@@ -4,11 +4,24 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use crate::{cli::Options, error::Result, types::Benchmarks, wrap};
+use super::{
+    diagnostics::{Diagnostic, BOTH_RUNS},
+    flamegraph::{self, HotFunctions},
+    html::parse_timestamp,
+    palette::PhaseColors,
+    stats,
+};
+use crate::{
+    cli::{Options, PlotFormat, RunOrder},
+    error::{warn, Error, Result},
+    types::{Benchmarks, Collection},
+    wrap,
+};
 use serde::{Deserialize, Serialize};
 
 /// Represents the execution structure for the site
 #[derive(Serialize, Deserialize, Clone)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct Execution {
     pub previous_benchmarks: PathBuf,
     pub current_benchmarks: PathBuf,
@@ -22,46 +35,85 @@ pub struct Execution {
     pub stats: Vec<PathBuf>,
     pub plots: Option<Vec<PathBuf>>,
     pub flamegraphs: Option<Vec<PathBuf>>,
+    #[serde(default)]
+    pub differential_flamegraphs: Option<Vec<PathBuf>>,
+    #[serde(default)]
+    pub hot_functions: Option<PathBuf>,
+    /// The number of functions by self and by inclusive samples in the hot functions table
+    #[serde(default = "default_hot_function_count")]
+    pub hot_function_count: usize,
+    #[serde(default)]
+    pub pinned_baseline: bool,
+    #[serde(default)]
+    pub interactive_charts: bool,
+    #[serde(default)]
+    pub overlay: bool,
+    #[serde(default)]
+    pub overlay_delta: bool,
+    #[serde(default)]
+    pub plot_format: PlotFormat,
+    /// The problems with the data of the execution that aren't specific to a benchmark
+    #[serde(default)]
+    pub diagnostics: Vec<Diagnostic>,
 }
 
 impl Execution {
     /// Create a new execution
-    pub fn new(root_folder: &Path, options: &Options) -> Result<Self> {
+    /// Without an explicit baseline, the pinned baseline run of the site is used as the previous run
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the runs can't be selected, copied or parsed, or if the stats, plots or
+    /// flamegraphs can't be written.
+    pub fn new(
+        root_folder: &Path,
+        options: &Options,
+        pinned_baseline: Option<&Path>,
+        phase_colors: &PhaseColors,
+    ) -> Result<Self> {
+        let pinned_baseline = if options.baseline.is_none() {
+            pinned_baseline
+        } else {
+            None
+        };
+
+        // Select the previous and current runs from the benchmarks folder
+        let (current_run, previous_run) = select_benchmarks(
+            &options.benchmarks_folder.join("runs"),
+            options,
+            pinned_baseline,
+        )
+        .map_err(|e| wrap!(e))?;
+        let runs = vec![current_run.clone(), previous_run.clone()];
+
         // Create the necessary folder structures
         let (root_folder, runs_folder, stats_folder, plots_folder, flamegraphs_folder) =
-            Self::create_structures(root_folder, options).map_err(|e| wrap!(e))?;
+            Self::create_structures(root_folder, &current_run, options.force)
+                .map_err(|e| wrap!(e))?;
 
-        // Update the runs, stats, and flamegraphs folders with the latest entries
-        let runs = Self::get_latest_entries(&options.benchmarks_folder.join("runs"), 2)
-            .map_err(|e| wrap!(e))?;
+        // Copy the selected runs into the runs folder
+        let previous_benchmarks_path =
+            copy_to_folder(&previous_run, &runs_folder).map_err(|e| wrap!(e))?;
+        let current_benchmarks_path =
+            copy_to_folder(&current_run, &runs_folder).map_err(|e| wrap!(e))?;
 
-        for run in &runs {
-            let run_file_name = run
-                .file_name()
-                .ok_or_else(|| wrap!("Failed to get filename for run".into()))?
-                .to_str()
-                .ok_or_else(|| wrap!("Failed to convert run filename to string".into()))?;
-            let run_file_path = runs_folder.join(run_file_name);
-            std::fs::copy(run, &run_file_path).map_err(|e| wrap!(e.into()))?;
-        }
+        let mut diagnostics = Vec::new();
 
-        let stats = Self::get_latest_entries(&options.benchmarks_folder.join("stats"), 1)
+        let flamegraphs = if options.data_only {
+            None
+        } else {
+            // The latest flamegraph folders only belong to the runs when the latest runs are compared
+            let latest = options.baseline.is_none()
+                && options.candidate.is_none()
+                && pinned_baseline.is_none();
+            let (flamegraphs, missing) = Self::select_flamegraphs(
+                &options.benchmarks_folder.join("flamegraphs"),
+                &[&previous_run, &current_run],
+                options.order_by,
+                latest,
+            )
             .map_err(|e| wrap!(e))?;
-
-        for stat in &stats {
-            let stat_file_name = stat
-                .file_name()
-                .ok_or_else(|| wrap!("Failed to get filename for stat".into()))?
-                .to_str()
-                .ok_or_else(|| wrap!("Failed to convert stat filename to string".into()))?;
-            let stat_file_path = stats_folder.join(stat_file_name);
-            std::fs::copy(stat, &stat_file_path).map_err(|e| wrap!(e.into()))?;
-        }
-
-        let flamegraphs = if !options.data_only {
-            let flamegraphs =
-                Self::get_latest_entries(&options.benchmarks_folder.join("flamegraphs"), 2)
-                    .map_err(|e| wrap!(e))?;
+            diagnostics.extend(missing);
             for flamegraph_folder in &flamegraphs {
                 copy_dir_all(
                     flamegraph_folder,
@@ -78,14 +130,8 @@ impl Execution {
                 .map_err(|e| wrap!(e))?;
             }
             Some(flamegraphs)
-        } else {
-            None
         };
 
-        // Get the latest benchmarks
-        let (current_benchmarks_path, previous_benchmarks_path) =
-            get_latest_benchmarks(&runs_folder).map_err(|e| wrap!(e))?;
-
         // Deserialize the benchmarks
         let previous_benchmarks =
             parse_json_benchmarks(&previous_benchmarks_path).map_err(|e| wrap!(e))?;
@@ -93,15 +139,20 @@ impl Execution {
         let current_benchmarks =
             parse_json_benchmarks(&current_benchmarks_path).map_err(|e| wrap!(e))?;
 
-        // Generate the plots
-        let plots = if !options.data_only {
-            Some(
-                Self::generate_plots(&previous_benchmarks, &current_benchmarks, &plots_folder)
-                    .map_err(|e| wrap!(e))?,
-            )
-        } else {
-            None
-        };
+        // Compute the stats from the selected runs
+        let stats_file = stats_folder.join(
+            root_folder
+                .file_name()
+                .ok_or_else(|| wrap!("Failed to get the execution identifier".into()))?,
+        );
+        let stats_file = stats_file.with_extension("json");
+        let stats_collection = stats::compare(&previous_benchmarks, &current_benchmarks);
+        std::fs::write(
+            &stats_file,
+            serde_json::to_string(&stats_collection).map_err(|e| wrap!(e.into()))?,
+        )
+        .map_err(|e| wrap!(e.into()))?;
+        let stats = vec![stats_file];
 
         // Create the github information structure
         let github_information = crate::types::PRInformation {
@@ -119,7 +170,7 @@ impl Execution {
                 .unwrap_or("dyno local execution".to_string()),
         };
 
-        Ok(Self {
+        let mut execution = Self {
             previous_benchmarks: previous_benchmarks_path,
             current_benchmarks: current_benchmarks_path,
             github_information,
@@ -130,69 +181,279 @@ impl Execution {
             flamegraphs_folder,
             runs,
             stats,
-            plots,
+            plots: None,
             flamegraphs,
-        })
+            differential_flamegraphs: None,
+            hot_functions: None,
+            hot_function_count: options.hot_functions,
+            pinned_baseline: pinned_baseline.is_some(),
+            interactive_charts: options.interactive_charts,
+            overlay: options.overlay || options.overlay_delta,
+            overlay_delta: options.overlay_delta,
+            plot_format: options.plot_format,
+            diagnostics,
+        };
+
+        // Compare the flamegraphs of the previous and current runs
+        execution
+            .compare_flamegraphs(&previous_benchmarks, &current_benchmarks)
+            .map_err(|e| wrap!(e))?;
+
+        // Generate the plots
+        if !options.data_only {
+            execution.plots = Some(
+                execution
+                    .generate_plots(&previous_benchmarks, &current_benchmarks, phase_colors)
+                    .map_err(|e| wrap!(e))?,
+            );
+        }
+
+        Ok(execution)
+    }
+
+    /// Get the identifier of the execution, the file stem of its current run
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the current run has no file stem.
+    pub fn identifier(&self) -> Result<&str> {
+        self.root_folder
+            .file_name()
+            .ok_or_else(|| wrap!("Failed to get the execution identifier.".into()))?
+            .to_str()
+            .ok_or_else(|| wrap!("Failed to get the execution identifier as str.".into()).into())
+    }
+
+    /// Recompute the stats, differential flamegraphs and hot functions and regenerate the plots of
+    /// the execution from its stored runs and flamegraphs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stored runs can't be parsed, or if the stats or plots can't be
+    /// written.
+    pub fn rebuild(&mut self, data_only: bool, phase_colors: &PhaseColors) -> Result<()> {
+        let previous_benchmarks =
+            parse_json_benchmarks(&self.previous_benchmarks).map_err(|e| wrap!(e))?;
+        let current_benchmarks =
+            parse_json_benchmarks(&self.current_benchmarks).map_err(|e| wrap!(e))?;
+
+        let stats_collection = stats::compare(&previous_benchmarks, &current_benchmarks);
+        std::fs::write(
+            self.stats_file().map_err(|e| wrap!(e))?,
+            serde_json::to_string(&stats_collection).map_err(|e| wrap!(e.into()))?,
+        )
+        .map_err(|e| wrap!(e.into()))?;
+
+        // Drop the diagnostics of the last comparison of the flamegraphs before redoing it
+        self.diagnostics
+            .retain(|diagnostic| diagnostic.run != BOTH_RUNS);
+        self.compare_flamegraphs(&previous_benchmarks, &current_benchmarks)
+            .map_err(|e| wrap!(e))?;
+
+        if !data_only {
+            std::fs::create_dir_all(&self.plots_folder).map_err(|e| wrap!(e.into()))?;
+            self.plots = Some(
+                self.generate_plots(&previous_benchmarks, &current_benchmarks, phase_colors)
+                    .map_err(|e| wrap!(e))?,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Check that the files of the execution exist and parse, returning the problems found
+    #[must_use]
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        for run in [&self.previous_benchmarks, &self.current_benchmarks] {
+            if let Err(e) = parse_json_benchmarks(run) {
+                problems.push(format!(
+                    "The run `{}` can't be read: {}",
+                    run.display(),
+                    e.root_cause()
+                ));
+            }
+        }
+
+        if let Err(e) = self.stats_collection() {
+            problems.push(format!("The stats can't be read: {}", e.root_cause()));
+        }
+
+        if let Err(e) = self.hot_functions() {
+            problems.push(format!(
+                "The hot functions can't be read: {}",
+                e.root_cause()
+            ));
+        }
+
+        let files = self
+            .plots
+            .iter()
+            .chain(&self.differential_flamegraphs)
+            .flatten();
+        for file in files {
+            if !file.is_file() {
+                problems.push(format!("The file `{}` is missing", file.display()));
+            }
+        }
+
+        for folder in self.flamegraphs.iter().flatten() {
+            let Some(name) = folder.file_name() else {
+                continue;
+            };
+            let folder = self.flamegraphs_folder.join(name);
+            if !folder.is_dir() {
+                problems.push(format!("The folder `{}` is missing", folder.display()));
+            }
+        }
+
+        problems
+    }
+
+    /// Get the path of the stats file of the execution
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the identifier of the execution can't be read.
+    pub fn stats_file(&self) -> Result<PathBuf> {
+        let identifier = self
+            .root_folder
+            .components()
+            .next_back()
+            .ok_or_else(|| {
+                wrap!("Failed to get the last component of the root folder path.".into())
+            })?
+            .as_os_str()
+            .to_str()
+            .ok_or_else(|| {
+                wrap!("Failed to get the last component of the root folder path as str.".into())
+            })?;
+        Ok(self.stats_folder.join(format!("{identifier}.json")))
+    }
+
+    /// Load the stats collection of the execution
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stats file can't be read or parsed.
+    pub fn stats_collection(&self) -> Result<Collection> {
+        let stats_file_string = std::fs::read_to_string(self.stats_file().map_err(|e| wrap!(e))?)
+            .map_err(|e| wrap!(e.into()))?;
+        let stats_collection: Collection =
+            serde_json::from_str(&stats_file_string).map_err(|e| wrap!(e.into()))?;
+        Ok(stats_collection)
+    }
+
+    /// Load the hot functions of the benchmarks of the execution, empty when the execution has no
+    /// flamegraphs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the hot functions file exists but can't be read or parsed.
+    pub fn hot_functions(&self) -> Result<HotFunctions> {
+        let Some(hot_functions) = &self.hot_functions else {
+            return Ok(HotFunctions::new());
+        };
+
+        let hot_functions_string =
+            std::fs::read_to_string(hot_functions).map_err(|e| wrap!(e.into()))?;
+        Ok(serde_json::from_str(&hot_functions_string).map_err(|e| wrap!(e.into()))?)
     }
 
     /// Create the necessary folder structures for the current execution
+    /// An existing execution is only replaced when forced, its generated files are removed while
+    /// its runs are kept. The folders are staged next to the execution and swapped in once
+    /// complete, so a failure leaves the existing execution untouched
     fn create_structures(
         root_folder: &Path,
-        options: &Options,
+        current_run: &Path,
+        force: bool,
     ) -> Result<(PathBuf, PathBuf, PathBuf, PathBuf, PathBuf)> {
         // Create the root folder for the current execution and its sub folders
-        let root_folder = root_folder.join(
-            Self::get_current_execution_identifier(&options.benchmarks_folder)
-                .map_err(|e| wrap!(e))?,
-        );
+        let identifier =
+            Self::get_current_execution_identifier(current_run).map_err(|e| wrap!(e))?;
+        let staging_folder = root_folder.join(format!(".{}.staging", identifier.display()));
+        let root_folder = root_folder.join(&identifier);
 
-        if !root_folder.exists() {
-            std::fs::create_dir(&root_folder).map_err(|e| wrap!(e.into()))?;
+        if root_folder.exists() && !force {
+            return Err(wrap!(format!(
+                "The execution `{}` already exists in the site, pass `--force` to replace it",
+                identifier.display()
+            )
+            .into())
+            .into());
         }
 
-        let runs_folder = root_folder.join("runs");
-        if !runs_folder.exists() {
-            std::fs::create_dir(&runs_folder).map_err(|e| wrap!(e.into()))?;
+        // A staging folder left over by an interrupted execution is started over
+        if staging_folder.exists() {
+            std::fs::remove_dir_all(&staging_folder).map_err(|e| wrap!(e.into()))?;
         }
 
-        let stats_folder = root_folder.join("stats");
-        if !stats_folder.exists() {
-            std::fs::create_dir(&stats_folder).map_err(|e| wrap!(e.into()))?;
+        let staged = Self::stage_structures(&staging_folder, &root_folder)
+            .and_then(|()| Self::swap_in(&staging_folder, &root_folder));
+        if let Err(e) = staged {
+            let _ = std::fs::remove_dir_all(&staging_folder);
+            return Err(wrap!(e).into());
         }
 
-        let plots_folder = root_folder.join("plots");
-        if !plots_folder.exists() {
-            std::fs::create_dir(&plots_folder).map_err(|e| wrap!(e.into()))?;
+        Ok((
+            root_folder.clone(),
+            root_folder.join("runs"),
+            root_folder.join("stats"),
+            root_folder.join("plots"),
+            root_folder.join("flamegraphs"),
+        ))
+    }
+
+    /// Create the folders of an execution in its staging folder, with the runs of the existing
+    /// execution
+    fn stage_structures(staging_folder: &Path, root_folder: &Path) -> Result<()> {
+        for folder in ["runs", "stats", "plots", "flamegraphs"] {
+            std::fs::create_dir_all(staging_folder.join(folder)).map_err(|e| wrap!(e.into()))?;
         }
 
-        let flamegraphs_folder = root_folder.join("flamegraphs");
-        if !flamegraphs_folder.exists() {
-            std::fs::create_dir(&flamegraphs_folder).map_err(|e| wrap!(e.into()))?;
+        let runs_folder = root_folder.join("runs");
+        if runs_folder.exists() {
+            copy_dir_all(&runs_folder, &staging_folder.join("runs")).map_err(|e| wrap!(e))?;
         }
-        Ok((
-            root_folder,
-            runs_folder,
-            stats_folder,
-            plots_folder,
-            flamegraphs_folder,
-        ))
+
+        Ok(())
     }
 
-    /// Get the current execution identifier from the latest stats file
-    fn get_current_execution_identifier(benchmarks_folder: &Path) -> Result<PathBuf> {
-        let benchmarks_stats_path = benchmarks_folder.join("stats");
-        let latest_stats_file_path =
-            get_latest_stats_file(&benchmarks_stats_path).map_err(|e| wrap!(e))?;
+    /// Replace the folder of an execution with its staging folder, putting the existing execution
+    /// back when the staging folder can't be moved in
+    fn swap_in(staging_folder: &Path, root_folder: &Path) -> Result<()> {
+        if !root_folder.exists() {
+            std::fs::rename(staging_folder, root_folder).map_err(|e| wrap!(e.into()))?;
+            return Ok(());
+        }
 
-        // Keep the last part of the path
-        let stats_file_last_part = latest_stats_file_path
-            .components()
-            .last()
-            .ok_or_else(|| wrap!("Failed to get the last part of the stats file path".into()))?;
-        let current_execution_identifier = PathBuf::from(stats_file_last_part.as_os_str());
+        let replaced_folder = staging_folder.with_extension("replaced");
+        std::fs::rename(root_folder, &replaced_folder).map_err(|e| wrap!(e.into()))?;
 
-        // Remove the file extension from the path and keep the folder path
-        let current_execution_identifier = current_execution_identifier
+        if let Err(e) = std::fs::rename(staging_folder, root_folder) {
+            let _ = std::fs::rename(&replaced_folder, root_folder);
+            return Err(wrap!(e.into()).into());
+        }
+
+        if let Err(e) = std::fs::remove_dir_all(&replaced_folder) {
+            warn(
+                &format!(
+                    "Failed to remove the replaced execution `{}`",
+                    replaced_folder.display()
+                ),
+                &e.into(),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Get the current execution identifier from the file name of the current run
+    fn get_current_execution_identifier(current_run: &Path) -> Result<PathBuf> {
+        // Remove the file extension from the path and keep the file name
+        let current_execution_identifier = current_run
             .file_stem()
             .ok_or_else(|| wrap!("Failed to remove file stem".into()))?;
 
@@ -273,89 +534,391 @@ impl Execution {
         Ok(source_modified > target_modified)
     }
 
-    /// Get the latest two files or folders from a folder
-    fn get_latest_entries(target: &Path, count: usize) -> Result<Vec<PathBuf>> {
+    /// Get the flamegraph folders that belong to the previous and current runs, matched by the
+    /// timestamp in their names, in the order of the runs
+    /// When a run has no matching folder, the latest flamegraph folders, oldest first, are used if
+    /// the `latest` runs are compared, otherwise there are no flamegraphs and a diagnostic names
+    /// each run without a folder
+    fn select_flamegraphs(
+        target: &Path,
+        runs: &[&Path],
+        order: RunOrder,
+        latest: bool,
+    ) -> Result<(Vec<PathBuf>, Vec<Diagnostic>)> {
+        let matched: Vec<Option<PathBuf>> = runs
+            .iter()
+            .map(|run| {
+                let timestamp = file_name_timestamp(run)?;
+
+                // Runs stored in the site keep their flamegraphs next to their runs folder
+                let sibling = run.parent()?.parent()?.join("flamegraphs");
+
+                [sibling.as_path(), target]
+                    .iter()
+                    .filter_map(|folder| std::fs::read_dir(folder).ok())
+                    .flatten()
+                    .filter_map(std::result::Result::ok)
+                    .map(|entry| entry.path())
+                    .find(|folder| file_name_timestamp(folder) == Some(timestamp))
+            })
+            .collect();
+
+        if matched.iter().all(Option::is_some) {
+            return Ok((matched.into_iter().flatten().collect(), Vec::new()));
+        }
+
+        if latest {
+            let mut latest =
+                Self::get_latest_entries(target, runs.len(), order).map_err(|e| wrap!(e))?;
+            latest.reverse();
+            return Ok((latest, Vec::new()));
+        }
+
+        // Pairing the folders of other runs would compare the flamegraphs of unrelated runs
+        let diagnostics = ["previous", "current"]
+            .iter()
+            .zip(runs)
+            .zip(&matched)
+            .filter(|(_, folder)| folder.is_none())
+            .map(|((name, run), _)| {
+                let error = Error::MissingFlamegraphs {
+                    run: run.display().to_string(),
+                };
+                warn("The flamegraphs of the execution are skipped", &error);
+                Diagnostic::new(name, &[], &error)
+            })
+            .collect();
+
+        Ok((Vec::new(), diagnostics))
+    }
+
+    /// Generate the differential flamegraphs and the hot functions of the stored flamegraphs of
+    /// both runs, an execution without flamegraphs for both runs has neither
+    fn compare_flamegraphs(
+        &mut self,
+        previous_benchmarks: &Benchmarks,
+        current_benchmarks: &Benchmarks,
+    ) -> Result<()> {
+        let Some([previous, current]) = self.flamegraphs.as_deref() else {
+            self.differential_flamegraphs = None;
+            self.hot_functions = None;
+            return Ok(());
+        };
+
+        let previous_folder = self.flamegraphs_folder.join(
+            previous
+                .file_name()
+                .ok_or_else(|| wrap!("Failed to get filename for flamegraph".into()))?,
+        );
+        let current_folder = self.flamegraphs_folder.join(
+            current
+                .file_name()
+                .ok_or_else(|| wrap!("Failed to get filename for flamegraph".into()))?,
+        );
+
+        self.differential_flamegraphs = Self::generate_differentials(
+            &previous_folder,
+            &current_folder,
+            previous_benchmarks,
+            current_benchmarks,
+            &self.flamegraphs_folder.join("differential"),
+            &mut self.diagnostics,
+        );
+
+        self.hot_functions = Self::write_hot_functions(
+            &previous_folder,
+            &current_folder,
+            previous_benchmarks,
+            current_benchmarks,
+            self.hot_function_count,
+            &self.flamegraphs_folder.join("hot_functions.json"),
+            &mut self.diagnostics,
+        );
+
+        Ok(())
+    }
+
+    /// Generate the differential flamegraphs of the benchmarks of both runs, a failure leaves them
+    /// out of the execution with a diagnostic instead of failing the whole execution
+    fn generate_differentials(
+        previous_folder: &Path,
+        current_folder: &Path,
+        previous_benchmarks: &Benchmarks,
+        current_benchmarks: &Benchmarks,
+        output_folder: &Path,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Option<Vec<PathBuf>> {
+        flamegraph::generate_differentials(
+            previous_folder,
+            current_folder,
+            previous_benchmarks,
+            current_benchmarks,
+            output_folder,
+        )
+        .map_err(|error| {
+            warn(
+                "The differential flamegraphs of the execution are skipped",
+                &error,
+            );
+            diagnostics.push(Diagnostic::new(BOTH_RUNS, &[], &error));
+        })
+        .ok()
+    }
+
+    /// Write the hot functions of the benchmarks of both runs, a failure leaves the hot functions
+    /// table out of the execution with a diagnostic instead of failing the whole execution
+    fn write_hot_functions(
+        previous_folder: &Path,
+        current_folder: &Path,
+        previous_benchmarks: &Benchmarks,
+        current_benchmarks: &Benchmarks,
+        count: usize,
+        output_file: &Path,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Option<PathBuf> {
+        flamegraph::write_hot_functions(
+            previous_folder,
+            current_folder,
+            previous_benchmarks,
+            current_benchmarks,
+            count,
+            output_file,
+        )
+        .map(|()| output_file.to_path_buf())
+        .map_err(|error| {
+            warn("The hot functions of the execution are skipped", &error);
+            diagnostics.push(Diagnostic::new(BOTH_RUNS, &[], &error));
+        })
+        .ok()
+    }
+
+    /// Get the latest files or folders from a folder
+    fn get_latest_entries(target: &Path, count: usize, order: RunOrder) -> Result<Vec<PathBuf>> {
         let mut entries: Vec<_> = std::fs::read_dir(target)
             .map_err(|e| wrap!(e.into()))?
             .filter_map(std::result::Result::ok)
+            .map(|entry| entry.path())
             .collect();
 
-        // Sort entries by modification time in descending order
-        entries.sort_by_key(|entry| {
-            entry
-                .metadata()
-                .and_then(|meta| meta.modified())
-                .unwrap_or(std::time::SystemTime::UNIX_EPOCH)
-        });
-        entries.reverse();
+        // Folders carry no `benchmarks_datetime` so they are ordered by their names instead
+        let order = if order == RunOrder::Datetime {
+            RunOrder::FileName
+        } else {
+            order
+        };
+
+        // Sort entries in descending order
+        entries.sort_by_cached_key(|path| std::cmp::Reverse(order_key(path, order)));
 
         // Take the latest `count` entries
-        let latest_entries: Vec<PathBuf> = entries
-            .into_iter()
-            .take(count)
-            .map(|entry| entry.path())
-            .collect();
-        Ok(latest_entries)
+        entries.truncate(count);
+        Ok(entries)
     }
 
-    /// Generate plots for the previous and current benchmarks
+    /// Generate plots for the previous and current benchmarks, in the plot mode of the execution
+    /// Interactive charts only need the series of the frames, which are rendered in the browser
     fn generate_plots(
+        &self,
         previous_benchmarks: &Benchmarks,
         current_benchmarks: &Benchmarks,
-        plots_folder: &Path,
+        colors: &PhaseColors,
     ) -> Result<Vec<PathBuf>> {
         let mut plots = Vec::new();
-        // Generate plots
-        plots.extend(
-            super::plot::generate_plots(
-                previous_benchmarks,
-                plots_folder.join("previous").display().to_string().as_str(),
-            )
-            .map_err(|e| wrap!(e))?,
-        );
+        let plots_folder = &self.plots_folder;
 
-        plots.extend(
-            super::plot::generate_plots(
+        if self.interactive_charts {
+            plots.extend(
+                super::chart::write_series(
+                    previous_benchmarks,
+                    plots_folder.join("previous").display().to_string().as_str(),
+                    colors,
+                )
+                .map_err(|e| wrap!(e))?,
+            );
+
+            plots.extend(
+                super::chart::write_series(
+                    current_benchmarks,
+                    plots_folder.join("current").display().to_string().as_str(),
+                    colors,
+                )
+                .map_err(|e| wrap!(e))?,
+            );
+            return Ok(plots);
+        }
+
+        if self.overlay {
+            plots.extend(super::plot::generate_overlay_plots(
+                previous_benchmarks,
                 current_benchmarks,
-                plots_folder.join("current").display().to_string().as_str(),
-            )
-            .map_err(|e| wrap!(e))?,
-        );
+                plots_folder.join("overlay").display().to_string().as_str(),
+                self.plot_format,
+                self.overlay_delta,
+            ));
+            return Ok(plots);
+        }
+
+        // Generate plots
+        plots.extend(super::plot::generate_plots(
+            previous_benchmarks,
+            plots_folder.join("previous").display().to_string().as_str(),
+            self.plot_format,
+            colors,
+        ));
+
+        plots.extend(super::plot::generate_plots(
+            current_benchmarks,
+            plots_folder.join("current").display().to_string().as_str(),
+            self.plot_format,
+            colors,
+        ));
         Ok(plots)
     }
 }
 
-/// Get the latest two benchmark files in the folder
-/// First item in the tuple is the previous benchmark file
-/// Second item in the tuple is the current benchmark file
-fn get_latest_benchmarks(folder: &Path) -> Result<(PathBuf, PathBuf)> {
+/// Select the benchmark files to compare in the folder
+/// First item in the tuple is the current benchmark file
+/// Second item in the tuple is the previous benchmark file
+fn select_benchmarks(
+    folder: &Path,
+    options: &Options,
+    pinned_baseline: Option<&Path>,
+) -> Result<(PathBuf, PathBuf)> {
     let mut entries: Vec<_> = std::fs::read_dir(folder)
         .map_err(|e| wrap!(e.into()))?
         .filter_map(std::result::Result::ok)
-        .filter_map(|entry| {
-            let path = entry.path();
-            if path.is_file() && path.file_name()?.to_str()?.ends_with("json") {
-                let metadata = std::fs::metadata(&path).ok()?;
-                let modified = metadata.modified().ok()?;
-                Some((path, modified))
-            } else {
-                None
-            }
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.ends_with("json"))
         })
         .collect();
 
-    // Sort entries by modification time in descending order
-    entries.sort_by_key(|&(_, modified)| std::cmp::Reverse(modified));
+    // Sort entries in descending order, newest first
+    entries.sort_by_cached_key(|path| std::cmp::Reverse(order_key(path, options.order_by)));
+
+    let baseline = options
+        .baseline
+        .as_deref()
+        .map(|baseline| resolve_run(&entries, baseline))
+        .transpose()
+        .map_err(|e| wrap!(e))?
+        .or_else(|| pinned_baseline.map(Path::to_path_buf));
+
+    let candidate = match options.candidate.as_deref() {
+        Some(candidate) => resolve_run(&entries, candidate).map_err(|e| wrap!(e))?,
+        None => entries
+            .iter()
+            .find(|entry| baseline.as_ref().is_none_or(|b| !is_same_run(entry, b)))
+            .cloned()
+            .ok_or_else(|| wrap!("Not enough files in the folder".into()))?,
+    };
+
+    // Without an explicit baseline, compare against the run right before the candidate
+    let baseline = match baseline {
+        Some(baseline) => baseline,
+        None => entries
+            .iter()
+            .skip_while(|entry| !is_same_run(entry, &candidate))
+            .find(|entry| !is_same_run(entry, &candidate))
+            .cloned()
+            .ok_or_else(|| wrap!("Failed to find a run before the candidate run".into()))?,
+    };
+
+    if is_same_run(&baseline, &candidate) {
+        return Err(wrap!("The baseline and candidate runs are the same run".into()).into());
+    }
+
+    Ok((candidate, baseline))
+}
+
+/// Resolve a run from a file path or from the file name, stem or timestamp of an entry
+fn resolve_run(entries: &[PathBuf], run: &str) -> Result<PathBuf> {
+    let path = Path::new(run);
+    if path.is_file() {
+        return Ok(path.to_path_buf());
+    }
+
+    let file_name = |entry: &PathBuf| entry.file_name().and_then(|n| n.to_str()).map(String::from);
+    let file_stem = |entry: &PathBuf| entry.file_stem().and_then(|n| n.to_str()).map(String::from);
+
+    entries
+        .iter()
+        .find(|entry| {
+            file_name(entry).as_deref() == Some(run) || file_stem(entry).as_deref() == Some(run)
+        })
+        .or_else(|| {
+            entries
+                .iter()
+                .find(|entry| file_stem(entry).is_some_and(|stem| stem.ends_with(run)))
+        })
+        .cloned()
+        .ok_or_else(|| wrap!(format!("Failed to find the run `{run}`").into()).into())
+}
+
+/// Check whether two paths point to the same run, runs copied into the site keep their file names
+fn is_same_run(a: &Path, b: &Path) -> bool {
+    a.file_name() == b.file_name()
+}
 
-    if entries.len() < 2 {
-        return Err("Not enough files in the folder".into());
+/// Get the key used to order a run or folder, entries without a key sort as the oldest
+fn order_key(path: &Path, order: RunOrder) -> Option<chrono::NaiveDateTime> {
+    match order {
+        RunOrder::Modified => {
+            let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+            Some(chrono::DateTime::<chrono::Utc>::from(modified).naive_utc())
+        }
+        RunOrder::FileName => file_name_timestamp(path),
+        RunOrder::Datetime => {
+            #[derive(Deserialize)]
+            struct Run {
+                benchmarks_datetime: String,
+            }
+
+            let run: Run = serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()?;
+            parse_datetime(&run.benchmarks_datetime)
+        }
     }
+}
 
-    Ok((entries[0].0.clone(), entries[1].0.clone()))
+/// Get the timestamp embedded at the end of a file or folder name
+fn file_name_timestamp(path: &Path) -> Option<chrono::NaiveDateTime> {
+    let name = if path.is_dir() {
+        path.file_name()
+    } else {
+        path.file_stem()
+    };
+    parse_timestamp(name?.to_str()?).ok()
+}
+
+/// Parse the `benchmarks_datetime` of a run
+fn parse_datetime(datetime: &str) -> Option<chrono::NaiveDateTime> {
+    if let Ok(datetime) = chrono::DateTime::parse_from_rfc3339(datetime) {
+        return Some(datetime.naive_utc());
+    }
+
+    if let Ok(datetime) = chrono::DateTime::parse_from_str(datetime, "%Y-%m-%d %H:%M:%S%.f %z") {
+        return Some(datetime.naive_utc());
+    }
+
+    [
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d_%H:%M:%S",
+    ]
+    .iter()
+    .find_map(|format| chrono::NaiveDateTime::parse_from_str(datetime, format).ok())
 }
 
 /// Parse the JSON benchmarks from a file
-fn parse_json_benchmarks(file_path: &Path) -> Result<Benchmarks> {
+///
+/// # Errors
+///
+/// Returns an error if the file can't be read or isn't valid benchmarks JSON.
+pub fn parse_json_benchmarks(file_path: &Path) -> Result<Benchmarks> {
     let mut file = std::fs::File::open(file_path).map_err(|e| wrap!(e.into()))?;
     let mut data = String::new();
     file.read_to_string(&mut data)
@@ -364,33 +927,32 @@ fn parse_json_benchmarks(file_path: &Path) -> Result<Benchmarks> {
     Ok(benchmark_data)
 }
 
-/// Get the latest stats file in the folder
-pub fn get_latest_stats_file(folder: &Path) -> Result<PathBuf> {
-    let mut entries: Vec<_> = std::fs::read_dir(folder)
-        .map_err(|e| wrap!(e.into()))?
-        .filter_map(std::result::Result::ok)
-        .filter_map(|entry| {
-            let path = entry.path();
-            if path.is_file() && path.file_name()?.to_str()?.ends_with("json") {
-                let metadata = entry.metadata().ok()?;
-                let modified = metadata.modified().ok()?;
-                Some((path, modified))
-            } else {
-                None
-            }
-        })
-        .collect();
+/// The number of hot functions of executions stored before the count was kept in `site.json`
+fn default_hot_function_count() -> usize {
+    flamegraph::DEFAULT_HOT_FUNCTIONS
+}
 
-    // Sort entries by modification time in descending order
-    entries.sort_by_key(|&(_, modified)| std::cmp::Reverse(modified));
-    if let Some((latest_path, _)) = entries.first() {
-        Ok(latest_path.clone())
-    } else {
-        Err("No stats files found in the folder".into())
+/// Copy a file into a folder, returning the path of the copy
+fn copy_to_folder(file: &Path, folder: &Path) -> Result<PathBuf> {
+    let file_name = file
+        .file_name()
+        .ok_or_else(|| wrap!("Failed to get filename for run".into()))?
+        .to_str()
+        .ok_or_else(|| wrap!("Failed to convert run filename to string".into()))?;
+    let file_path = folder.join(file_name);
+
+    // A run that is already in the folder, like the pinned baseline of a replaced execution, is kept
+    if file != file_path {
+        std::fs::copy(file, &file_path).map_err(|e| wrap!(e.into()))?;
     }
+    Ok(file_path)
 }
 
 /// Copy the contents of a directory to another directory
+///
+/// # Errors
+///
+/// Returns an error if a file or folder can't be read, created or copied.
 pub fn copy_dir_all(src: &Path, dst: &Path) -> Result<()> {
     std::fs::create_dir_all(dst)?;
     for entry in std::fs::read_dir(src)? {
@@ -405,3 +967,375 @@ pub fn copy_dir_all(src: &Path, dst: &Path) -> Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site::fixtures;
+    use clap::Parser;
+
+    const RUNS: [&str; 3] = [
+        "benchmarks_2024-10-21_10:00:00.json",
+        "benchmarks_2024-10-22_10:00:00.json",
+        "benchmarks_2024-10-23_10:00:00.json",
+    ];
+
+    /// A runs folder with the `RUNS`, plus a run with a malformed name
+    fn runs_folder() -> tempfile::TempDir {
+        let folder = tempfile::tempdir().unwrap();
+        let run = fixtures::benchmarks(vec![fixtures::benchmark("hashing")]);
+        for file_name in RUNS.iter().chain(&["benchmarks_latest.json"]) {
+            fixtures::write_run(folder.path(), file_name, &run);
+        }
+        folder
+    }
+
+    fn add_options(arguments: &[&str]) -> Options {
+        Options::parse_from(["dynosite", "-b", "benchmarks"].iter().chain(arguments))
+    }
+
+    fn file_names(runs: &(PathBuf, PathBuf)) -> (String, String) {
+        let file_name = |run: &PathBuf| run.file_name().unwrap().to_str().unwrap().to_string();
+        (file_name(&runs.0), file_name(&runs.1))
+    }
+
+    #[test]
+    fn select_benchmarks_compares_the_latest_runs() {
+        let folder = runs_folder();
+
+        let runs = select_benchmarks(folder.path(), &add_options(&[]), None).unwrap();
+
+        assert_eq!(
+            file_names(&runs),
+            (RUNS[2].to_string(), RUNS[1].to_string())
+        );
+    }
+
+    #[test]
+    fn select_benchmarks_uses_the_explicit_runs() {
+        let folder = runs_folder();
+
+        let options = add_options(&["--baseline", "2024-10-21_10:00:00", "--candidate", RUNS[1]]);
+        let runs = select_benchmarks(folder.path(), &options, None).unwrap();
+        assert_eq!(
+            file_names(&runs),
+            (RUNS[1].to_string(), RUNS[0].to_string())
+        );
+
+        let options = add_options(&["--baseline", "benchmarks_2024-10-21_10:00:00"]);
+        let runs = select_benchmarks(folder.path(), &options, None).unwrap();
+        assert_eq!(
+            file_names(&runs),
+            (RUNS[2].to_string(), RUNS[0].to_string())
+        );
+    }
+
+    #[test]
+    fn select_benchmarks_uses_the_pinned_baseline() {
+        let folder = runs_folder();
+        let pinned = folder.path().join(RUNS[0]);
+
+        let runs = select_benchmarks(folder.path(), &add_options(&[]), Some(&pinned)).unwrap();
+        assert_eq!(
+            file_names(&runs),
+            (RUNS[2].to_string(), RUNS[0].to_string())
+        );
+
+        // A candidate that is the pinned baseline itself is rejected
+        let options = add_options(&["--candidate", RUNS[0]]);
+        assert!(select_benchmarks(folder.path(), &options, Some(&pinned)).is_err());
+    }
+
+    #[test]
+    fn select_benchmarks_rejects_unknown_and_identical_runs() {
+        let folder = runs_folder();
+
+        let options = add_options(&["--baseline", "2024-01-01_00:00:00"]);
+        assert!(select_benchmarks(folder.path(), &options, None).is_err());
+
+        let options = add_options(&["--baseline", RUNS[1], "--candidate", RUNS[1]]);
+        assert!(select_benchmarks(folder.path(), &options, None).is_err());
+    }
+
+    #[test]
+    fn select_benchmarks_orders_the_runs_by_their_datetime() {
+        let folder = tempfile::tempdir().unwrap();
+        for (file_name, datetime) in [
+            ("benchmarks_a.json", "2024-10-23 10:00:00"),
+            ("benchmarks_b.json", "2024-10-21T10:00:00Z"),
+            ("benchmarks_c.json", "2024-10-22 10:00:00"),
+        ] {
+            let mut run = fixtures::benchmarks(vec![fixtures::benchmark("hashing")]);
+            run.benchmarks_datetime = datetime.to_string();
+            fixtures::write_run(folder.path(), file_name, &run);
+        }
+
+        let options =
+            Options::parse_from(["dynosite", "-b", "benchmarks", "--order-by", "datetime"]);
+        let runs = select_benchmarks(folder.path(), &options, None).unwrap();
+
+        assert_eq!(
+            file_names(&runs),
+            (
+                "benchmarks_a.json".to_string(),
+                "benchmarks_c.json".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn malformed_file_names_have_no_timestamp_and_sort_as_the_oldest() {
+        for file_name in [
+            "benchmarks_latest.json",
+            "benchmarks_2024-13-01_10:00:00.json",
+            "benchmarks_2024-10-22.json",
+            "2024-10-22_10:00:00.json",
+        ] {
+            assert_eq!(
+                file_name_timestamp(Path::new(file_name)),
+                None,
+                "{file_name}"
+            );
+        }
+
+        assert!(
+            order_key(Path::new("benchmarks_latest.json"), RunOrder::FileName)
+                < order_key(Path::new(RUNS[0]), RunOrder::FileName)
+        );
+    }
+
+    #[test]
+    fn parse_datetime_accepts_the_datetime_formats() {
+        let expected = chrono::NaiveDate::from_ymd_opt(2024, 10, 22)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+
+        for datetime in [
+            "2024-10-22T10:00:00Z",
+            "2024-10-22T12:00:00+02:00",
+            "2024-10-22 10:00:00 +0000",
+            "2024-10-22 10:00:00",
+            "2024-10-22 10:00:00.000",
+            "2024-10-22T10:00:00",
+            "2024-10-22_10:00:00",
+        ] {
+            assert_eq!(parse_datetime(datetime), Some(expected), "{datetime}");
+        }
+
+        assert_eq!(parse_datetime("yesterday"), None);
+        assert_eq!(parse_datetime(""), None);
+    }
+
+    #[test]
+    fn select_flamegraphs_only_pairs_the_folders_of_the_runs() {
+        let folder = tempfile::tempdir().unwrap();
+        let flamegraphs = folder.path().join("flamegraphs");
+        for name in [
+            "flamegraph_2024-10-22_10:00:00",
+            "flamegraph_2024-10-23_10:00:00",
+        ] {
+            std::fs::create_dir_all(flamegraphs.join(name)).unwrap();
+        }
+        let run = |file_name: &str| folder.path().join("runs").join(file_name);
+
+        // Both runs have a folder
+        let (folders, diagnostics) = Execution::select_flamegraphs(
+            &flamegraphs,
+            &[&run(RUNS[1]), &run(RUNS[2])],
+            RunOrder::FileName,
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            folders,
+            [
+                flamegraphs.join("flamegraph_2024-10-22_10:00:00"),
+                flamegraphs.join("flamegraph_2024-10-23_10:00:00")
+            ]
+        );
+        assert!(diagnostics.is_empty());
+
+        // An explicit baseline without a folder gets no flamegraphs of other runs
+        let (folders, diagnostics) = Execution::select_flamegraphs(
+            &flamegraphs,
+            &[&run(RUNS[0]), &run(RUNS[2])],
+            RunOrder::FileName,
+            false,
+        )
+        .unwrap();
+        assert!(folders.is_empty());
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].run, "previous");
+
+        // The latest runs fall back to the latest folders
+        let (folders, diagnostics) = Execution::select_flamegraphs(
+            &flamegraphs,
+            &[&run("benchmarks_a.json"), &run("benchmarks_b.json")],
+            RunOrder::FileName,
+            true,
+        )
+        .unwrap();
+        assert_eq!(folders.len(), 2);
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn create_structures_only_replaces_an_execution_when_forced() {
+        let folder = tempfile::tempdir().unwrap();
+        let current_run = Path::new(RUNS[1]);
+
+        let (root_folder, runs_folder, _, plots_folder, _) =
+            Execution::create_structures(folder.path(), current_run, false).unwrap();
+        std::fs::write(runs_folder.join(RUNS[0]), "").unwrap();
+        std::fs::write(plots_folder.join("stale.png"), "").unwrap();
+
+        let error = Execution::create_structures(folder.path(), current_run, false)
+            .err()
+            .unwrap();
+        assert!(error.root_cause().to_string().contains("pass `--force`"));
+
+        let (replaced, ..) =
+            Execution::create_structures(folder.path(), current_run, true).unwrap();
+        assert_eq!(replaced, root_folder);
+        assert!(runs_folder.join(RUNS[0]).is_file());
+        assert!(plots_folder.is_dir());
+        assert!(!plots_folder.join("stale.png").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn create_structures_keeps_the_execution_when_it_fails() {
+        let folder = tempfile::tempdir().unwrap();
+        let current_run = Path::new(RUNS[1]);
+
+        let (root_folder, runs_folder, _, plots_folder, _) =
+            Execution::create_structures(folder.path(), current_run, false).unwrap();
+        std::fs::write(plots_folder.join("hashing.png"), "").unwrap();
+        // A run that can't be copied fails the staging of the replacement
+        std::os::unix::fs::symlink(
+            folder.path().join("missing.json"),
+            runs_folder.join(RUNS[0]),
+        )
+        .unwrap();
+
+        assert!(Execution::create_structures(folder.path(), current_run, true).is_err());
+
+        assert!(plots_folder.join("hashing.png").is_file());
+        let entries: Vec<_> = std::fs::read_dir(folder.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(entries, [root_folder]);
+    }
+
+    #[test]
+    fn failed_differentials_are_skipped_with_a_diagnostic() {
+        let folder = tempfile::tempdir().unwrap();
+        let run = fixtures::benchmarks(vec![fixtures::benchmark("hashing")]);
+        let [previous, current] = ["previous", "current"].map(|name| folder.path().join(name));
+        for run_folder in [&previous, &current] {
+            std::fs::create_dir_all(run_folder).unwrap();
+            std::fs::write(run_folder.join("hashing.folded"), "main;parse 1\n").unwrap();
+        }
+
+        // The differential flamegraphs can't be written when a file takes the place of their folder
+        let output = folder.path().join("differential");
+        std::fs::write(&output, "").unwrap();
+        let mut diagnostics = vec![];
+
+        let differentials = Execution::generate_differentials(
+            &previous,
+            &current,
+            &run,
+            &run,
+            &output,
+            &mut diagnostics,
+        );
+
+        assert_eq!(differentials, None);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].run, BOTH_RUNS);
+        assert!(diagnostics[0].metrics.is_empty());
+    }
+
+    #[test]
+    fn failed_hot_functions_are_skipped_with_a_diagnostic() {
+        let folder = tempfile::tempdir().unwrap();
+        let run = fixtures::benchmarks(vec![fixtures::benchmark("hashing")]);
+        let [previous, current] = ["previous", "current"].map(|name| folder.path().join(name));
+        for run_folder in [&previous, &current] {
+            std::fs::create_dir_all(run_folder).unwrap();
+            std::fs::write(run_folder.join("hashing.folded"), "main;parse 1\n").unwrap();
+        }
+        let mut diagnostics = vec![];
+
+        // The hot functions can't be written when a folder takes the place of their file
+        let output = folder.path().join("hot_functions.json");
+        std::fs::create_dir_all(&output).unwrap();
+        let hot_functions = Execution::write_hot_functions(
+            &previous,
+            &current,
+            &run,
+            &run,
+            20,
+            &output,
+            &mut diagnostics,
+        );
+        assert_eq!(hot_functions, None);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].run, BOTH_RUNS);
+
+        let output = folder.path().join("written.json");
+        let hot_functions = Execution::write_hot_functions(
+            &previous,
+            &current,
+            &run,
+            &run,
+            20,
+            &output,
+            &mut diagnostics,
+        );
+        assert_eq!(hot_functions, Some(output));
+        assert_eq!(diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn rebuild_compares_the_stored_flamegraphs_again() {
+        let folder = tempfile::tempdir().unwrap();
+        let run = fixtures::benchmarks(vec![fixtures::benchmark("hashing")]);
+        let mut execution = fixtures::execution(
+            folder.path(),
+            "benchmarks_2024-10-23_10:00:00",
+            &run,
+            &run,
+            crate::types::PRInformation::default(),
+        );
+        let flamegraphs = ["previous", "current"].map(|name| {
+            let run_folder = execution.flamegraphs_folder.join(name);
+            std::fs::create_dir_all(&run_folder).unwrap();
+            std::fs::write(run_folder.join("hashing.folded"), "main;parse 1\n").unwrap();
+            run_folder
+        });
+        execution.flamegraphs = Some(flamegraphs.to_vec());
+        execution.diagnostics.push(Diagnostic::new(
+            BOTH_RUNS,
+            &[],
+            &"The hot functions of the execution are skipped".into(),
+        ));
+
+        execution
+            .rebuild(true, &PhaseColors::load(None).unwrap())
+            .unwrap();
+
+        assert_eq!(
+            execution.differential_flamegraphs,
+            Some(vec![execution
+                .flamegraphs_folder
+                .join("differential")
+                .join("hashing.svg")])
+        );
+        assert_eq!(execution.hot_functions().unwrap().len(), 1);
+        assert!(execution.diagnostics.is_empty());
+    }
+}
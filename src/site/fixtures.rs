@@ -0,0 +1,111 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use super::{execution::Execution, flamegraph::DEFAULT_HOT_FUNCTIONS, stats};
+use crate::cli::PlotFormat;
+use crate::types::{
+    Benchmark, BenchmarkFrame, BenchmarkPhase, Benchmarks, PRInformation, SystemSpecs,
+};
+
+/// A benchmark of one second with a single phase, two frames and asm information
+pub fn benchmark(name: &str) -> Benchmark {
+    let frame = |millis| BenchmarkFrame {
+        timestamp: Duration::from_millis(millis),
+        relative_timestamp: Duration::from_millis(millis),
+        cpu_usage: 50.0,
+        memory_usage: 1000,
+        virtual_memory_usage: 2000,
+        disk_total_written_bytes: 0,
+        disk_written_bytes: 0,
+        disk_total_read_bytes: 0,
+        disk_read_bytes: 0,
+    };
+
+    Benchmark {
+        name: name.to_string(),
+        path: PathBuf::from("/projects").join(name),
+        start_time: Some(Duration::ZERO),
+        end_time: Some(Duration::from_secs(1)),
+        phases: vec![BenchmarkPhase {
+            name: "compile to ast".to_string(),
+            start_time: Some(Duration::ZERO),
+            end_time: Some(Duration::from_secs(1)),
+        }],
+        frames: Arc::new(Mutex::new(vec![frame(0), frame(500)])),
+        asm_information: Some(serde_json::json!({
+            "bytecode_size": 100,
+            "data_section": { "size": 10 }
+        })),
+        hyperfine: None,
+    }
+}
+
+/// A run of the benchmarks
+pub fn benchmarks(benchmarks: Vec<Benchmark>) -> Benchmarks {
+    Benchmarks {
+        total_time: Duration::from_secs(1),
+        system_specs: SystemSpecs::default(),
+        benchmarks,
+        forc_version: "0.66.0".to_string(),
+        compiler_hash: "abc".to_string(),
+        benchmarks_datetime: "2024-10-22 10:00:00".to_string(),
+    }
+}
+
+/// Write a run to `<folder>/<file_name>`
+pub fn write_run(folder: &Path, file_name: &str, benchmarks: &Benchmarks) -> PathBuf {
+    std::fs::create_dir_all(folder).unwrap();
+    let file = folder.join(file_name);
+    std::fs::write(&file, serde_json::to_string(benchmarks).unwrap()).unwrap();
+    file
+}
+
+/// An execution of the site in `folder` comparing two runs, with its runs and stats written
+pub fn execution(
+    folder: &Path,
+    identifier: &str,
+    previous: &Benchmarks,
+    current: &Benchmarks,
+    github_information: PRInformation,
+) -> Execution {
+    let root_folder = folder.join("data").join(identifier);
+    let runs_folder = root_folder.join("runs");
+    let stats_folder = root_folder.join("stats");
+
+    let previous_benchmarks = write_run(&runs_folder, "previous.json", previous);
+    let current_benchmarks = write_run(&runs_folder, &format!("{identifier}.json"), current);
+
+    std::fs::create_dir_all(&stats_folder).unwrap();
+    std::fs::write(
+        stats_folder.join(format!("{identifier}.json")),
+        serde_json::to_string(&stats::compare(previous, current)).unwrap(),
+    )
+    .unwrap();
+
+    Execution {
+        previous_benchmarks: previous_benchmarks.clone(),
+        current_benchmarks: current_benchmarks.clone(),
+        github_information,
+        plots_folder: root_folder.join("plots"),
+        flamegraphs_folder: root_folder.join("flamegraphs"),
+        root_folder,
+        runs_folder,
+        stats_folder,
+        runs: vec![previous_benchmarks, current_benchmarks],
+        stats: vec![],
+        plots: None,
+        flamegraphs: None,
+        pinned_baseline: false,
+        interactive_charts: false,
+        overlay: false,
+        overlay_delta: false,
+        plot_format: PlotFormat::default(),
+        differential_flamegraphs: None,
+        hot_functions: None,
+        hot_function_count: DEFAULT_HOT_FUNCTIONS,
+        diagnostics: vec![],
+    }
+}
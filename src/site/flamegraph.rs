@@ -0,0 +1,605 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{Error, Result},
+    site::stats,
+    types::Benchmarks,
+    wrap,
+};
+
+/// The number of functions by self and by inclusive samples in the hot functions table when none
+/// is given
+pub const DEFAULT_HOT_FUNCTIONS: usize = 20;
+
+/// The hot functions of every benchmark by benchmark name
+pub type HotFunctions = BTreeMap<String, Vec<HotFunction>>;
+
+/// The share of the samples of a function in the previous and current runs, in percent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotFunction {
+    pub name: String,
+    /// The share of the samples spent in the function itself.
+    pub previous_self: f64,
+    pub current_self: f64,
+    /// The share of the samples spent in the function and its callees.
+    pub previous_inclusive: f64,
+    pub current_inclusive: f64,
+}
+
+/// A frame of a flamegraph, with its position in samples
+struct Frame {
+    name: String,
+    depth: usize,
+    x: u64,
+    width: u64,
+}
+
+/// Get the flamegraph of a benchmark in a flamegraph folder
+#[must_use]
+pub fn flamegraph_file(folder: &Path, benchmark_name: &str) -> PathBuf {
+    folder.join(format!("{benchmark_name}.svg"))
+}
+
+/// Read the folded stacks of a benchmark from a flamegraph folder, preferring `<name>.folded`
+/// over the frames of `<name>.svg`. Returns `None` when neither holds any stacks.
+///
+/// # Errors
+///
+/// Returns an error if a flamegraph file exists but can't be read.
+pub fn read_folded(folder: &Path, benchmark_name: &str) -> Result<Option<Vec<String>>> {
+    let folded_file = folder.join(format!("{benchmark_name}.folded"));
+    if folded_file.is_file() {
+        let stacks = std::fs::read_to_string(&folded_file).map_err(|e| wrap!(e.into()))?;
+        return Ok(Some(stacks.lines().map(str::to_string).collect()));
+    }
+
+    let svg_file = flamegraph_file(folder, benchmark_name);
+    if svg_file.is_file() {
+        let stacks = parse_svg(&std::fs::read_to_string(&svg_file).map_err(|e| wrap!(e.into()))?);
+        return Ok((!stacks.is_empty()).then_some(stacks));
+    }
+
+    Ok(None)
+}
+
+/// Rebuild the folded stacks from the frames of a flamegraph rendered by inferno.
+/// Every frame carries its name in its title and its position in samples in the `fg:x` and `fg:w`
+/// attributes of its rectangle, the depth is derived from the `y` attribute.
+pub fn parse_svg(svg: &str) -> Vec<String> {
+    let mut frames = vec![];
+
+    let mut remaining = svg;
+    while let Some(start) = remaining.find("<title>") {
+        remaining = &remaining[start + "<title>".len()..];
+
+        let Some(end) = remaining.find("</title>") else {
+            break;
+        };
+        let title = unescape(&remaining[..end]);
+        remaining = &remaining[end..];
+
+        // The rectangle follows the title of its frame
+        let Some(rect) = remaining
+            .find("<rect")
+            .map(|start| &remaining[start..])
+            .and_then(|rect| rect.find("/>").map(|end| &rect[..end]))
+        else {
+            break;
+        };
+
+        let (Some(y), Some(x), Some(width)) = (
+            attribute(rect, "y").and_then(|y| y.parse::<f64>().ok()),
+            attribute(rect, "fg:x").and_then(|x| x.parse::<u64>().ok()),
+            attribute(rect, "fg:w").and_then(|width| width.parse::<u64>().ok()),
+        ) else {
+            continue;
+        };
+
+        // Strip the ` (<samples> samples, <percentage>%)` suffix
+        let name = title
+            .rfind(" (")
+            .map_or(title.as_str(), |index| &title[..index])
+            .to_string();
+
+        frames.push((name, y, x, width));
+    }
+
+    // The root frame `all` spans every sample and is drawn at the bottom, unless the flamegraph is
+    // inverted
+    let Some(total) = frames.iter().map(|(_, _, _, width)| *width).max() else {
+        return vec![];
+    };
+    let bottom = frames
+        .iter()
+        .map(|(_, y, _, _)| *y)
+        .fold(f64::MIN, f64::max);
+    let inverted = !frames.iter().any(|(name, y, _, width)| {
+        name == "all" && *width == total && y.total_cmp(&bottom).is_eq()
+    });
+
+    let mut levels: Vec<f64> = frames.iter().map(|(_, y, _, _)| *y).collect();
+    levels.sort_by(f64::total_cmp);
+    levels.dedup();
+    if !inverted {
+        levels.reverse();
+    }
+
+    let frames: Vec<Frame> = frames
+        .into_iter()
+        .filter_map(|(name, y, x, width)| {
+            Some(Frame {
+                name,
+                depth: levels
+                    .iter()
+                    .position(|level| level.total_cmp(&y).is_eq())?,
+                x,
+                width,
+            })
+        })
+        .collect();
+
+    fold(&frames)
+}
+
+/// Fold the frames into stacks with their own samples, skipping the root frame
+fn fold(frames: &[Frame]) -> Vec<String> {
+    let mut depths: BTreeMap<usize, Vec<&Frame>> = BTreeMap::new();
+    for frame in frames {
+        depths.entry(frame.depth).or_default().push(frame);
+    }
+    for frames in depths.values_mut() {
+        frames.sort_by_key(|frame| frame.x);
+    }
+
+    // Find the frame at a depth that contains a sample
+    let find = |depth: usize, x: u64| {
+        let frames = depths.get(&depth)?;
+        let index = frames
+            .partition_point(|frame| frame.x <= x)
+            .checked_sub(1)?;
+        let frame = frames[index];
+        (x < frame.x + frame.width).then_some(frame)
+    };
+
+    let mut stacks: BTreeMap<String, u64> = BTreeMap::new();
+
+    for frame in frames.iter().filter(|frame| frame.depth > 0) {
+        let mut names = vec![frame.name.as_str()];
+        for depth in (1..frame.depth).rev() {
+            match find(depth, frame.x) {
+                Some(parent) => names.push(parent.name.as_str()),
+                None => break,
+            }
+        }
+        names.reverse();
+
+        let children: u64 = depths
+            .get(&(frame.depth + 1))
+            .map(|children| {
+                children
+                    .iter()
+                    .filter(|child| child.x >= frame.x && child.x < frame.x + frame.width)
+                    .map(|child| child.width)
+                    .sum()
+            })
+            .unwrap_or_default();
+
+        let samples = frame.width.saturating_sub(children);
+        if samples > 0 {
+            *stacks.entry(names.join(";")).or_default() += samples;
+        }
+    }
+
+    stacks
+        .into_iter()
+        .map(|(stack, samples)| format!("{stack} {samples}"))
+        .collect()
+}
+
+/// Get the value of an attribute of an element
+fn attribute<'a>(element: &'a str, name: &str) -> Option<&'a str> {
+    let pattern = format!(" {name}=\"");
+    let start = element.find(&pattern)? + pattern.len();
+    let end = element[start..].find('"')?;
+    Some(&element[start..start + end])
+}
+
+/// Unescape the entities written by inferno in the titles
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Render the differential flamegraph of two runs of a benchmark.
+/// The frames have the shape of the current run and are coloured by how much their share of the
+/// samples grew (red) or shrank (blue) since the previous run.
+///
+/// # Errors
+///
+/// Returns an error if the flamegraph can't be rendered or written.
+pub fn generate_differential(
+    benchmark_name: &str,
+    previous: &[String],
+    current: &[String],
+    output_file: &Path,
+) -> Result<()> {
+    let mut folded = vec![];
+
+    // Normalizing the previous run to the samples of the current run compares the shares
+    inferno::differential::from_readers(
+        inferno::differential::Options {
+            normalize: true,
+            ..Default::default()
+        },
+        previous.join("\n").as_bytes(),
+        current.join("\n").as_bytes(),
+        &mut folded,
+    )
+    .map_err(|e| wrap!(e.into()))?;
+    let folded = String::from_utf8(folded).map_err(|e| wrap!(e.into()))?;
+
+    let mut options = inferno::flamegraph::Options::default();
+    options.title = format!("Differential Flamegraph: {benchmark_name}");
+    options.subtitle = Some("Red grew and blue shrank since the previous run".to_string());
+
+    let file = std::fs::File::create(output_file).map_err(|e| wrap!(e.into()))?;
+    inferno::flamegraph::from_lines(&mut options, folded.lines(), std::io::BufWriter::new(file))
+        .map_err(|e| wrap!(Error::Flamegraph(e.to_string())))?;
+
+    Ok(())
+}
+
+/// Render the differential flamegraph of every benchmark with stacks in both flamegraph folders
+/// to `<output_folder>/<current benchmark name>.svg`
+///
+/// # Errors
+///
+/// Returns an error if the output folder can't be created or a flamegraph can't be read.
+pub fn generate_differentials(
+    previous_folder: &Path,
+    current_folder: &Path,
+    previous_benchmarks: &Benchmarks,
+    current_benchmarks: &Benchmarks,
+    output_folder: &Path,
+) -> Result<Vec<PathBuf>> {
+    let mut files = vec![];
+
+    for benchmark in &current_benchmarks.benchmarks {
+        let Some(previous_benchmark) = stats::find_match(previous_benchmarks, benchmark) else {
+            continue;
+        };
+        let (Some(previous), Some(current)) = (
+            read_folded(previous_folder, &previous_benchmark.name).map_err(|e| wrap!(e))?,
+            read_folded(current_folder, &benchmark.name).map_err(|e| wrap!(e))?,
+        ) else {
+            continue;
+        };
+
+        if !output_folder.exists() {
+            std::fs::create_dir_all(output_folder).map_err(|e| wrap!(e.into()))?;
+        }
+
+        let file = flamegraph_file(output_folder, &benchmark.name);
+        generate_differential(&benchmark.name, &previous, &current, &file).map_err(|e| wrap!(e))?;
+        files.push(file);
+    }
+
+    Ok(files)
+}
+
+/// Count the self and inclusive samples of every function in the folded stacks, returning them
+/// with the total number of samples. Recursive functions count once per stack.
+fn function_samples(stacks: &[String]) -> (BTreeMap<String, (u64, u64)>, u64) {
+    let mut functions: BTreeMap<String, (u64, u64)> = BTreeMap::new();
+    let mut total = 0;
+
+    for line in stacks {
+        let Some((stack, samples)) = line.trim().rsplit_once(' ') else {
+            continue;
+        };
+        let Ok(samples) = samples.parse::<u64>() else {
+            continue;
+        };
+        total += samples;
+
+        let frames: Vec<&str> = stack.split(';').collect();
+        let mut seen = vec![];
+        for frame in &frames {
+            if !seen.contains(frame) {
+                seen.push(*frame);
+                functions.entry((*frame).to_string()).or_default().1 += samples;
+            }
+        }
+        if let Some(leaf) = frames.last() {
+            functions.entry((*leaf).to_string()).or_default().0 += samples;
+        }
+    }
+
+    (functions, total)
+}
+
+/// Get the top `count` functions by self samples and the top `count` functions by inclusive
+/// samples in either run, ordered by their share of self samples in the current run
+#[allow(clippy::cast_precision_loss)]
+#[must_use]
+pub fn hot_functions(previous: &[String], current: &[String], count: usize) -> Vec<HotFunction> {
+    let (previous_functions, previous_total) = function_samples(previous);
+    let (current_functions, current_total) = function_samples(current);
+
+    let share = |functions: &BTreeMap<String, (u64, u64)>, total: u64, name: &str| {
+        let (self_samples, inclusive_samples) = functions.get(name).copied().unwrap_or_default();
+        if total == 0 {
+            (0.0, 0.0)
+        } else {
+            (
+                self_samples as f64 / total as f64 * 100.0,
+                inclusive_samples as f64 / total as f64 * 100.0,
+            )
+        }
+    };
+
+    let mut functions: Vec<HotFunction> = previous_functions
+        .keys()
+        .chain(current_functions.keys())
+        .collect::<std::collections::BTreeSet<_>>()
+        .into_iter()
+        .map(|name| {
+            let (previous_self, previous_inclusive) =
+                share(&previous_functions, previous_total, name);
+            let (current_self, current_inclusive) = share(&current_functions, current_total, name);
+            HotFunction {
+                name: name.clone(),
+                previous_self,
+                current_self,
+                previous_inclusive,
+                current_inclusive,
+            }
+        })
+        .collect();
+
+    let top = |functions: &mut Vec<HotFunction>, value: fn(&HotFunction) -> f64| {
+        functions.sort_by(|a, b| value(b).total_cmp(&value(a)));
+        functions
+            .iter()
+            .take(count)
+            .map(|function| function.name.clone())
+            .collect::<Vec<_>>()
+    };
+
+    let mut names = top(&mut functions, |function| {
+        function.previous_self.max(function.current_self)
+    });
+    names.extend(top(&mut functions, |function| {
+        function.previous_inclusive.max(function.current_inclusive)
+    }));
+
+    functions.retain(|function| names.contains(&function.name));
+    functions.sort_by(|a, b| b.current_self.total_cmp(&a.current_self));
+    functions
+}
+
+/// Write the hot functions of every benchmark with stacks in both flamegraph folders, by the name
+/// of the benchmark in the current run, to `output_file`
+///
+/// # Errors
+///
+/// Returns an error if a flamegraph can't be read or the output file can't be written.
+pub fn write_hot_functions(
+    previous_folder: &Path,
+    current_folder: &Path,
+    previous_benchmarks: &Benchmarks,
+    current_benchmarks: &Benchmarks,
+    count: usize,
+    output_file: &Path,
+) -> Result<()> {
+    let mut functions = HotFunctions::new();
+
+    for benchmark in &current_benchmarks.benchmarks {
+        let Some(previous_benchmark) = stats::find_match(previous_benchmarks, benchmark) else {
+            continue;
+        };
+        let (Some(previous), Some(current)) = (
+            read_folded(previous_folder, &previous_benchmark.name).map_err(|e| wrap!(e))?,
+            read_folded(current_folder, &benchmark.name).map_err(|e| wrap!(e))?,
+        ) else {
+            continue;
+        };
+
+        functions.insert(
+            benchmark.name.clone(),
+            hot_functions(&previous, &current, count),
+        );
+    }
+
+    std::fs::write(
+        output_file,
+        serde_json::to_string(&functions).map_err(|e| wrap!(e.into()))?,
+    )
+    .map_err(|e| wrap!(e.into()))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site::fixtures;
+
+    /// A flamegraph of `main` calling `parse` and `emit<T>`, with the root frame at `y`
+    fn svg(levels: [u32; 3]) -> String {
+        let [root, main, leaves] = levels;
+        format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg"><g id="frames">
+<g><title>all (10 samples, 100%)</title><rect x="0%" y="{root}" width="100%" height="15" fill="rgb(0,0,0)" fg:x="0" fg:w="10"/><text x="0.25%" y="{root}">all</text></g>
+<g><title>main (10 samples, 100%)</title><rect x="0%" y="{main}" width="100%" height="15" fill="rgb(0,0,0)" fg:x="0" fg:w="10"/><text x="0.25%" y="{main}">main</text></g>
+<g><title>parse (6 samples, 60.00%)</title><rect x="0%" y="{leaves}" width="60%" height="15" fill="rgb(0,0,0)" fg:x="0" fg:w="6"/><text x="0.25%" y="{leaves}">parse</text></g>
+<g><title>emit&lt;T&gt; (3 samples, 30.00%)</title><rect x="60%" y="{leaves}" width="30%" height="15" fill="rgb(0,0,0)" fg:x="6" fg:w="3"/><text x="60.25%" y="{leaves}">emit&lt;T&gt;</text></g>
+</g></svg>"#
+        )
+    }
+
+    fn stacks(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn parse_svg_folds_the_frames_into_stacks() {
+        let expected = stacks(&["main 1", "main;emit<T> 3", "main;parse 6"]);
+
+        assert_eq!(parse_svg(&svg([68, 52, 36])), expected);
+        // Inverted flamegraphs draw the root frame at the top
+        assert_eq!(parse_svg(&svg([4, 20, 36])), expected);
+    }
+
+    #[test]
+    fn parse_svg_ignores_svgs_without_frames() {
+        assert!(
+            parse_svg("<svg xmlns='http://www.w3.org/2000/svg'><text>hashing</text></svg>")
+                .is_empty()
+        );
+        assert!(parse_svg("<svg><title>all</title></svg>").is_empty());
+    }
+
+    #[test]
+    fn parse_svg_reads_the_flamegraphs_of_inferno() {
+        let folded = stacks(&["main 1", "main;emit 4", "main;parse 2", "main;parse;lex 3"]);
+
+        let mut svg = vec![];
+        inferno::flamegraph::from_lines(
+            &mut inferno::flamegraph::Options::default(),
+            folded.iter().map(String::as_str),
+            &mut svg,
+        )
+        .unwrap();
+
+        assert_eq!(parse_svg(&String::from_utf8(svg).unwrap()), folded);
+    }
+
+    #[test]
+    fn read_folded_prefers_the_folded_stacks() {
+        let folder = tempfile::tempdir().unwrap();
+        std::fs::write(folder.path().join("hashing.svg"), svg([68, 52, 36])).unwrap();
+        std::fs::write(folder.path().join("sorting.svg"), svg([68, 52, 36])).unwrap();
+        std::fs::write(folder.path().join("sorting.folded"), "main;sort 4\n").unwrap();
+        std::fs::write(folder.path().join("empty.svg"), "<svg></svg>").unwrap();
+
+        assert_eq!(
+            read_folded(folder.path(), "hashing").unwrap(),
+            Some(stacks(&["main 1", "main;emit<T> 3", "main;parse 6"]))
+        );
+        assert_eq!(
+            read_folded(folder.path(), "sorting").unwrap(),
+            Some(stacks(&["main;sort 4"]))
+        );
+        assert_eq!(read_folded(folder.path(), "empty").unwrap(), None);
+        assert_eq!(read_folded(folder.path(), "missing").unwrap(), None);
+    }
+
+    #[test]
+    fn differentials_are_generated_for_the_benchmarks_of_both_runs() {
+        let folder = tempfile::tempdir().unwrap();
+        let previous = folder.path().join("previous");
+        let current = folder.path().join("current");
+        let output = folder.path().join("differential");
+        for (run, file, stacks) in [
+            (&previous, "hashing.folded", "main;parse 6\n"),
+            (&current, "hashing v2.folded", "main;parse 2\n"),
+            (&current, "sorting.folded", "main;sort 4\n"),
+        ] {
+            std::fs::create_dir_all(run).unwrap();
+            std::fs::write(run.join(file), stacks).unwrap();
+        }
+        // The renamed benchmark is matched by its path
+        let mut renamed = fixtures::benchmark("hashing v2");
+        renamed.path = PathBuf::from("/projects/hashing");
+
+        let files = generate_differentials(
+            &previous,
+            &current,
+            &fixtures::benchmarks(vec![fixtures::benchmark("hashing")]),
+            &fixtures::benchmarks(vec![renamed, fixtures::benchmark("sorting")]),
+            &output,
+        )
+        .unwrap();
+
+        assert_eq!(files, vec![output.join("hashing v2.svg")]);
+        let svg = std::fs::read_to_string(&files[0]).unwrap();
+        assert!(svg.contains("Differential Flamegraph: hashing v2"));
+        assert_eq!(parse_svg(&svg), stacks(&["main;parse 2"]));
+    }
+
+    #[test]
+    fn hot_functions_compare_the_shares_of_the_samples() {
+        let previous = stacks(&["main;parse 6", "main;emit 4"]);
+        let current = stacks(&["main;parse 2", "main;emit 8"]);
+
+        let functions = hot_functions(&previous, &current, 1);
+
+        let names: Vec<_> = functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["emit", "main"]);
+
+        let emit = &functions[0];
+        assert!((emit.previous_self - 40.0).abs() < 1e-9);
+        assert!((emit.current_self - 80.0).abs() < 1e-9);
+        assert!((emit.current_inclusive - 80.0).abs() < 1e-9);
+
+        let main = &functions[1];
+        assert!(main.current_self.abs() < 1e-9);
+        assert!((main.previous_inclusive - 100.0).abs() < 1e-9);
+        assert!((main.current_inclusive - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn recursive_functions_count_once_per_stack() {
+        let (functions, total) = function_samples(&stacks(&["main;visit;visit 5", "main 5"]));
+
+        assert_eq!(total, 10);
+        assert_eq!(functions["visit"], (5, 5));
+        assert_eq!(functions["main"], (5, 10));
+    }
+
+    #[test]
+    fn write_hot_functions_keeps_the_benchmarks_of_both_runs() {
+        let folder = tempfile::tempdir().unwrap();
+        let previous = folder.path().join("previous");
+        let current = folder.path().join("current");
+        let output_file = folder.path().join("hot_functions.json");
+        for (run, stacks) in [(&previous, "main;parse 6\n"), (&current, "main;parse 2\n")] {
+            std::fs::create_dir_all(run).unwrap();
+            std::fs::write(run.join("hashing.folded"), stacks).unwrap();
+        }
+        std::fs::write(current.join("sorting.folded"), "main;sort 4\n").unwrap();
+
+        let benchmarks = fixtures::benchmarks(vec![
+            fixtures::benchmark("hashing"),
+            fixtures::benchmark("sorting"),
+        ]);
+        write_hot_functions(
+            &previous,
+            &current,
+            &benchmarks,
+            &benchmarks,
+            10,
+            &output_file,
+        )
+        .unwrap();
+
+        let functions: HotFunctions =
+            serde_json::from_str(&std::fs::read_to_string(&output_file).unwrap()).unwrap();
+        assert_eq!(functions.keys().collect::<Vec<_>>(), ["hashing"]);
+        let names: Vec<_> = functions["hashing"]
+            .iter()
+            .map(|f| f.name.as_str())
+            .collect();
+        assert_eq!(names, ["parse", "main"]);
+    }
+}
@@ -1,69 +1,99 @@
+use std::fmt::Write;
 use std::path::{Path, PathBuf};
 
-use crate::site::dynosite::DynoSite;
-use crate::types::Benchmarks;
-
-use crate::error::Result;
+use crate::cli::PlotFormat;
+use crate::site::{
+    chart::{self, CHART_METRICS},
+    diagnostics::Diagnostic,
+    dynosite::DynoSite,
+    escape::{escape, escape_url},
+    execution::{parse_json_benchmarks, Execution},
+    flamegraph,
+    hyperfine::{Significance, ALPHA},
+    palette::{self, PhaseColors},
+    regression::{percentage_change, METRICS},
+    stats,
+    trend::{self, Trend, TREND_METRICS},
+};
+use crate::types::{Benchmark, Benchmarks, Stats};
+
+use crate::error::{warn, Error, Result};
 use crate::wrap;
 
-/// Generate the HTML for the site
-pub fn generate(site: &DynoSite, data_only: bool) -> Result<String> {
-    let mut html = generate_header();
+/// The path from the pages of the executions and benchmarks back to the site root
+const PAGE_ROOT: &str = "../../";
 
-    // TODO : We might wanna have a check that makes sure the system settings are the same for all the benchmarks
-    let system_settings_benchmarks = &serde_json::from_str(
-        &std::fs::read_to_string(&site.data.executions[0].current_benchmarks)
-            .map_err(|e| wrap!(e.into()))?,
-    )
-    .map_err(|e| wrap!(e.into()))?;
+/// A page of the site, with its path relative to the site root
+pub struct Page {
+    pub path: PathBuf,
+    pub title: String,
+    pub content: String,
+}
 
-    // Create the container
-    html.push_str("<div class=\"container-fluid\">");
+/// Generate the HTML content of the index page and of the pages of every execution and benchmark
+///
+/// # Errors
+///
+/// Returns an error if a page can't be rendered from its template.
+pub fn generate(
+    site: &DynoSite,
+    trends: &[Trend],
+    data_only: bool,
+    plot_format: PlotFormat,
+    colors: &PhaseColors,
+) -> Result<Vec<Page>> {
+    let mut pages = vec![];
+    let mut html = String::new();
 
-    // Title of the page
-    html.push_str("<h1>Forc Performance Profiler</h1>");
-    // Version of the page
-    html.push_str(&format!("<p>Version: {}</p>", env!("CARGO_PKG_VERSION")));
+    // Pinned baseline of the site
+    if let Some(pinned_baseline) = &site.data.pinned_baseline {
+        let _ = write!(
+            html,
+            "<p><i class=\"material-icons tiny\">push_pin</i> Pinned Baseline: {}</p>",
+            pinned_baseline
+                .file_name()
+                .ok_or_else(|| wrap!("Failed to get the pinned baseline file name.".into()))?
+                .to_str()
+                .ok_or_else(|| wrap!("Failed to get the pinned baseline file name as str.".into()))
+                .map(escape)?
+        );
+    }
 
-    // Generate the system specifications
-    html.push_str(&generate_system_specs(system_settings_benchmarks));
+    // Generate the system specifications, a site without executions has none to show
+    // TODO : We might wanna have a check that makes sure the system settings are the same for all the benchmarks
+    if let Some(execution) = site.data.executions.first() {
+        let system_settings_benchmarks =
+            parse_json_benchmarks(&execution.current_benchmarks).map_err(|e| wrap!(e))?;
+        html.push_str(&generate_system_specs(&system_settings_benchmarks));
+    } else {
+        html.push_str("<p>The site has no executions.</p>");
+    }
+
+    // Generate the trend history across all executions
+    html.push_str(&generate_trend_history(trends, data_only, plot_format));
 
-    // Add collapsible structure for each folder in the data directory
+    // Add an overview of every execution, linking to the page of the execution
     html.push_str("<h5>Run Collection</h5>");
-    html.push_str("<ul class=\"collapsible\">");
+    html.push_str("<table class=\"striped\">");
+    html.push_str("<thead><tr><th>Execution</th><th>PR Title</th><th>PR Hash</th><th>Date and Time</th><th>Benchmarks</th></tr></thead>");
+    html.push_str("<tbody>");
 
-    // Get all the folders in the data directory
-    let folders: Vec<_> = std::fs::read_dir(&site.data_folder)
+    // Get all the folders in the data directory, leaving out the hidden staging folders of the
+    // executions being added
+    let mut sorted_folders = std::fs::read_dir(&site.data_folder)
         .map_err(|e| wrap!(e.into()))?
-        .collect();
-
-    // Sort folders by timestamp in descending order (newest first)
-    let mut sorted_folders = folders;
-
-    // TODO : refactor this
-    sorted_folders.sort_by(|a, b| {
-        let folder_name_a = a
-            .as_ref()
-            .expect("Failed to get folder name a as ref")
-            .file_name()
-            .into_string()
-            .expect("Failed to get folder name a as string");
-        let folder_name_b = b
-            .as_ref()
-            .expect("Failed to get folder name b as ref")
-            .file_name()
-            .into_string()
-            .expect("Failed to get folder name b as string");
-
-        let timestamp_a = parse_timestamp(&folder_name_a).expect("Failed to parse timestamp a");
-        let timestamp_b = parse_timestamp(&folder_name_b).expect("Failed to parse timestamp b");
-
-        timestamp_b.cmp(&timestamp_a)
+        .collect::<std::io::Result<Vec<_>>>()
+        .map_err(|e| wrap!(e.into()))?;
+    sorted_folders.retain(|folder| !folder.file_name().to_string_lossy().starts_with('.'));
+
+    // Sort folders by timestamp in descending order (newest first), with the folders without a
+    // timestamp last
+    sorted_folders.sort_by_cached_key(|folder| {
+        std::cmp::Reverse(parse_timestamp(&folder.file_name().to_string_lossy()).ok())
     });
 
     // Iterate over the sorted folders
     for folder in sorted_folders {
-        let folder = folder.map_err(|e| wrap!(e.into()))?;
         let folder_path = folder.path();
 
         // Get the current execution
@@ -83,241 +113,529 @@ pub fn generate(site: &DynoSite, data_only: bool) -> Result<String> {
                 .to_str()
                 .ok_or_else(|| wrap!("Failed to get file name as str from folder path.".into()))?;
 
-            let previous_benchmarks: Benchmarks = serde_json::from_str(
-                &std::fs::read_to_string(&current_execution.previous_benchmarks)
-                    .map_err(|e| wrap!(e.into()))?,
-            )
-            .map_err(|e| wrap!(e.into()))?;
-
             let current_benchmarks: Benchmarks = serde_json::from_str(
                 &std::fs::read_to_string(&current_execution.current_benchmarks)
                     .map_err(|e| wrap!(e.into()))?,
             )
             .map_err(|e| wrap!(e.into()))?;
 
-            html.push_str("<li>");
-            html.push_str(&format!(
-                "<div class=\"collapsible-header\"><i class=\"material-icons\">folder</i>{folder_name}</div>",
-            ));
-            html.push_str("<div class=\"collapsible-body\">");
-            html.push_str("<div class=\"container-fluid\">");
-            html.push_str(&format!(
-                "<h5>PR Link : {}</h5>",
-                current_execution.github_information.link
-            ));
-            html.push_str(&format!(
-                "<h5>PR Title : {}</h5>",
-                current_execution.github_information.title
-            ));
-            html.push_str(&format!(
-                "<h5>PR Hash : {}</h5>",
-                current_execution.github_information.hash
-            ));
-            html.push_str(
-                &generate_previous_current_information(
-                    &previous_benchmarks,
-                    &current_benchmarks,
-                    &current_execution.previous_benchmarks,
-                    &current_execution.current_benchmarks,
-                )
-                .map_err(|e| wrap!(e))?,
+            let _ = write!(html, "<tr id=\"{}\">", escape(folder_name));
+            let _ = write!(
+                html,
+                "<td><i class=\"material-icons tiny\">folder</i> <a href=\"{}\">{}</a></td>",
+                escape_url(&execution_page(folder_name).display().to_string()),
+                escape(folder_name)
+            );
+            let _ = write!(
+                html,
+                "<td>{}</td><td>{}</td><td>{}</td><td>{}</td>",
+                escape(&current_execution.github_information.title),
+                escape(&current_execution.github_information.hash),
+                escape(&current_benchmarks.benchmarks_datetime),
+                current_benchmarks.benchmarks.len()
             );
+            html.push_str("</tr>");
 
-            // Add collapsible structure for the stats collection
-            html.push_str("<ul class=\"collapsible\">");
-            let stats_file_path = PathBuf::from(format!(
-                "{}/{}.json",
-                current_execution.stats_folder.display(),
-                current_execution
-                    .root_folder
-                    .components()
-                    .last()
-                    .ok_or_else(|| wrap!(
-                        "Failed to get the last component of the root folder path.".into()
-                    ))?
-                    .as_os_str()
-                    .to_str()
-                    .ok_or_else(|| wrap!(
-                        "Failed to get the last component of the root folder path as str.".into()
-                    ))?,
-            ));
-            let stats_file_string =
-                &std::fs::read_to_string(&stats_file_path).map_err(|e| wrap!(e.into()))?;
+            pages.extend(
+                generate_execution(current_execution, folder_name, data_only, colors)
+                    .map_err(|e| wrap!(e))?,
+            );
+        }
+    }
 
-            let stats_collection: crate::types::Collection =
-                serde_json::from_str(stats_file_string).map_err(|e| wrap!(e.into()))?;
+    html.push_str("</tbody></table>");
 
-            for (file_name, stats) in &stats_collection.0 {
-                if stats_file_path
-                    .to_str()
-                    .ok_or_else(|| wrap!("Failed to get the stats file path as str.".into()))?
-                    .contains(folder_name)
-                {
-                    html.push_str("<li>");
-                    html.push_str(&format!(
-                    "<div class=\"collapsible-header\"><i class=\"material-icons\">insert_chart</i>{file_name}</div>"
-                ));
-                    html.push_str("<div class=\"collapsible-body\"><table class=\"striped\">");
-                    html.push_str(
-                    "<thead><tr><th>Metric</th><th>Regression(Red)/Improvement(Green)</th></tr></thead>",
-                );
-                    html.push_str("<tbody>");
-
-                    let metrics = [
-                        ("CPU Usage", stats.cpu_usage.1),
-                        ("Memory Usage", stats.memory_usage.1),
-                        ("Virtual Memory Usage", stats.virtual_memory_usage.1),
-                        ("Disk Total Written Bytes", stats.disk_total_written_bytes.1),
-                        ("Disk Written Bytes", stats.disk_written_bytes.1),
-                        ("Disk Total Read Bytes", stats.disk_total_read_bytes.1),
-                        ("Disk Read Bytes", stats.disk_read_bytes.1),
-                        ("Bytecode Size", stats.bytecode_size.1),
-                        ("Data Section Size", stats.data_section_size.1),
-                        ("Time", stats.time.1),
-                    ];
-
-                    for (metric, value) in &metrics {
-                        let color = if *value > 0.0 {
-                            "red"
-                        } else if *value < 0.0 {
-                            "green"
-                        } else {
-                            "white"
-                        };
-                        html.push_str(&format!(
-                        "<tr><td>{metric}</td><td style=\"color: {color}\">{value:.2}%</td></tr>"
-                    ));
-                    }
+    pages.insert(
+        0,
+        Page {
+            path: PathBuf::from("index.html"),
+            title: "Forc Performance Profiler".to_string(),
+            content: html,
+        },
+    );
 
-                    html.push_str("<table class=\"striped\">");
-                    html.push_str("<thead><tr><th>Metric</th><th>Previous Benchmark</th><th>Current Benchmark</th></tr></thead>");
-                    html.push_str("<tbody>");
-
-                    html.push_str(&format!(
-                        "<tr><td>Bytecode Size</td><td>{} bytes</td><td>{} bytes</td></tr>",
-                        previous_benchmarks
-                            .benchmarks
-                            .iter()
-                            .find(|b| file_name.contains(&b.name))
-                            .ok_or_else(|| wrap!("Failed to find the benchmark in the previous benchmarks.".into()))?
-                            .asm_information
-                            .clone()
-                            .ok_or_else(|| wrap!("Failed to get the asm information for bytecode size from previous benchmakrs.".into()))?["bytecode_size"],
-                        current_benchmarks
-                            .benchmarks
-                            .iter()
-                            .find(|b| file_name.contains(&b.name))
-                            .ok_or_else(|| wrap!("Failed to find the benchmark in the current benchmarks.".into()))?
-                            .asm_information
-                            .clone()
-                            .ok_or_else(|| wrap!("Failed to get the asm information for bytecode size from current benchmars.".into()))?["bytecode_size"]
-                    ));
-
-                    html.push_str(&format!(
-                    "<tr><td>Data Section</td><td>Size : {} - Used : {}</td><td>Size : {} - Used : {}</td></tr>",
-                    previous_benchmarks
-                        .benchmarks
-                        .iter()
-                        .find(|b| file_name.contains(&b.name))
-                        .ok_or_else(|| wrap!("Failed to find the benchmark in the previous benchmarks.".into()))?
-                        .asm_information
-                        .clone()
-                        .ok_or_else(|| wrap!("Failed to get the asm information for data section size from the previous benchmarks.".into()))?["data_section"]["size"],
-                    previous_benchmarks
-                        .benchmarks
-                        .iter()
-                        .find(|b| file_name.contains(&b.name))
-                        .ok_or_else(|| wrap!("Failed to find the benchmark in the previous benchmarks.".into()))?
-                        .asm_information
-                        .clone()
-                        .ok_or_else(|| wrap!("Failed to get the asm information for data section used from the previous benchmarks.".into()))?["data_section"]["used"],
-                    current_benchmarks
-                        .benchmarks
-                        .iter()
-                        .find(|b| file_name.contains(&b.name))
-                        .ok_or_else(|| wrap!("Failed to find the benchmark in the current benchmarks.".into()))?
-                        .asm_information
-                        .clone()
-                        .ok_or_else(|| wrap!("Failed to get the asm information for data section size from the current benchmarks.".into()))?["data_section"]["size"],
-                    current_benchmarks
-                        .benchmarks
-                        .iter()
-                        .find(|b| file_name.contains(&b.name))
-                        .ok_or_else(|| wrap!("Failed to find the benchmark in the current benchmarks.".into()))?
-                        .asm_information
-                        .clone()
-                        .ok_or_else(|| wrap!("Failed to get the asm information for data section used from the current benchmarks.".into()))?["data_section"]["used"]
-                ));
-                    html.push_str("</tbody></table>");
-                    if !data_only {
-                        html.push_str("<h3>Flamegraphs</h3>");
-                        html.push_str(&generate_flamegraphs(
-                            current_benchmarks
-                                .benchmarks
-                                .iter()
-                                .find(|b| file_name.contains(&b.name))
-                                .ok_or_else(|| {
-                                    wrap!("Failed to find the benchmark in the current benchmarks."
-                                        .into())
-                                })?
-                                .name
-                                .as_str(),
-                            &current_execution.flamegraphs_folder,
-                        )?);
-
-                        html.push_str("<h3>Plots</h3>");
-
-                        html.push_str(
-                            &generate_plots(&current_execution.plots_folder, file_name)
-                                .map_err(|e| wrap!(e))?,
-                        );
-                    }
+    Ok(pages)
+}
+
+/// Get the path of the page of an execution, relative to the site root
+#[must_use]
+pub fn execution_page(identifier: &str) -> PathBuf {
+    PathBuf::from("executions")
+        .join(identifier)
+        .join("index.html")
+}
+
+/// Get the path of the page of a benchmark in an execution, relative to the site root
+#[must_use]
+pub fn benchmark_page(identifier: &str, benchmark_name: &str) -> PathBuf {
+    execution_page(identifier).with_file_name(format!("{}.html", safe_name(benchmark_name)))
+}
+
+/// The name of a benchmark made safe for file names and URLs, used by its page and trend plots
+#[must_use]
+pub fn safe_name(benchmark_name: &str) -> String {
+    let mut name: String = benchmark_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+
+    // Names that had to be changed get a hash of the original name, so distinct names like `a.b`
+    // and `a_b` get distinct files, and the execution page name stays free for the execution
+    if name != benchmark_name || name.is_empty() || name == "index" {
+        let _ = write!(
+            name,
+            "-{:08x}",
+            palette::fnv1a(benchmark_name) & 0xffff_ffff
+        );
+    }
+
+    name
+}
+
+/// Generate the page of an execution and the pages of its benchmarks
+fn generate_execution(
+    current_execution: &Execution,
+    folder_name: &str,
+    data_only: bool,
+    colors: &PhaseColors,
+) -> Result<Vec<Page>> {
+    let mut pages = vec![];
+    let mut html = String::new();
+
+    let previous_benchmarks: Benchmarks = serde_json::from_str(
+        &std::fs::read_to_string(&current_execution.previous_benchmarks)
+            .map_err(|e| wrap!(e.into()))?,
+    )
+    .map_err(|e| wrap!(e.into()))?;
+
+    let current_benchmarks: Benchmarks = serde_json::from_str(
+        &std::fs::read_to_string(&current_execution.current_benchmarks)
+            .map_err(|e| wrap!(e.into()))?,
+    )
+    .map_err(|e| wrap!(e.into()))?;
+
+    let _ = write!(
+        html,
+        "<p><a href=\"{PAGE_ROOT}index.html\">Overview</a> / {}</p>",
+        escape(folder_name)
+    );
+    let _ = write!(html, "<h4>{}</h4>", escape(folder_name));
+    let _ = write!(
+        html,
+        "<h5>PR Link : <a href=\"{}\">{}</a></h5>",
+        escape_url(&current_execution.github_information.link),
+        escape(&current_execution.github_information.link)
+    );
+    let _ = write!(
+        html,
+        "<h5>PR Title : {}</h5>",
+        escape(&current_execution.github_information.title)
+    );
+    let _ = write!(
+        html,
+        "<h5>PR Hash : {}</h5>",
+        escape(&current_execution.github_information.hash)
+    );
+    html.push_str(
+        &generate_previous_current_information(
+            &previous_benchmarks,
+            &current_benchmarks,
+            &current_execution.previous_benchmarks,
+            &current_execution.current_benchmarks,
+            current_execution.pinned_baseline,
+        )
+        .map_err(|e| wrap!(e))?,
+    );
+    // The diagnostics are listed here once the benchmarks have been matched
+    let mut diagnostics = current_execution.diagnostics.clone();
+    let diagnostics_position = html.len();
+
+    // Add an overview of the benchmarks, linking to the page of each benchmark
+    html.push_str("<h5>Benchmarks</h5>");
+    html.push_str("<table class=\"striped\">");
+    html.push_str("<thead><tr><th>Benchmark</th><th>Time</th><th>Memory Usage</th><th>Bytecode Size</th><th>Data Section Size</th></tr></thead>");
+    html.push_str("<tbody>");
+
+    let stats_collection = current_execution.stats_collection().map_err(|e| wrap!(e))?;
 
-                    html.push_str("</div>");
-                    html.push_str("</li>");
+    for (file_name, stats) in &stats_collection.0 {
+        // Stats that no longer match the runs, e.g. after the runs were edited, are left out
+        let mut skip = |run: &str| {
+            let error = Error::from(format!(
+                "The stats of `{file_name}` match no benchmark of the {run} run."
+            ));
+            warn("Skipping a benchmark of the execution", &error);
+            diagnostics.push(Diagnostic::new(run, &[], &error));
+        };
+        let Some(current_benchmark) = stats::find_by_key(&current_benchmarks, file_name) else {
+            skip("current");
+            continue;
+        };
+        let Some(previous_benchmark) = stats::find_match(&previous_benchmarks, current_benchmark)
+        else {
+            skip("previous");
+            continue;
+        };
+        let benchmark_name = current_benchmark.name.as_str();
+
+        let page = benchmark_page(folder_name, benchmark_name);
+        let page_file_name = page
+            .file_name()
+            .ok_or_else(|| wrap!("Failed to get the benchmark page file name.".into()))?
+            .to_str()
+            .ok_or_else(|| wrap!("Failed to get the benchmark page file name as str.".into()))?;
+
+        let _ = write!(
+            html,
+            "<tr><td><i class=\"material-icons tiny\">insert_chart</i> <a href=\"{}\">{}</a>{}</td>",
+            escape_url(page_file_name),
+            escape(file_name),
+            generate_warning_badge(&stats.diagnostics)
+        );
+        for metric in ["time", "memory_usage", "bytecode_size", "data_section_size"] {
+            match percentage_change(stats, metric) {
+                Some(percentage) => {
+                    let _ = write!(html, "<td>{percentage:.2}%</td>");
                 }
+                None => html.push_str("<td>n/a</td>"),
+            }
+        }
+        html.push_str("</tr>");
+
+        let mut benchmark_html = format!(
+            "<p><a href=\"{PAGE_ROOT}index.html\">Overview</a> / <a href=\"index.html\">{}</a> / {}</p>",
+            escape(folder_name),
+            escape(benchmark_name)
+        );
+        let _ = write!(benchmark_html, "<h4>{}</h4>", escape(file_name));
+
+        // A benchmark with malformed data gets a page with its diagnostics instead of failing the
+        // whole site
+        benchmark_html.push_str(&generate_benchmark(
+            current_execution,
+            stats,
+            previous_benchmark,
+            current_benchmark,
+            data_only,
+            colors,
+        ));
+
+        pages.push(Page {
+            path: page,
+            title: format!("{benchmark_name} - {folder_name}"),
+            content: benchmark_html,
+        });
+    }
+
+    html.push_str("</tbody></table>");
+
+    // Benchmarks that only ran in one of the runs are listed without a comparison
+    let (added, removed) = stats::added_and_removed(&previous_benchmarks, &current_benchmarks);
+    html.push_str(&generate_unmatched_benchmarks(
+        "New Benchmarks",
+        "current",
+        &added,
+    ));
+    html.push_str(&generate_unmatched_benchmarks(
+        "Removed Benchmarks",
+        "previous",
+        &removed,
+    ));
+
+    html.insert_str(
+        diagnostics_position,
+        &generate_diagnostics(
+            &diagnostics,
+            "The data of this execution is incomplete, the affected sections are left out.",
+        ),
+    );
+
+    pages.insert(
+        0,
+        Page {
+            path: execution_page(folder_name),
+            title: folder_name.to_string(),
+            content: html,
+        },
+    );
+
+    Ok(pages)
+}
+
+/// Generate the HTML content of the page of a benchmark in an execution
+fn generate_benchmark(
+    current_execution: &Execution,
+    stats: &Stats,
+    previous_benchmark: &Benchmark,
+    current_benchmark: &Benchmark,
+    data_only: bool,
+    colors: &PhaseColors,
+) -> String {
+    // Sections that fail to generate show n/a, with their problem next to the data diagnostics
+    let mut diagnostics = stats.diagnostics.clone();
+    let mut html = String::new();
+
+    html.push_str("<h5>Metrics</h5>");
+    html.push_str("<table class=\"striped\">");
+    html.push_str(
+        "<thead><tr><th>Metric</th><th>Regression(Red)/Improvement(Green)</th></tr></thead>",
+    );
+    html.push_str("<tbody>");
+
+    for (metric, title) in &METRICS {
+        let Some(value) = percentage_change(stats, metric) else {
+            let _ = write!(html, "<tr><td>{title}</td><td>n/a</td></tr>");
+            continue;
+        };
+
+        // Time changes that hyperfine can't tell apart from noise are not painted
+        let significance = stats
+            .hyperfine
+            .as_ref()
+            .filter(|_| *metric == "time")
+            .map(|significance| (significance.significant, significance.p_value));
+
+        let class = if significance.is_some_and(|(significant, _)| !significant) {
+            "unchanged"
+        } else if value > 0.0 {
+            "regression"
+        } else if value < 0.0 {
+            "improvement"
+        } else {
+            "unchanged"
+        };
+        let note = match significance {
+            Some((false, p_value)) => {
+                format!(" (not significant, p = {p_value:.3})")
             }
+            Some((true, p_value)) => format!(" (p = {p_value:.3})"),
+            None => String::new(),
+        };
+        let _ = write!(
+            html,
+            "<tr><td>{title}</td><td class=\"{class}\">{value:.2}%{note}</td></tr>"
+        );
+    }
+    html.push_str("</tbody></table>");
 
-            html.push_str("</ul>"); // Close stats collection collapsible
+    html.push_str("<table class=\"striped\">");
+    html.push_str("<thead><tr><th>Metric</th><th>Previous Benchmark</th><th>Current Benchmark</th></tr></thead>");
+    html.push_str("<tbody>");
+
+    // Runs without asm information, or without one of its values, show n/a in place of the sizes
+    let size = |value: &serde_json::Value| {
+        value
+            .as_u64()
+            .map_or("n/a".to_string(), |size| size.to_string())
+    };
+    let bytecode_size = |benchmark: &Benchmark| {
+        benchmark
+            .asm_information
+            .as_ref()
+            .and_then(|asm_information| asm_information["bytecode_size"].as_u64())
+            .map_or("n/a".to_string(), |bytecode_size| {
+                format!("{bytecode_size} bytes")
+            })
+    };
+    let data_section = |benchmark: &Benchmark| {
+        benchmark
+            .asm_information
+            .as_ref()
+            .map_or("n/a".to_string(), |asm_information| {
+                format!(
+                    "Size : {} - Used : {}",
+                    size(&asm_information["data_section"]["size"]),
+                    size(&asm_information["data_section"]["used"])
+                )
+            })
+    };
+
+    let _ = write!(
+        html,
+        "<tr><td>Bytecode Size</td><td>{}</td><td>{}</td></tr>",
+        bytecode_size(previous_benchmark),
+        bytecode_size(current_benchmark)
+    );
+
+    let _ = write!(
+        html,
+        "<tr><td>Data Section</td><td>{}</td><td>{}</td></tr>",
+        data_section(previous_benchmark),
+        data_section(current_benchmark)
+    );
+    html.push_str("</tbody></table>");
+
+    if let Some(significance) = &stats.hyperfine {
+        html.push_str(&generate_hyperfine(significance));
+    }
 
-            html.push_str("</div>"); // Close container-fluid
-            html.push_str("</div>"); // Close collapsible-body
-            html.push_str("</li>");
+    html.push_str(&generate_phases(
+        previous_benchmark,
+        current_benchmark,
+        colors,
+    ));
+
+    if !data_only {
+        html.push_str("<h3>Flamegraphs</h3>");
+        html.push_str(&generate_flamegraphs(
+            &previous_benchmark.name,
+            &current_benchmark.name,
+            current_execution,
+            PAGE_ROOT,
+            &mut diagnostics,
+        ));
+
+        html.push_str(&generate_hot_functions(
+            current_execution,
+            &current_benchmark.name,
+            PAGE_ROOT,
+            &mut diagnostics,
+        ));
+
+        if current_execution.interactive_charts {
+            html.push_str("<h3>Charts</h3>");
+
+            html.push_str(&generate_charts(
+                &previous_benchmark.name,
+                &current_benchmark.name,
+                current_execution,
+                PAGE_ROOT,
+                &mut diagnostics,
+            ));
+        } else {
+            html.push_str("<h3>Plots</h3>");
+
+            html.push_str(&generate_plots(
+                &previous_benchmark.name,
+                &current_benchmark.name,
+                current_execution,
+                PAGE_ROOT,
+            ));
         }
     }
 
-    html.push_str("</ul>"); // Close main collapsible
+    generate_diagnostics(
+        &diagnostics,
+        "The data of this benchmark is incomplete, the affected values show n/a.",
+    ) + &html
+}
 
-    html.push_str("</div>");
-    html.push_str("<script src=\"https://cdnjs.cloudflare.com/ajax/libs/materialize/1.0.0/js/materialize.min.js\"></script>");
-    html.push_str("<script>");
-    html.push_str("document.addEventListener('DOMContentLoaded', function() {");
-    html.push_str("var elems = document.querySelectorAll('.collapsible');");
-    html.push_str("var instances = M.Collapsible.init(elems);");
-    html.push_str("});");
-    html.push_str("</script>");
-    html.push_str("</body></html>");
+/// Report a section of a benchmark page that failed to generate, and show n/a in its place
+fn section_failed(
+    section: &str,
+    run: &str,
+    benchmark_name: &str,
+    error: &Error,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> String {
+    warn(
+        &format!("Skipping the {section} of `{benchmark_name}`"),
+        error,
+    );
+    diagnostics.push(Diagnostic::new(run, &[], error));
+    "<p>n/a</p>".to_string()
+}
 
-    Ok(html)
+/// Generate a table of the benchmarks that only ran in one of the runs, with their values in that
+/// run, empty when there are none
+fn generate_unmatched_benchmarks(title: &str, run: &str, benchmarks: &[&Benchmark]) -> String {
+    if benchmarks.is_empty() {
+        return String::new();
+    }
+
+    let mut html = String::new();
+
+    let _ = write!(html, "<h5>{title}</h5>");
+    let _ = write!(
+        html,
+        "<p>These benchmarks only ran in the {run} run, so they are not compared.</p>"
+    );
+    html.push_str("<table class=\"striped\">");
+    html.push_str("<thead><tr><th>Benchmark</th><th>Path</th><th>Time</th><th>Memory Usage</th><th>Bytecode Size</th></tr></thead>");
+    html.push_str("<tbody>");
+
+    for benchmark in benchmarks {
+        let summary = stats::Summary::new(benchmark);
+        let bytecode_size = summary
+            .bytecode_size
+            .map_or("n/a".to_string(), |bytecode_size| {
+                format!("{bytecode_size:.0} bytes")
+            });
+
+        let time = summary
+            .time
+            .map_or("n/a".to_string(), |time| format!("{time:.2} ms"));
+        let memory_usage = summary
+            .memory_usage
+            .map_or("n/a".to_string(), |memory_usage| {
+                format!("{memory_usage:.0} bytes")
+            });
+
+        let _ = write!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{time}</td><td>{memory_usage}</td><td>{bytecode_size}</td></tr>",
+            escape(&benchmark.name),
+            escape(&benchmark.path.display()),
+        );
+    }
+
+    html.push_str("</tbody></table>");
+
+    html
 }
 
-fn generate_header() -> String {
-    let mut header = String::new();
-    header.push_str("<html><head>");
-    header.push_str("<link href=\"https://cdnjs.cloudflare.com/ajax/libs/materialize/1.0.0/css/materialize.min.css\" rel=\"stylesheet\">");
-    header.push_str("<link href=\"https://fonts.googleapis.com/icon?family=Material+Icons\" rel=\"stylesheet\">");
-    header.push_str("<title>System Specifications</title>");
-    header.push_str("<style>");
-    header.push_str("body { background-color: #121212; color: #ffffff; }");
-    header.push_str(".container-fluid { background-color: #1e1e1e; padding: 20px; border-radius: 8px; width: 100%; }");
-    header.push_str("table.striped > tbody > tr:nth-child(odd) { background-color: #2c2c2c; }");
-    header.push_str("table.striped > tbody > tr:nth-child(even) { background-color: #1e1e1e; }");
-    header.push_str(".collapsible-header { background-color: #333333; color: #ffffff; }");
-    header.push_str(".collapsible-body { background-color: #1e1e1e; color: #ffffff; }");
-    header.push_str(".collapsible-header.active { background-color: #333333 !important; }");
-    header.push_str("</style>");
-    header.push_str("</head><body>");
-    header
+/// Generate a badge counting the diagnostics of a benchmark, empty when its data is complete
+fn generate_warning_badge(diagnostics: &[Diagnostic]) -> String {
+    if diagnostics.is_empty() {
+        return String::new();
+    }
+
+    let titles = diagnostics
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!(
+        " <span class=\"warning-badge\" title=\"{}\"><i class=\"material-icons tiny\">warning</i> {}</span>",
+        escape(&titles),
+        diagnostics.len()
+    )
+}
+
+/// Generate the list of the problems with the data of a benchmark or an execution, empty when its
+/// data is complete
+fn generate_diagnostics(diagnostics: &[Diagnostic], introduction: &str) -> String {
+    if diagnostics.is_empty() {
+        return String::new();
+    }
+
+    let mut html = String::new();
+
+    let _ = write!(
+        html,
+        "<h5>Diagnostics{}</h5>",
+        generate_warning_badge(diagnostics)
+    );
+    let _ = write!(html, "<p>{introduction}</p>");
+    html.push_str("<ul class=\"diagnostics\">");
+    for diagnostic in diagnostics {
+        let _ = write!(
+            html,
+            "<li><b>{}</b>: {}</li>",
+            escape(&diagnostic.run),
+            escape(&diagnostic.message)
+        );
+    }
+    html.push_str("</ul>");
+
+    html
 }
 
 fn generate_system_specs(benchmarks: &Benchmarks) -> String {
@@ -378,7 +696,7 @@ fn generate_system_specs(benchmarks: &Benchmarks) -> String {
     ];
 
     for (spec, value) in &specs {
-        html.push_str(&format!("<tr><td>{spec}</td><td>{value}</td></tr>"));
+        let _ = write!(html, "<tr><td>{spec}</td><td>{}</td></tr>", escape(value));
     }
 
     html.push_str("</tbody></table>");
@@ -394,10 +712,14 @@ fn generate_system_specs(benchmarks: &Benchmarks) -> String {
     html.push_str("<tbody>");
 
     for cpu in &benchmarks.system_specs.cpus {
-        html.push_str(&format!(
+        let _ = write!(
+            html,
             "<tr><td>{}</td><td>{}</td><td>{}</td><td>{} MHz</td></tr>",
-            cpu.name, cpu.vendor_id, cpu.brand, cpu.frequency
-        ));
+            escape(&cpu.name),
+            escape(&cpu.vendor_id),
+            escape(&cpu.brand),
+            cpu.frequency
+        );
     }
 
     html.push_str("</tbody></table></div>");
@@ -411,11 +733,93 @@ fn generate_system_specs(benchmarks: &Benchmarks) -> String {
     html
 }
 
+fn generate_trend_history(trends: &[Trend], data_only: bool, plot_format: PlotFormat) -> String {
+    let mut html = String::new();
+
+    // A site without trends, or whose trends failed to generate, has no trend history
+    if trends.is_empty() {
+        return html;
+    }
+
+    html.push_str("<h5>Trend History</h5>");
+    html.push_str("<ul class=\"collapsible\">");
+
+    for trend in trends {
+        html.push_str("<li>");
+        let _ = write!(
+            html,
+            "<div class=\"collapsible-header\"><i class=\"material-icons\">timeline</i>{}</div>",
+            escape(&trend.benchmark)
+        );
+        html.push_str("<div class=\"collapsible-body\"><table class=\"striped\">");
+        html.push_str("<thead><tr><th>Execution</th><th>Date and Time</th>");
+        for (_, title) in &TREND_METRICS {
+            let _ = write!(html, "<th>{title}</th>");
+        }
+        html.push_str("</tr></thead>");
+        html.push_str("<tbody>");
+
+        for point in &trend.points {
+            let _ = write!(
+                html,
+                "<tr><td>{}</td><td>{}</td>",
+                escape(&point.execution),
+                escape(&point.datetime)
+            );
+            for (metric, _) in &TREND_METRICS {
+                match point.value(metric) {
+                    Some(value) => {
+                        let _ = write!(html, "<td>{value:.0}</td>");
+                    }
+                    None => html.push_str("<td>n/a</td>"),
+                }
+            }
+            html.push_str("</tr>");
+        }
+
+        html.push_str("</tbody></table>");
+
+        if !data_only {
+            for (metric, title) in &TREND_METRICS {
+                // Metrics without values in any execution have no plot
+                if trend
+                    .points
+                    .iter()
+                    .all(|point| point.value(metric).is_none())
+                {
+                    continue;
+                }
+
+                html.push_str("<div class=\"row\">");
+                html.push_str("<div class=\"col s12\">");
+                let _ = write!(
+                    html,
+                    "<img src=\"{}\" alt=\"{title}\" class=\"responsive-img\">",
+                    escape_url(&format!(
+                        "trend/{}",
+                        trend::plot_file_name(&trend.benchmark, metric, plot_format)
+                    ))
+                );
+                html.push_str("</div>");
+                html.push_str("</div>");
+            }
+        }
+
+        html.push_str("</div>");
+        html.push_str("</li>");
+    }
+
+    html.push_str("</ul>");
+
+    html
+}
+
 fn generate_previous_current_information(
     previous_benchmarks: &Benchmarks,
     current_benchmarks: &Benchmarks,
     previous_file_name: &Path,
     current_file_name: &Path,
+    pinned_baseline: bool,
 ) -> Result<String> {
     let mut html = String::new();
 
@@ -424,7 +828,11 @@ fn generate_previous_current_information(
 
     let benchmarks_info = [
         (
-            "Previous Benchmarks Collection",
+            if pinned_baseline {
+                "Previous Benchmarks Collection (Pinned Baseline)"
+            } else {
+                "Previous Benchmarks Collection"
+            },
             previous_file_name,
             previous_benchmarks,
         ),
@@ -438,27 +846,32 @@ fn generate_previous_current_information(
     for (title, file_name, benchmarks) in &benchmarks_info {
         let file_path = Path::new(file_name);
         html.push_str("<div class=\"col s12 m6\">");
-        html.push_str(&format!("<h5>{title}</h5>"));
-        html.push_str(&format!(
+        let _ = write!(html, "<h5>{title}</h5>");
+        let _ = write!(
+            html,
             "<p><strong>File Name:</strong> {}</p>",
             file_path
                 .file_name()
                 .ok_or_else(|| wrap!("Failed to get the file name from the file path.".into()))?
                 .to_str()
-                .ok_or_else(|| wrap!("Failed to get the file name as str.".into()))?
-        ));
-        html.push_str(&format!(
+                .ok_or_else(|| wrap!("Failed to get the file name as str.".into()))
+                .map(escape)?
+        );
+        let _ = write!(
+            html,
             "<p><strong>Forc Version:</strong> {}</p>",
-            benchmarks.forc_version
-        ));
-        html.push_str(&format!(
+            escape(&benchmarks.forc_version)
+        );
+        let _ = write!(
+            html,
             "<p><strong>Compiler File Hash:</strong> {}</p>",
-            benchmarks.compiler_hash
-        ));
-        html.push_str(&format!(
+            escape(&benchmarks.compiler_hash)
+        );
+        let _ = write!(
+            html,
             "<p><strong>Date and Time of Benchmarks:</strong> {}</p>",
-            benchmarks.benchmarks_datetime
-        ));
+            escape(&benchmarks.benchmarks_datetime)
+        );
         html.push_str("</div>");
     }
 
@@ -471,16 +884,18 @@ fn generate_previous_current_information(
     html.push_str("<table class=\"striped\">");
     html.push_str("<thead><tr><th>Metric</th><th>Previous Benchmark</th><th>Current Benchmark</th></tr></thead>");
     html.push_str("<tbody>");
-    html.push_str(&format!(
+    let _ = write!(
+        html,
         "<tr><td>Total Number of Files Processed</td><td>{}</td><td>{}</td></tr>",
         previous_benchmarks.benchmarks.len(),
         current_benchmarks.benchmarks.len()
-    ));
-    html.push_str(&format!(
+    );
+    let _ = write!(
+        html,
         "<tr><td>Total Time of Execution</td><td>{} ms</td><td>{} ms</td></tr>",
         previous_benchmarks.total_time.as_millis(),
         current_benchmarks.total_time.as_millis()
-    ));
+    );
     html.push_str("</tbody></table>");
     html.push_str("</div>");
     html.push_str("</div>");
@@ -488,43 +903,307 @@ fn generate_previous_current_information(
     Ok(html)
 }
 
-fn generate_flamegraphs(benchmark_name: &str, flamegraph_file_path: &Path) -> Result<String> {
+/// Generate the duration of every phase in both runs, with a timeline of the phases of each run
+fn generate_phases(previous: &Benchmark, current: &Benchmark, colors: &PhaseColors) -> String {
+    let phases = stats::compare_phases(previous, current);
+
+    if phases.is_empty() {
+        return String::new();
+    }
+
+    let colors = colors.for_phases(phases.iter().map(|phase| phase.name.as_str()));
+
     let mut html = String::new();
-    let flamegraphs_paths = get_all_files(flamegraph_file_path).map_err(|e| wrap!(e))?;
 
-    let file1 = remove_first_component(&flamegraphs_paths[0]);
-    let file1 = file1
-        .to_str()
-        .ok_or_else(|| wrap!("Failed to get the file path as str.".into()))?;
-    let file2 = remove_first_component(&flamegraphs_paths[1]);
-    let file2 = file2
-        .to_str()
-        .ok_or_else(|| wrap!("Failed to get the file path as str.".into()))?;
+    html.push_str("<h5>Phases</h5>");
+    html.push_str("<table class=\"striped\">");
+    html.push_str("<thead><tr><th>Phase</th><th>Previous (ms)</th><th>Current (ms)</th><th>Difference (ms)</th><th>Change</th></tr></thead>");
+    html.push_str("<tbody>");
 
-    let (previous_flamegraph, current_flamegraph) = sort_files_by_timestamp(file1, file2)?;
+    let duration =
+        |value: Option<f64>| value.map_or("n/a".to_string(), |value| format!("{value:.2}"));
+
+    for phase in &phases {
+        let (class, difference, percentage) = match phase.change() {
+            Some((difference, percentage)) => (
+                if percentage > 0.0 {
+                    "regression"
+                } else if percentage < 0.0 {
+                    "improvement"
+                } else {
+                    "unchanged"
+                },
+                format!("{difference:+.2}"),
+                format!("{percentage:+.2}%"),
+            ),
+            None => ("unchanged", "n/a".to_string(), "n/a".to_string()),
+        };
+
+        let _ = write!(
+            html,
+            "<tr><td><span class=\"phase-swatch\" style=\"background-color: {}\"></span>{}</td><td>{}</td><td>{}</td><td class=\"{class}\">{difference}</td><td class=\"{class}\">{percentage}</td></tr>",
+            colors.hex(&phase.name),
+            escape(&phase.name),
+            duration(phase.previous),
+            duration(phase.current),
+        );
+    }
+    html.push_str("</tbody></table>");
+
+    html.push_str(&generate_phase_resources(&phases));
+
+    // Both timelines share the same scale so the runs can be compared at a glance
+    let runs = [
+        ("Previous", stats::phase_windows(previous)),
+        ("Current", stats::phase_windows(current)),
+    ];
+    let total = runs
+        .iter()
+        .flat_map(|(_, windows)| windows.iter().map(|(_, _, end)| *end))
+        .fold(0.0, f64::max);
+
+    if total > 0.0 {
+        html.push_str("<div class=\"phase-timeline\">");
+        for (run, windows) in &runs {
+            let _ = write!(
+                html,
+                "<div class=\"phase-timeline-row\"><span class=\"phase-timeline-label\">{run}</span><div class=\"phase-timeline-track\">"
+            );
+            for (name, start, end) in windows {
+                let _ = write!(
+                    html,
+                    "<div class=\"phase-timeline-bar\" style=\"left: {:.3}%; width: {:.3}%; background-color: {}\" title=\"{}: {:.2} ms\"></div>",
+                    start / total * 100.0,
+                    (end - start) / total * 100.0,
+                    colors.hex(name),
+                    escape(name),
+                    end - start,
+                );
+            }
+            html.push_str("</div></div>");
+        }
+        let _ = write!(
+            html,
+            "<div class=\"phase-timeline-row\"><span class=\"phase-timeline-label\"></span><div class=\"phase-timeline-axis\"><span>0 ms</span><span>{total:.2} ms</span></div></div>"
+        );
+        html.push_str("</div>");
+    }
+
+    html
+}
+
+/// Generate the resource usage of every phase in both runs, as `previous / current (change)`
+fn generate_phase_resources(phases: &[stats::PhaseChange]) -> String {
+    if phases
+        .iter()
+        .all(|phase| phase.previous_resources.is_none() && phase.current_resources.is_none())
+    {
+        return String::new();
+    }
+
+    let mut html = String::new();
+
+    html.push_str("<h5>Phase Resources</h5>");
+    html.push_str("<table class=\"striped\">");
+    html.push_str("<thead><tr><th>Phase</th>");
+    for (_, title) in &stats::PHASE_RESOURCE_METRICS {
+        let _ = write!(html, "<th>{title}</th>");
+    }
+    html.push_str("</tr></thead>");
+    html.push_str("<tbody>");
+
+    for phase in phases {
+        let _ = write!(html, "<tr><td>{}</td>", escape(&phase.name));
+
+        for (metric, _) in &stats::PHASE_RESOURCE_METRICS {
+            let previous = phase
+                .previous_resources
+                .as_ref()
+                .and_then(|resources| resources.value(metric));
+            let current = phase
+                .current_resources
+                .as_ref()
+                .and_then(|resources| resources.value(metric));
+
+            // Bytes are whole numbers
+            let precision = if metric.ends_with("cpu_usage") { 2 } else { 0 };
+            let value = |value: Option<f64>| {
+                value.map_or("n/a".to_string(), |value| format!("{value:.precision$}"))
+            };
+
+            match previous.zip(current) {
+                Some((previous, current)) => {
+                    let (_, percentage) = stats::change(previous, current);
+                    let class = if percentage > 0.0 {
+                        "regression"
+                    } else if percentage < 0.0 {
+                        "improvement"
+                    } else {
+                        "unchanged"
+                    };
+                    let _ = write!(
+                        html,
+                        "<td>{previous:.precision$} / <span class=\"{class}\">{current:.precision$} ({percentage:+.2}%)</span></td>"
+                    );
+                }
+                None => {
+                    let _ = write!(html, "<td>{} / {}</td>", value(previous), value(current));
+                }
+            }
+        }
+        html.push_str("</tr>");
+    }
+    html.push_str("</tbody></table>");
+
+    html
+}
+
+fn generate_hyperfine(significance: &Significance) -> String {
+    let mut html = String::new();
+
+    html.push_str("<h5>Hyperfine</h5>");
+    html.push_str("<table class=\"striped\">");
+    html.push_str("<thead><tr><th>Statistic</th><th>Previous Benchmark</th><th>Current Benchmark</th></tr></thead>");
+    html.push_str("<tbody>");
+
+    let (previous, current) = (&significance.previous, &significance.current);
+    let _ = write!(
+        html,
+        "<tr><td>Runs</td><td>{}</td><td>{}</td></tr>",
+        previous.count, current.count
+    );
+
+    let rows = [
+        ("Mean", previous.mean, current.mean),
+        ("Standard Deviation", previous.stddev, current.stddev),
+        ("Min", previous.min, current.min),
+        ("Max", previous.max, current.max),
+    ];
+
+    for (statistic, previous, current) in &rows {
+        let _ = write!(
+            html,
+            "<tr><td>{statistic}</td><td>{previous:.4} s</td><td>{current:.4} s</td></tr>"
+        );
+    }
+
+    let _ = write!(
+        html,
+        "<tr><td>{:.0}% Confidence Interval</td><td>{:.4} s - {:.4} s</td><td>{:.4} s - {:.4} s</td></tr>",
+        (1.0 - ALPHA) * 100.0,
+        previous.ci_low,
+        previous.ci_high,
+        current.ci_low,
+        current.ci_high
+    );
+    html.push_str("</tbody></table>");
+
+    let _ = write!(
+        html,
+        "<p>Welch's t-test: t = {:.3}, df = {:.1}, p = {:.4} ({})</p>",
+        significance.t,
+        significance.degrees_of_freedom,
+        significance.p_value,
+        if significance.significant {
+            "significant"
+        } else {
+            "not significant"
+        }
+    );
+
+    html
+}
+
+/// Generate the links to the flamegraphs of a benchmark in the previous and current runs, and the
+/// differential flamegraph between them when both runs have stacks
+fn generate_flamegraphs(
+    previous_name: &str,
+    current_name: &str,
+    current_execution: &Execution,
+    root: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> String {
+    let mut html = String::new();
+
+    // The flamegraph folders are stored in the order of the runs, previous first
+    let folders = current_execution.flamegraphs.as_deref().unwrap_or_default();
 
     // Start flexbox container
     html.push_str("<div style=\"display: flex; justify-content: space-between;\">");
-    html.push_str(&format!(
-        "<div><i class=\"material-icons\">whatshot</i> <a href=\"{previous_flamegraph}\" target=\"_blank\">Previous Flamegraph : {benchmark_name}</a></div>",
-    ));
-    html.push_str(&format!(
-        "<div style=\"margin-left: auto; margin-right: auto;\"><i class=\"material-icons\">whatshot</i> <a href=\"{current_flamegraph}\" target=\"_blank\">Current Flamegraph : {benchmark_name}</a></div>",
-            ));
+    let runs = [("Previous", previous_name), ("Current", current_name)];
+    for ((run, benchmark_name), folder) in runs.iter().zip(folders) {
+        let Some(folder_name) = folder.file_name() else {
+            let error = wrap!("Failed to get filename for flamegraph".into()).into();
+            section_failed(
+                "flamegraph",
+                &run.to_lowercase(),
+                benchmark_name,
+                &error,
+                diagnostics,
+            );
+            let _ = write!(
+                html,
+                "<div><i class=\"material-icons\">whatshot</i> {run} Flamegraph : n/a</div>"
+            );
+            continue;
+        };
+        let file = flamegraph::flamegraph_file(
+            &current_execution.flamegraphs_folder.join(folder_name),
+            benchmark_name,
+        );
+
+        if file.is_file() {
+            let _ = write!(
+                html,
+                "<div><i class=\"material-icons\">whatshot</i> <a href=\"{}\" target=\"_blank\">{run} Flamegraph : {}</a></div>",
+                escape_url(&format!("{root}{}", remove_first_component(&file).display())),
+                escape(benchmark_name)
+            );
+        } else {
+            let _ = write!(
+                html,
+                "<div><i class=\"material-icons\">whatshot</i> {run} Flamegraph : n/a</div>"
+            );
+        }
+    }
     // End flexbox container
     html.push_str("</div>");
 
-    Ok(html)
+    // Differential flamegraph of the runs
+    if let Some(differential) = current_execution
+        .differential_flamegraphs
+        .iter()
+        .flatten()
+        .find(|file| file.file_stem().and_then(|stem| stem.to_str()) == Some(current_name))
+    {
+        let differential = format!("{root}{}", remove_first_component(differential).display());
+        let differential = escape_url(&differential);
+
+        let _ = write!(
+            html,
+            "<p><i class=\"material-icons\">compare_arrows</i> <a href=\"{differential}\" target=\"_blank\">Differential Flamegraph : {}</a></p>",
+            escape(current_name)
+        );
+        let _ = write!(
+            html,
+            "<object class=\"flamegraph\" type=\"image/svg+xml\" data=\"{differential}\"></object>"
+        );
+    }
+
+    html
 }
 
-fn generate_plots(current_folder: &Path, name: &str) -> Result<String> {
+/// Generate the plots of a benchmark in the previous and current runs, the plots of each run are
+/// named after the benchmark in that run
+fn generate_plots(
+    previous_name: &str,
+    current_name: &str,
+    current_execution: &Execution,
+    root: &str,
+) -> String {
     let mut html = String::new();
-    let name = name
-        .rsplit('/')
-        .next()
-        .ok_or_else(|| wrap!("Failed to get the name.".into()))?;
 
-    let current_folder = remove_first_component(current_folder);
+    let extension = current_execution.plot_format.extension();
 
     let plot_sections = [
         ("CPU Usage", "cpu_usage"),
@@ -538,44 +1217,221 @@ fn generate_plots(current_folder: &Path, name: &str) -> Result<String> {
 
     for (title, suffix) in &plot_sections {
         html.push_str("<div class=\"row\">");
+
+        // Both runs on shared axes
+        if current_execution.overlay {
+            html.push_str("<div class=\"col s12\">");
+            let _ = write!(html, "<h5>{title}</h5>");
+            html.push_str(&plot_image(
+                current_execution,
+                &format!("overlay_{current_name}_{suffix}.{extension}"),
+                title,
+                root,
+            ));
+            html.push_str("</div>");
+            html.push_str("</div>"); // Close row
+            continue;
+        }
+
         // Previous benchmark plot
         html.push_str("<div class=\"col s12 m6\">");
-        html.push_str(&format!("<h5>Previous Benchmark {title}</h5>"));
-        html.push_str(&format!(
-            "<img src=\"{}/previous_{name}_{suffix}.png\" alt=\"Previous Benchmark {title}\" class=\"responsive-img\">", current_folder.display()
+        let _ = write!(html, "<h5>Previous Benchmark {title}</h5>");
+        html.push_str(&plot_image(
+            current_execution,
+            &format!("previous_{previous_name}_{suffix}.{extension}"),
+            &format!("Previous Benchmark {title}"),
+            root,
         ));
         html.push_str("</div>");
 
         // Current benchmark plot
         html.push_str("<div class=\"col s12 m6\">");
-        html.push_str(&format!("<h5>Current Benchmark {title}</h5>"));
-        html.push_str(&format!(
-            "<img src=\"{}/current_{name}_{suffix}.png\" alt=\"Current Benchmark {title}\" class=\"responsive-img\">", current_folder.display()
+        let _ = write!(html, "<h5>Current Benchmark {title}</h5>");
+        html.push_str(&plot_image(
+            current_execution,
+            &format!("current_{current_name}_{suffix}.{extension}"),
+            &format!("Current Benchmark {title}"),
+            root,
         ));
         html.push_str("</div>");
 
         html.push_str("</div>"); // Close row
     }
 
-    Ok(html)
+    html
 }
 
-pub fn generate_error_page() -> String {
+/// Link a plot of the current execution, or note that it could not be drawn
+fn plot_image(current_execution: &Execution, file_name: &str, alt: &str, root: &str) -> String {
+    if !current_execution.plots_folder.join(file_name).exists() {
+        return "<p>n/a</p>".to_string();
+    }
+
+    format!(
+        "<img src=\"{}\" alt=\"{alt}\" class=\"responsive-img\">",
+        escape_url(&format!(
+            "{root}{}/{file_name}",
+            remove_first_component(&current_execution.plots_folder).display()
+        ))
+    )
+}
+
+/// Generate the interactive charts of a benchmark, with the series of both runs embedded in the page
+fn generate_charts(
+    previous_name: &str,
+    current_name: &str,
+    current_execution: &Execution,
+    root: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> String {
     let mut html = String::new();
+    let current_folder = &current_execution.plots_folder;
+
+    // The charts need the series of both runs, each named after the benchmark in its run
+    for (source, name) in [("previous", previous_name), ("current", current_name)] {
+        match chart::read_embeddable(&current_folder.join(format!("{source}_{name}.json"))) {
+            Ok(series) => {
+                let _ = write!(
+                    html,
+                    "<script type=\"application/json\" data-chart-series=\"{source}\">{series}</script>"
+                );
+            }
+            Err(e) => return section_failed("charts", source, name, &e, diagnostics),
+        }
+    }
 
-    html.push_str("<html><head>");
-    html.push_str("<link href=\"https://cdnjs.cloudflare.com/ajax/libs/materialize/1.0.0/css/materialize.min.css\" rel=\"stylesheet\">");
-    html.push_str("<link href=\"https://fonts.googleapis.com/icon?family=Material+Icons\" rel=\"stylesheet\">");
-    html.push_str("<title>404 Error Not Found</title>");
-    html.push_str("<style>");
-    html.push_str("body { background-color: #121212; color: #ffffff; display: flex; justify-content: center; align-items: center; height: 100vh; margin: 0; }");
-    html.push_str(".container { text-align: center; }");
-    html.push_str("</style>");
-    html.push_str("</head><body>");
-    html.push_str("<div class=\"container\">");
-    html.push_str("<h1>404 Error Not Found</h1>");
-    html.push_str("</div>");
-    html.push_str("</body></html>");
+    let previous =
+        serde_json::json!({ "source": "previous", "name": "Previous", "color": "#1e88e5" });
+    let current = serde_json::json!({ "source": "current", "name": "Current", "color": "#e53935" });
+    let delta = serde_json::json!({
+        "source": "current",
+        "delta": "previous",
+        "name": "Current - Previous",
+        "color": "#9e9e9e",
+        "dashed": true,
+    });
+
+    for (metric, title, _) in &CHART_METRICS {
+        let max = if *metric == "cpu_usage" {
+            Some(100)
+        } else {
+            None
+        };
+
+        html.push_str("<div class=\"row\">");
+
+        // Both runs on shared axes, with the phases of the current run
+        let charts = if current_execution.overlay {
+            let mut series = vec![previous.clone(), current.clone()];
+            series[1]["phases"] = true.into();
+            if current_execution.overlay_delta {
+                series.push(delta.clone());
+            }
+
+            vec![(
+                "col s12",
+                serde_json::json!({ "title": title, "metric": metric, "max": max, "series": series }),
+            )]
+        } else {
+            [("Previous", &previous), ("Current", &current)]
+                .into_iter()
+                .map(|(label, series)| {
+                    let mut series = series.clone();
+                    series["phases"] = true.into();
+                    (
+                        "col s12 m6",
+                        serde_json::json!({
+                            "title": format!("{label} Benchmark {title}"),
+                            "metric": metric,
+                            "max": max,
+                            "series": [series],
+                        }),
+                    )
+                })
+                .collect()
+        };
+
+        for (class, chart) in charts {
+            let _ = write!(
+                html,
+                "<div class=\"{class}\"><div data-chart=\"{}\"></div></div>",
+                escape(chart)
+            );
+        }
+
+        html.push_str("</div>"); // Close row
+    }
+
+    let _ = write!(
+        html,
+        "<script src=\"{}\"></script>",
+        escape_url(&format!("{root}assets/charts.js"))
+    );
+
+    html
+}
+
+/// Generate a sortable table of the share of the samples of the hot functions of a benchmark in the
+/// previous and current runs
+fn generate_hot_functions(
+    current_execution: &Execution,
+    benchmark_name: &str,
+    root: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> String {
+    // The hot functions are stored with the current run
+    let hot_functions = match current_execution.hot_functions() {
+        Ok(hot_functions) => hot_functions,
+        Err(e) => {
+            return "<h5>Hot Functions</h5>".to_string()
+                + &section_failed("hot functions", "current", benchmark_name, &e, diagnostics);
+        }
+    };
+
+    let Some(functions) = hot_functions
+        .get(benchmark_name)
+        .filter(|functions| !functions.is_empty())
+    else {
+        return String::new();
+    };
+
+    let mut html = String::new();
+
+    html.push_str("<h5>Hot Functions</h5>");
+    html.push_str("<table class=\"striped sortable\">");
+    html.push_str("<thead><tr><th>Function</th><th>Previous Self</th><th>Current Self</th><th>Self Change</th><th>Previous Inclusive</th><th>Current Inclusive</th><th>Inclusive Change</th></tr></thead>");
+    html.push_str("<tbody>");
+
+    for function in functions {
+        let _ = write!(html, "<tr><td><code>{}</code></td>", escape(&function.name));
+
+        for (previous, current) in [
+            (function.previous_self, function.current_self),
+            (function.previous_inclusive, function.current_inclusive),
+        ] {
+            // The change of a share is in percentage points
+            let change = current - previous;
+            let class = if change > 0.0 {
+                "regression"
+            } else if change < 0.0 {
+                "improvement"
+            } else {
+                "unchanged"
+            };
+            let _ = write!(
+                html,
+                "<td data-value=\"{previous}\">{previous:.2}%</td><td data-value=\"{current}\">{current:.2}%</td><td data-value=\"{change}\" class=\"{class}\">{change:+.2} pp</td>"
+            );
+        }
+        html.push_str("</tr>");
+    }
+    html.push_str("</tbody></table>");
+
+    let _ = write!(
+        html,
+        "<script src=\"{}\"></script>",
+        escape_url(&format!("{root}assets/tables.js"))
+    );
 
     html
 }
@@ -586,7 +1442,10 @@ fn remove_first_component(path: &Path) -> PathBuf {
     components.as_path().to_path_buf()
 }
 
-fn parse_timestamp(file_name: &str) -> Result<chrono::NaiveDateTime> {
+/// # Errors
+///
+/// Returns an error if the file name doesn't start with a `%Y-%m-%d_%H:%M:%S` timestamp.
+pub fn parse_timestamp(file_name: &str) -> Result<chrono::NaiveDateTime> {
     let file = if file_name.contains('/') {
         // Get the part before the last / in the file name
         let file_name_parts = file_name.split('/').collect::<Vec<_>>();
@@ -608,17 +1467,6 @@ fn parse_timestamp(file_name: &str) -> Result<chrono::NaiveDateTime> {
     }
 }
 
-fn sort_files_by_timestamp(file1: &str, file2: &str) -> Result<(String, String)> {
-    let timestamp1 = parse_timestamp(file1)?;
-    let timestamp2 = parse_timestamp(file2)?;
-
-    if timestamp1 < timestamp2 {
-        Ok((file1.to_string(), file2.to_string()))
-    } else {
-        Ok((file2.to_string(), file1.to_string()))
-    }
-}
-
 fn find_second_last_underscore(s: &str) -> Option<usize> {
     let underscores: Vec<_> = s.match_indices('_').collect();
     if underscores.len() >= 2 {
@@ -628,18 +1476,226 @@ fn find_second_last_underscore(s: &str) -> Option<usize> {
     }
 }
 
-fn get_all_files(dir: &Path) -> Result<Vec<PathBuf>> {
-    let mut files = Vec::new();
-    if dir.is_dir() {
-        for entry in std::fs::read_dir(dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.is_dir() {
-                files.extend(get_all_files(&path)?);
-            } else {
-                files.push(path);
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site::{fixtures, template::Templates};
+    use crate::types::PRInformation;
+
+    #[test]
+    fn benchmark_pages_are_distinct_and_stable() {
+        let page = |name| benchmark_page("execution", name);
+
+        assert_eq!(
+            execution_page("execution"),
+            PathBuf::from("executions/execution/index.html")
+        );
+        assert_eq!(
+            page("hashing"),
+            PathBuf::from("executions/execution/hashing.html")
+        );
+        assert_eq!(page("a.b"), page("a.b"));
+
+        let names = ["a.b", "a_b", "a-b", "a b", "a/b", "index", "index-", ""];
+        let pages: std::collections::HashSet<_> = names.iter().map(|name| page(name)).collect();
+        assert_eq!(pages.len(), names.len());
+        assert!(!pages.contains(&execution_page("execution")));
+    }
+
+    #[test]
+    fn hostile_pr_information_is_escaped() {
+        let folder = tempfile::tempdir().unwrap();
+        let run = fixtures::benchmarks(vec![fixtures::benchmark("<img src=x onerror=alert(1)>")]);
+        let execution = fixtures::execution(
+            folder.path(),
+            "benchmarks_2024-10-22_10:00:00",
+            &run,
+            &run,
+            PRInformation {
+                hash: "\"><script>alert(1)</script>".to_string(),
+                title: "<script>alert(1)</script>".to_string(),
+                link: "java\tscript:alert(1)".to_string(),
+            },
+        );
+
+        let pages = generate_execution(
+            &execution,
+            "benchmarks_2024-10-22_10:00:00",
+            true,
+            &PhaseColors::load(None).unwrap(),
+        )
+        .unwrap();
+        let templates = Templates::new(None, false).unwrap();
+        for page in &pages {
+            let page = templates
+                .render_page(&page.title, &page.content, &page.path)
+                .unwrap();
+            assert!(!page.contains("<script>alert(1)</script>"));
+            assert!(!page.contains("<img src=x"));
+        }
+
+        let page = &pages[0].content;
+        assert!(page.contains("PR Title : &lt;script&gt;alert(1)&lt;/script&gt;"));
+        assert!(page.contains("PR Hash : &quot;&gt;&lt;script&gt;"));
+        assert!(page.contains("<a href=\"#\">java\tscript:alert(1)</a>"));
+        assert!(page.contains("&lt;img src=x onerror=alert(1)&gt;"));
+    }
+
+    #[test]
+    fn svg_plots_are_referenced_with_their_extension() {
+        let folder = tempfile::tempdir().unwrap();
+        let run = fixtures::benchmarks(vec![fixtures::benchmark("hashing")]);
+        let mut execution = fixtures::execution(
+            folder.path(),
+            "benchmarks_2024-10-22_10:00:00",
+            &run,
+            &run,
+            PRInformation::default(),
+        );
+        execution.plot_format = PlotFormat::Svg;
+        std::fs::create_dir_all(&execution.plots_folder).unwrap();
+        std::fs::write(
+            execution.plots_folder.join("current_hashing_cpu_usage.svg"),
+            "<svg></svg>",
+        )
+        .unwrap();
+
+        let html = generate_plots("hashing", "hashing", &execution, PAGE_ROOT);
+
+        assert!(html.contains(&format!(
+            "<img src=\"../../{}/current_hashing_cpu_usage.svg\"",
+            remove_first_component(&execution.plots_folder).display()
+        )));
+        // The plots that were not drawn are shown as n/a
+        assert!(html.contains("<p>n/a</p>"));
+        assert!(!html.contains(".png"));
+    }
+
+    #[test]
+    fn failed_sections_show_n_a_with_a_diagnostic() {
+        let folder = tempfile::tempdir().unwrap();
+        let run = fixtures::benchmarks(vec![fixtures::benchmark("hashing")]);
+        let mut execution = fixtures::execution(
+            folder.path(),
+            "benchmarks_2024-10-22_10:00:00",
+            &run,
+            &run,
+            PRInformation::default(),
+        );
+        execution.interactive_charts = true;
+        execution.hot_functions = Some(folder.path().join("missing.json"));
+
+        let pages = generate_execution(
+            &execution,
+            "benchmarks_2024-10-22_10:00:00",
+            false,
+            &PhaseColors::load(None).unwrap(),
+        )
+        .unwrap();
+
+        let page = &pages[1].content;
+        assert!(page.contains("<h5>Hot Functions</h5><p>n/a</p>"));
+        assert!(page.contains("<h3>Charts</h3><p>n/a</p>"));
+        assert!(page.contains("<h5>Metrics</h5>"));
+        assert_eq!(page.matches("<li><b>").count(), 2);
+        assert!(page.contains("<li><b>previous</b>: I/O error"));
+        assert!(page.contains("<li><b>current</b>: I/O error"));
+    }
+
+    #[test]
+    fn renamed_benchmarks_show_the_plots_and_charts_of_both_runs() {
+        let folder = tempfile::tempdir().unwrap();
+        let mut renamed = fixtures::benchmark("hashing-old");
+        renamed.path = PathBuf::from("/projects/hashing");
+        let mut execution = fixtures::execution(
+            folder.path(),
+            "benchmarks_2024-10-22_10:00:00",
+            &fixtures::benchmarks(vec![renamed]),
+            &fixtures::benchmarks(vec![fixtures::benchmark("hashing")]),
+            PRInformation::default(),
+        );
+        std::fs::create_dir_all(&execution.plots_folder).unwrap();
+        for file in [
+            "previous_hashing-old_cpu_usage.png",
+            "current_hashing_cpu_usage.png",
+            "previous_hashing-old.json",
+            "current_hashing.json",
+        ] {
+            std::fs::write(execution.plots_folder.join(file), "{}").unwrap();
         }
+        let colors = PhaseColors::load(None).unwrap();
+
+        let pages =
+            generate_execution(&execution, "benchmarks_2024-10-22_10:00:00", false, &colors)
+                .unwrap();
+        let page = &pages[1].content;
+        assert!(page.contains("plots/previous_hashing-old_cpu_usage.png"));
+        assert!(page.contains("plots/current_hashing_cpu_usage.png"));
+
+        execution.interactive_charts = true;
+        let pages =
+            generate_execution(&execution, "benchmarks_2024-10-22_10:00:00", false, &colors)
+                .unwrap();
+        let page = &pages[1].content;
+        assert!(page.contains("data-chart-series=\"previous\">{}</script>"));
+        assert!(page.contains("data-chart-series=\"current\">{}</script>"));
+        assert!(!page.contains("Diagnostics"));
+    }
+
+    #[test]
+    fn stats_without_a_benchmark_are_skipped_with_a_diagnostic() {
+        let folder = tempfile::tempdir().unwrap();
+        let run = fixtures::benchmarks(vec![fixtures::benchmark("hashing")]);
+        let execution = fixtures::execution(
+            folder.path(),
+            "benchmarks_2024-10-22_10:00:00",
+            &run,
+            &run,
+            PRInformation::default(),
+        );
+
+        // The current run no longer has the benchmark of the stats
+        fixtures::write_run(
+            &execution.runs_folder,
+            "benchmarks_2024-10-22_10:00:00.json",
+            &fixtures::benchmarks(vec![fixtures::benchmark("storage")]),
+        );
+
+        let pages = generate_execution(
+            &execution,
+            "benchmarks_2024-10-22_10:00:00",
+            true,
+            &PhaseColors::load(None).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(pages.len(), 1);
+        assert!(pages[0].content.contains(
+            "<li><b>current</b>: The stats of `/projects/hashing` match no benchmark of the current run.</li>"
+        ));
+    }
+
+    #[test]
+    fn trend_plots_are_referenced_by_their_file_name() {
+        let trend = Trend {
+            benchmark: "a b".to_string(),
+            points: vec![crate::site::trend::TrendPoint {
+                execution: "benchmarks_2024-10-22_10:00:00".to_string(),
+                datetime: "2024-10-22 10:00:00".to_string(),
+                time: Some(1000.0),
+                memory_usage: None,
+                bytecode_size: None,
+                data_section_size: None,
+            }],
+        };
+
+        let html = generate_trend_history(&[trend], false, PlotFormat::Svg);
+
+        assert!(html.contains(&format!(
+            "<img src=\"trend/{}\"",
+            trend::plot_file_name("a b", "time", PlotFormat::Svg)
+        )));
+        // Only the metrics with values have a plot
+        assert_eq!(html.matches("<img").count(), 1);
     }
-    Ok(files)
 }
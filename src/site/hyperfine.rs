@@ -0,0 +1,337 @@
+use crate::types::Benchmark;
+use serde::{Deserialize, Serialize};
+
+/// The significance level used for the confidence intervals and the t-test
+pub const ALPHA: f64 = 0.05;
+
+/// Summary statistics of the hyperfine run times of a benchmark, in seconds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sample {
+    pub count: usize,
+    pub mean: f64,
+    pub stddev: f64,
+    pub min: f64,
+    pub max: f64,
+    /// The lower bound of the confidence interval of the mean.
+    pub ci_low: f64,
+    /// The upper bound of the confidence interval of the mean.
+    pub ci_high: f64,
+}
+
+/// The comparison of the hyperfine run times of a benchmark between two runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Significance {
+    pub previous: Sample,
+    pub current: Sample,
+    /// The Welch's t statistic of the current against the previous mean.
+    pub t: f64,
+    /// The Welch–Satterthwaite degrees of freedom.
+    pub degrees_of_freedom: f64,
+    /// The two-sided p-value of the Welch's t-test.
+    pub p_value: f64,
+    /// Whether the difference of the means is significant at `ALPHA`.
+    pub significant: bool,
+}
+
+impl Sample {
+    /// Compute the summary statistics of a set of run times
+    #[allow(clippy::cast_precision_loss)]
+    pub fn new(times: &[f64]) -> Option<Self> {
+        if times.len() < 2 {
+            return None;
+        }
+
+        let count = times.len() as f64;
+        let mean = times.iter().sum::<f64>() / count;
+        let variance = times.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / (count - 1.0);
+        let stddev = variance.sqrt();
+        let margin = t_critical(count - 1.0) * stddev / count.sqrt();
+
+        Some(Self {
+            count: times.len(),
+            mean,
+            stddev,
+            min: times.iter().copied().fold(f64::INFINITY, f64::min),
+            max: times.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            ci_low: mean - margin,
+            ci_high: mean + margin,
+        })
+    }
+
+    /// Read the per-run times of the first command of a hyperfine JSON export
+    pub fn from_hyperfine(hyperfine: &serde_json::Value) -> Option<Self> {
+        let times: Vec<f64> = hyperfine["results"][0]["times"]
+            .as_array()?
+            .iter()
+            .filter_map(serde_json::Value::as_f64)
+            .collect();
+        Self::new(&times)
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn standard_error_squared(&self) -> f64 {
+        self.stddev.powi(2) / self.count as f64
+    }
+}
+
+/// Compare the hyperfine run times of a benchmark with a Welch's t-test
+#[must_use]
+pub fn compare(previous: &Benchmark, current: &Benchmark) -> Option<Significance> {
+    let previous = Sample::from_hyperfine(previous.hyperfine.as_ref()?)?;
+    let current = Sample::from_hyperfine(current.hyperfine.as_ref()?)?;
+
+    let previous_error = previous.standard_error_squared();
+    let current_error = current.standard_error_squared();
+    let error = previous_error + current_error;
+
+    #[allow(clippy::cast_precision_loss)]
+    let (t, degrees_of_freedom, p_value) = if error > 0.0 {
+        let t = (current.mean - previous.mean) / error.sqrt();
+        let degrees_of_freedom = error.powi(2)
+            / (previous_error.powi(2) / (previous.count as f64 - 1.0)
+                + current_error.powi(2) / (current.count as f64 - 1.0));
+        (
+            t,
+            degrees_of_freedom,
+            two_sided_p_value(t, degrees_of_freedom),
+        )
+    } else {
+        // Without any variance the means either match exactly or differ for certain
+        let p_value = if (current.mean - previous.mean).abs() > 0.0 {
+            0.0
+        } else {
+            1.0
+        };
+        (0.0, 0.0, p_value)
+    };
+
+    Some(Significance {
+        previous,
+        current,
+        t,
+        degrees_of_freedom,
+        p_value,
+        significant: p_value < ALPHA,
+    })
+}
+
+/// The two-sided p-value of a t statistic from the Student's t distribution
+fn two_sided_p_value(t: f64, degrees_of_freedom: f64) -> f64 {
+    regularized_incomplete_beta(
+        degrees_of_freedom / (degrees_of_freedom + t * t),
+        degrees_of_freedom / 2.0,
+        0.5,
+    )
+}
+
+/// The critical t value of a two-sided interval at `ALPHA`, found by bisection
+fn t_critical(degrees_of_freedom: f64) -> f64 {
+    let (mut low, mut high) = (0.0, 1000.0);
+    for _ in 0..100 {
+        let middle = f64::midpoint(low, high);
+        if two_sided_p_value(middle, degrees_of_freedom) > ALPHA {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    f64::midpoint(low, high)
+}
+
+/// The regularized incomplete beta function `I_x(a, b)`
+fn regularized_incomplete_beta(x: f64, a: f64, b: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+
+    // The continued fraction converges quickly only below the mean of the distribution
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(x, a, b) / a
+    } else {
+        1.0 - front * beta_continued_fraction(1.0 - x, b, a) / b
+    }
+}
+
+/// Evaluate the continued fraction of the incomplete beta function with Lentz's method
+fn beta_continued_fraction(x: f64, a: f64, b: f64) -> f64 {
+    const EPSILON: f64 = 1e-14;
+    const TINY: f64 = 1e-300;
+
+    let mut numerator_term = 1.0;
+    let mut denominator_term = 1.0 - (a + b) * x / (a + 1.0);
+    if denominator_term.abs() < TINY {
+        denominator_term = TINY;
+    }
+    denominator_term = 1.0 / denominator_term;
+    let mut result = denominator_term;
+
+    for step in 1..300 {
+        let m = f64::from(step);
+
+        // Even step
+        let numerator = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        denominator_term = 1.0 + numerator * denominator_term;
+        if denominator_term.abs() < TINY {
+            denominator_term = TINY;
+        }
+        numerator_term = 1.0 + numerator / numerator_term;
+        if numerator_term.abs() < TINY {
+            numerator_term = TINY;
+        }
+        denominator_term = 1.0 / denominator_term;
+        result *= denominator_term * numerator_term;
+
+        // Odd step
+        let numerator = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+        denominator_term = 1.0 + numerator * denominator_term;
+        if denominator_term.abs() < TINY {
+            denominator_term = TINY;
+        }
+        numerator_term = 1.0 + numerator / numerator_term;
+        if numerator_term.abs() < TINY {
+            numerator_term = TINY;
+        }
+        denominator_term = 1.0 / denominator_term;
+        let delta = denominator_term * numerator_term;
+        result *= delta;
+
+        if (delta - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+
+    result
+}
+
+/// The natural logarithm of the gamma function, using the Lanczos approximation
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.180_091_729_471_46,
+        -86.505_320_329_416_77,
+        24.014_098_240_830_91,
+        -1.231_739_572_450_155,
+        0.120_865_097_386_617_9e-2,
+        -0.539_523_938_495_3e-5,
+    ];
+
+    let mut y = x;
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let mut series = 1.000_000_000_190_015;
+    for coefficient in COEFFICIENTS {
+        y += 1.0;
+        series += coefficient / y;
+    }
+    -tmp + (2.506_628_274_631_000_5 * series / x).ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site::fixtures;
+
+    // The reference values were computed with mpmath (`betainc`, `loggamma` and `findroot`) at 30
+    // significant digits
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() < tolerance,
+            "{actual} is not within {tolerance} of {expected}"
+        );
+    }
+
+    #[test]
+    fn ln_gamma_matches_known_values() {
+        assert_close(ln_gamma(0.5), 0.572_364_942_924_700_1, 1e-10);
+        assert_close(ln_gamma(3.7), 1.428_072_326_665_387_9, 1e-10);
+        assert_close(ln_gamma(10.0), 12.801_827_480_081_469, 1e-10);
+    }
+
+    #[test]
+    fn regularized_incomplete_beta_matches_known_values() {
+        assert_close(
+            regularized_incomplete_beta(0.3, 2.5, 3.5),
+            0.296_752_989_295_666_4,
+            1e-10,
+        );
+        assert_close(
+            regularized_incomplete_beta(0.9, 0.5, 0.5),
+            0.795_167_235_300_866_6,
+            1e-10,
+        );
+        assert_close(regularized_incomplete_beta(0.5, 4.0, 4.0), 0.5, 1e-12);
+        assert_close(regularized_incomplete_beta(0.2, 3.0, 1.0), 0.008, 1e-12);
+        assert_close(regularized_incomplete_beta(0.0, 2.0, 3.0), 0.0, 1e-12);
+        assert_close(regularized_incomplete_beta(1.0, 2.0, 3.0), 1.0, 1e-12);
+    }
+
+    #[test]
+    fn two_sided_p_value_matches_the_t_distribution() {
+        assert_close(two_sided_p_value(2.0, 10.0), 0.073_388_034_770_740_37, 1e-9);
+        assert_close(two_sided_p_value(1.0, 1.0), 0.5, 1e-9);
+        assert_close(two_sided_p_value(2.5, 4.3), 0.062_448_074_636_211_39, 1e-9);
+        assert_close(two_sided_p_value(0.5, 30.0), 0.620_723_004_885_127_3, 1e-9);
+        assert_close(two_sided_p_value(6.0, 8.0), 0.000_323_393_221_885_149, 1e-9);
+        assert_close(
+            two_sided_p_value(-2.0, 10.0),
+            0.073_388_034_770_740_37,
+            1e-9,
+        );
+        assert_close(two_sided_p_value(0.0, 5.0), 1.0, 1e-12);
+    }
+
+    #[test]
+    fn t_critical_matches_the_t_distribution() {
+        assert_close(t_critical(1.0), 12.706_204_736_174_705, 1e-6);
+        assert_close(t_critical(4.0), 2.776_445_105_197_794, 1e-6);
+        assert_close(t_critical(9.0), 2.262_157_162_798_205_5, 1e-6);
+        assert_close(t_critical(29.0), 2.045_229_642_132_704, 1e-6);
+    }
+
+    #[test]
+    fn sample_computes_the_confidence_interval_of_the_mean() {
+        let sample = Sample::new(&[1.10, 1.20, 1.00, 1.15, 1.05, 1.12]).unwrap();
+
+        assert_eq!(sample.count, 6);
+        assert_close(sample.mean, 1.103_333_333_333_333_4, 1e-12);
+        assert_close(sample.stddev, 0.071_180_521_680_208_72, 1e-12);
+        assert_close(sample.ci_high - sample.mean, 0.074_699_376_317_599_85, 1e-8);
+        assert_close(sample.mean - sample.ci_low, 0.074_699_376_317_599_85, 1e-8);
+        assert_eq!((sample.min, sample.max), (1.00, 1.20));
+
+        assert!(Sample::new(&[1.0]).is_none());
+    }
+
+    #[test]
+    fn compare_runs_a_welch_t_test() {
+        let benchmark = |times: &[f64]| {
+            let mut benchmark = fixtures::benchmark("hashing");
+            benchmark.hyperfine = Some(serde_json::json!({ "results": [{ "times": times }] }));
+            benchmark
+        };
+
+        let significance = compare(
+            &benchmark(&[1.10, 1.20, 1.00, 1.15, 1.05, 1.12]),
+            &benchmark(&[1.30, 1.25, 1.35, 1.20, 1.40]),
+        )
+        .unwrap();
+
+        assert_close(significance.t, 4.297_306_540_302_456, 1e-9);
+        assert_close(significance.degrees_of_freedom, 8.226_462_272_205_033, 1e-9);
+        assert_close(significance.p_value, 0.002_460_724_892_934_828_5, 1e-9);
+        assert!(significance.significant);
+
+        let significance =
+            compare(&benchmark(&[1.0, 1.2, 1.1]), &benchmark(&[1.05, 1.15, 1.1])).unwrap();
+        assert_close(significance.p_value, 1.0, 1e-9);
+        assert!(!significance.significant);
+
+        assert!(compare(&fixtures::benchmark("hashing"), &benchmark(&[1.0, 1.1])).is_none());
+    }
+}
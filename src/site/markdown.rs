@@ -0,0 +1,391 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::site::{
+    escape::is_safe_url,
+    execution::parse_json_benchmarks,
+    execution::Execution,
+    html::execution_page,
+    stats::{added_and_removed, find_by_key, find_match},
+};
+use crate::types::{Benchmark, Benchmarks, Stats};
+use crate::{error::Result, wrap};
+
+/// The metrics of the summary table, the others are on the site
+const SUMMARY_METRICS: [(&str, &str); 3] = [
+    ("time", "Time"),
+    ("bytecode_size", "Bytecode Size"),
+    ("memory_usage", "Memory Usage"),
+];
+
+/// Generate a GitHub flavored Markdown summary of an execution, suitable for a PR comment
+///
+/// # Errors
+///
+/// Returns an error if the stats of the execution can't be read.
+pub fn generate(execution: &Execution, site_url: Option<&str>) -> Result<String> {
+    let previous_benchmarks =
+        parse_json_benchmarks(&execution.previous_benchmarks).map_err(|e| wrap!(e))?;
+    let current_benchmarks =
+        parse_json_benchmarks(&execution.current_benchmarks).map_err(|e| wrap!(e))?;
+    let stats_collection = execution.stats_collection().map_err(|e| wrap!(e))?;
+
+    let identifier = execution
+        .root_folder
+        .file_name()
+        .ok_or_else(|| wrap!("Failed to get the execution identifier.".into()))?
+        .to_str()
+        .ok_or_else(|| wrap!("Failed to get the execution identifier as str.".into()))?;
+
+    let mut markdown = String::new();
+
+    markdown.push_str("## Forc Performance Profiler\n\n");
+
+    // The PR information is user controlled, so it can't add markup or unsafe links
+    let github_information = &execution.github_information;
+    let title = escape(&github_information.title);
+    match url(&github_information.link) {
+        Some(link) => {
+            let _ = write!(markdown, "**PR:** [{title}]({link})");
+        }
+        None => {
+            let _ = write!(markdown, "**PR:** {title}");
+        }
+    }
+    let _ = write!(markdown, " ({})\n\n", code(&github_information.hash));
+
+    let _ = write!(
+        markdown,
+        "**Previous{}:** {}\n\n**Current:** {}\n\n",
+        if execution.pinned_baseline {
+            " (pinned baseline)"
+        } else {
+            ""
+        },
+        generate_benchmarks_information(&previous_benchmarks, &execution.previous_benchmarks)
+            .map_err(|e| wrap!(e))?,
+        generate_benchmarks_information(&current_benchmarks, &execution.current_benchmarks)
+            .map_err(|e| wrap!(e))?,
+    );
+
+    // Percentage deltas of the key metrics per benchmark
+    markdown.push_str("| Benchmark |");
+    for (_, title) in &SUMMARY_METRICS {
+        let _ = write!(markdown, " {title} |");
+    }
+    markdown.push_str("\n|---|");
+    for _ in &SUMMARY_METRICS {
+        markdown.push_str("---:|");
+    }
+    markdown.push('\n');
+
+    for (file_name, stats) in &stats_collection.0 {
+        // Benchmarks with incomplete data are marked, their affected metrics show n/a
+        let warning = if stats.diagnostics.is_empty() {
+            ""
+        } else {
+            " ⚠️"
+        };
+        let _ = write!(
+            markdown,
+            "| {}{warning} |",
+            cell(benchmark_name(&current_benchmarks, file_name))
+        );
+        for (metric, _) in &SUMMARY_METRICS {
+            let _ = write!(markdown, " {} |", format_change(stats, metric));
+        }
+        markdown.push('\n');
+    }
+
+    markdown.push_str("\n🔴 regression · 🟢 improvement · ⚪ unchanged · ⚠️ incomplete data\n\n");
+
+    // Absolute bytecode information per benchmark
+    markdown.push_str("<details><summary>Bytecode</summary>\n\n");
+    markdown.push_str(
+        "| Benchmark | Previous Bytecode Size | Current Bytecode Size | Previous Data Section | Current Data Section |\n",
+    );
+    markdown.push_str("|---|---:|---:|---:|---:|\n");
+
+    for (file_name, _) in &stats_collection.0 {
+        let current_benchmark = find_by_key(&current_benchmarks, file_name);
+        let previous_benchmark =
+            current_benchmark.and_then(|benchmark| find_match(&previous_benchmarks, benchmark));
+        let name = benchmark_name(&current_benchmarks, file_name);
+        let previous = asm_value(previous_benchmark);
+        let current = asm_value(current_benchmark);
+        let _ = writeln!(
+            markdown,
+            "| {} | {} | {} | {} | {} |",
+            cell(name),
+            previous.0,
+            current.0,
+            previous.1,
+            current.1
+        );
+    }
+
+    markdown.push_str("\n</details>\n\n");
+
+    // Benchmarks that only ran in one of the runs can't be compared
+    let (added, removed) = added_and_removed(&previous_benchmarks, &current_benchmarks);
+    for (title, benchmarks) in [("New Benchmarks", added), ("Removed Benchmarks", removed)] {
+        if benchmarks.is_empty() {
+            continue;
+        }
+
+        let _ = write!(markdown, "**{title}:** ");
+        markdown.push_str(
+            &benchmarks
+                .iter()
+                .map(|benchmark| code(&benchmark.name))
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+        markdown.push_str("\n\n");
+    }
+
+    // The other metrics, the plots and the flamegraphs are on the site
+    if let Some(site_url) = site_url.and_then(url) {
+        let _ = writeln!(
+            markdown,
+            "[View the full report with every metric]({}/{})",
+            site_url.trim_end_matches('/'),
+            url(&execution_page(identifier).display().to_string()).unwrap_or_default()
+        );
+    }
+
+    Ok(markdown)
+}
+
+fn generate_benchmarks_information(benchmarks: &Benchmarks, file_path: &Path) -> Result<String> {
+    Ok(format!(
+        "{} (forc {}, {})",
+        code(
+            file_path
+                .file_name()
+                .ok_or_else(|| wrap!("Failed to get the file name from the file path.".into()))?
+                .to_str()
+                .ok_or_else(|| wrap!("Failed to get the file name as str.".into()))?
+        ),
+        escape(&benchmarks.forc_version),
+        escape(&benchmarks.benchmarks_datetime)
+    ))
+}
+
+/// Get the name of a benchmark of the current run by its key in the stats collection
+fn benchmark_name<'a>(benchmarks: &'a Benchmarks, file_name: &'a str) -> &'a str {
+    find_by_key(benchmarks, file_name).map_or(file_name, |benchmark| benchmark.name.as_str())
+}
+
+fn format_change(stats: &Stats, metric: &str) -> String {
+    let Some(value) = super::regression::percentage_change(stats, metric) else {
+        return "n/a".to_string();
+    };
+
+    // Time changes that hyperfine can't tell apart from noise are not marked
+    let significance = stats.hyperfine.as_ref().filter(|_| metric == "time");
+
+    let marker = if significance.is_some_and(|significance| !significance.significant) {
+        "⚪"
+    } else if value > 0.0 {
+        "🔴"
+    } else if value < 0.0 {
+        "🟢"
+    } else {
+        "⚪"
+    };
+
+    match significance {
+        Some(significance) => format!("{marker} {value:+.2}% (p={:.3})", significance.p_value),
+        None => format!("{marker} {value:+.2}%"),
+    }
+}
+
+/// Get the bytecode size and data section size of a benchmark as displayable strings
+fn asm_value(benchmark: Option<&Benchmark>) -> (String, String) {
+    let asm_information = benchmark.and_then(|b| b.asm_information.as_ref());
+
+    let size = |value: Option<&serde_json::Value>| {
+        value
+            .and_then(serde_json::Value::as_u64)
+            .map_or("n/a".to_string(), |size| format!("{size} bytes"))
+    };
+
+    (
+        size(asm_information.map(|asm| &asm["bytecode_size"])),
+        size(asm_information.map(|asm| &asm["data_section"]["size"])),
+    )
+}
+
+/// Escape the Markdown and HTML special characters of a text, and join its lines
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '(' | ')' | '<' | '>' | '!' | '|' | '~' | '&'
+            | '#' => {
+                escaped.push('\\');
+                escaped.push(character);
+            }
+            _ if character.is_control() => escaped.push(' '),
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+/// Format a text as inline code
+fn code(text: &str) -> String {
+    // The code span is fenced by more backticks than the text contains in a row
+    let mut longest = 0;
+    let mut run = 0;
+    for character in text.chars() {
+        run = if character == '`' { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+    let fence = "`".repeat(longest + 1);
+    let padding = if text.starts_with('`') || text.ends_with('`') {
+        " "
+    } else {
+        ""
+    };
+
+    let text = text
+        .chars()
+        .map(|character| {
+            if character.is_control() {
+                ' '
+            } else {
+                character
+            }
+        })
+        .collect::<String>();
+
+    format!("{fence}{padding}{text}{padding}{fence}")
+}
+
+/// Format a text as inline code in a table cell
+fn cell(text: &str) -> String {
+    // Tables split cells on pipes even inside code spans
+    code(text).replace('|', "\\|")
+}
+
+/// Get a URL that can be used as a Markdown link destination, `None` when its scheme isn't safe
+fn url(url: &str) -> Option<String> {
+    if url.trim().is_empty() || !is_safe_url(url) {
+        return None;
+    }
+
+    let mut encoded = String::with_capacity(url.len());
+    for character in url.chars() {
+        if character.is_ascii_whitespace()
+            || character.is_ascii_control()
+            || matches!(
+                character,
+                '<' | '>' | '(' | ')' | '[' | ']' | '|' | '`' | '"' | '\\'
+            )
+        {
+            let _ = write!(encoded, "%{:02X}", u32::from(character));
+        } else {
+            encoded.push(character);
+        }
+    }
+    Some(encoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site::fixtures;
+    use crate::types::{Collection, PRInformation};
+
+    #[test]
+    fn generate_summarises_the_changes_of_every_benchmark() {
+        let folder = tempfile::tempdir().unwrap();
+        let run = fixtures::benchmarks(vec![fixtures::benchmark("hashing")]);
+        let execution = fixtures::execution(
+            folder.path(),
+            "benchmarks_2024-10-22_10:00:00",
+            &run,
+            &run,
+            PRInformation {
+                hash: "abc123".to_string(),
+                title: "Speed up hashing".to_string(),
+                link: "https://github.com/FuelLabs/sway/pull/1".to_string(),
+            },
+        );
+        std::fs::create_dir_all(&execution.stats_folder).unwrap();
+        std::fs::write(
+            execution.stats_file().unwrap(),
+            serde_json::to_string(&Collection(vec![(
+                "/projects/hashing".to_string(),
+                Stats {
+                    time: Some((100.0, 10.0)),
+                    bytecode_size: Some((0.0, 0.0)),
+                    memory_usage: Some((-50.0, -5.0)),
+                    ..Stats::default()
+                },
+            )]))
+            .unwrap(),
+        )
+        .unwrap();
+
+        let markdown = generate(&execution, Some("https://example.com/site/")).unwrap();
+
+        assert!(markdown.contains(
+            "**PR:** [Speed up hashing](https://github.com/FuelLabs/sway/pull/1) (`abc123`)"
+        ));
+        // The table is cut to the key metrics
+        assert!(markdown.contains("| Benchmark | Time | Bytecode Size | Memory Usage |"));
+        assert!(markdown.contains("| `hashing` | 🔴 +10.00% | ⚪ +0.00% | 🟢 -5.00% |"));
+        assert!(markdown.contains("| `hashing` | 100 bytes | 100 bytes | 10 bytes | 10 bytes |"));
+        assert!(markdown.contains(
+            "[View the full report with every metric](https://example.com/site/executions/benchmarks_2024-10-22_10:00:00/index.html)"
+        ));
+    }
+
+    #[test]
+    fn hostile_pr_information_is_escaped() {
+        let folder = tempfile::tempdir().unwrap();
+        let previous = fixtures::benchmarks(vec![fixtures::benchmark("a|b`c")]);
+        let current = fixtures::benchmarks(vec![fixtures::benchmark("a|b`c")]);
+        let execution = fixtures::execution(
+            folder.path(),
+            "benchmarks_2024-10-22_10:00:00",
+            &previous,
+            &current,
+            PRInformation {
+                hash: "abc`def".to_string(),
+                title: "[<script>alert(1)</script>](javascript:alert(1))".to_string(),
+                link: "javascript:alert(1)".to_string(),
+            },
+        );
+
+        let markdown = generate(&execution, None).unwrap();
+
+        assert!(markdown.contains(
+            "**PR:** \\[\\<script\\>alert\\(1\\)\\</script\\>\\]\\(javascript:alert\\(1\\)\\) (``abc`def``)"
+        ));
+        assert!(!markdown.contains("](javascript:"));
+        assert!(markdown.contains("| ``a\\|b`c`` |"));
+    }
+
+    #[test]
+    fn code_fences_the_backticks_of_the_text() {
+        assert_eq!(code("name"), "`name`");
+        assert_eq!(code("a`b"), "``a`b``");
+        assert_eq!(code("`a``"), "``` `a`` ```");
+        assert_eq!(cell("a|b"), "`a\\|b`");
+    }
+
+    #[test]
+    fn url_rejects_unsafe_schemes_and_encodes_the_destination() {
+        assert_eq!(url("javascript:alert(1)"), None);
+        assert_eq!(url(" java\tscript:alert(1)"), None);
+        assert_eq!(url(""), None);
+        assert_eq!(
+            url("https://example.com/a b(c)").as_deref(),
+            Some("https://example.com/a%20b%28c%29")
+        );
+    }
+}
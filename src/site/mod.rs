@@ -1,5 +1,41 @@
+/// The stylesheet and script written to the site for offline use
+pub mod assets;
+/// The series of the interactive charts
+pub mod chart;
+/// Ad-hoc comparisons of two runs outside of the site
+pub mod compare;
+/// The data folder of the site, with the stored runs and the executions
 pub mod data;
+/// The problems with the data of a benchmark
+pub mod diagnostics;
+/// The site on disk and the regeneration of its pages
 pub mod dynosite;
+/// HTML escaping of the values put in the pages
+pub mod escape;
+/// The comparison of a previous and a current run of the benchmarks
 pub mod execution;
+/// The benchmarks, runs and executions the tests are built from
+#[cfg(test)]
+pub(crate) mod fixtures;
+/// Differential flamegraphs and hot functions
+pub mod flamegraph;
+/// The pages of the site
 pub mod html;
+/// The statistics of the hyperfine timings
+pub mod hyperfine;
+/// The Markdown summary of an execution
+pub mod markdown;
+/// The colours of the compiler phases
+pub mod palette;
+/// The PNG and SVG plots of the frames of the benchmarks
 pub mod plot;
+/// The regression thresholds and the regression gate
+pub mod regression;
+/// The machine readable JSON report of an execution
+pub mod report;
+/// The metrics of the benchmarks and their changes between the runs
+pub mod stats;
+/// The templates the pages are rendered with
+pub mod template;
+/// The history of the metrics across the executions
+pub mod trend;
@@ -0,0 +1,256 @@
+use std::path::Path;
+
+use plotters::style::RGBColor;
+
+use crate::{error::Result, wrap};
+
+/// The colours of the phases of the sway compiler, matched by substring of the phase name
+const DEFAULT_PHASE_COLORS: [(&str, RGBColor); 6] = [
+    ("compile to ast", RGBColor(255, 0, 0)),
+    (
+        "parse the program to a concrete syntax tree (CST)",
+        RGBColor(0, 255, 0),
+    ),
+    (
+        "parse the concrete syntax tree (CST) to a typed AST",
+        RGBColor(0, 0, 255),
+    ),
+    ("compile ast to asm", RGBColor(255, 165, 0)),
+    ("generate JSON ABI program", RGBColor(255, 0, 255)),
+    ("compile asm to bytecode", RGBColor(0, 255, 255)),
+];
+
+/// The palette used for phases without a configured colour
+const PALETTE: [RGBColor; 12] = [
+    RGBColor(31, 119, 180),
+    RGBColor(255, 127, 14),
+    RGBColor(44, 160, 44),
+    RGBColor(214, 39, 40),
+    RGBColor(148, 103, 189),
+    RGBColor(140, 86, 75),
+    RGBColor(227, 119, 194),
+    RGBColor(127, 127, 127),
+    RGBColor(188, 189, 34),
+    RGBColor(23, 190, 207),
+    RGBColor(57, 59, 121),
+    RGBColor(99, 121, 57),
+];
+
+/// Maps the phase names of the benchmarks to the colours used in the plots and charts
+pub struct PhaseColors {
+    colors: Vec<(String, RGBColor)>,
+}
+
+impl PhaseColors {
+    /// Load the phase colours, with the colours of the optional JSON file taking precedence over
+    /// the defaults. The file maps a phase name, or a part of it, to a `#rrggbb` colour.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read or parsed, or if a colour isn't `#rrggbb`.
+    pub fn load(file: Option<&Path>) -> Result<Self> {
+        let mut colors = vec![];
+
+        if let Some(file) = file {
+            let overrides: serde_json::Map<String, serde_json::Value> =
+                serde_json::from_str(&std::fs::read_to_string(file).map_err(|e| wrap!(e.into()))?)
+                    .map_err(|e| wrap!(e.into()))?;
+
+            for (name, color) in overrides {
+                let color = color.as_str().and_then(parse_hex).ok_or_else(|| {
+                    wrap!(format!(
+                        "Invalid colour `{color}` for the phase `{name}`, expected `#rrggbb`."
+                    )
+                    .into())
+                })?;
+                colors.push((name, color));
+            }
+        }
+
+        colors.extend(
+            DEFAULT_PHASE_COLORS
+                .iter()
+                .map(|(name, color)| ((*name).to_string(), *color)),
+        );
+
+        Ok(Self { colors })
+    }
+
+    /// Get the colour of a phase, preferring an exact name match over a partial one.
+    /// Unknown phases get a colour from the palette derived from their name, so it is stable
+    /// across benchmarks and executions.
+    #[must_use]
+    pub fn get(&self, phase: &str) -> RGBColor {
+        self.configured(phase)
+            .unwrap_or_else(|| PALETTE[palette_slot(phase)])
+    }
+
+    /// Get the colours of the phases of a benchmark, looked up with `get` like the colours of
+    /// the site.
+    /// The phases without a configured colour get distinct colours of the palette while it has
+    /// unused colours: each phase starts at the slot derived from its name and probes forward
+    /// past the colours taken by the other phases, so the colours are stable for the same phases.
+    #[must_use]
+    pub fn for_phases<'a>(&self, phases: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut phases: Vec<&str> = phases.into_iter().collect();
+        phases.sort_unstable();
+        phases.dedup();
+
+        let mut colors = vec![];
+        let mut unconfigured = vec![];
+        for phase in phases {
+            match self.configured(phase) {
+                Some(color) => colors.push((phase.to_string(), color)),
+                None => unconfigured.push(phase),
+            }
+        }
+
+        for phase in unconfigured {
+            let slot = palette_slot(phase);
+            let color = (0..PALETTE.len())
+                .map(|offset| PALETTE[(slot + offset) % PALETTE.len()])
+                .find(|color| colors.iter().all(|(_, used)| used != color))
+                .unwrap_or(PALETTE[slot]);
+            colors.push((phase.to_string(), color));
+        }
+
+        colors.extend(self.colors.iter().cloned());
+
+        Self { colors }
+    }
+
+    /// Get the configured colour of a phase, preferring an exact name match over a partial one
+    fn configured(&self, phase: &str) -> Option<RGBColor> {
+        self.colors
+            .iter()
+            .find(|(name, _)| name == phase)
+            .or_else(|| {
+                self.colors
+                    .iter()
+                    .find(|(name, _)| phase.contains(name.as_str()))
+            })
+            .map(|(_, color)| *color)
+    }
+
+    /// Get the colour of a phase as a `#rrggbb` string
+    #[must_use]
+    pub fn hex(&self, phase: &str) -> String {
+        let RGBColor(r, g, b) = self.get(phase);
+        format!("#{r:02x}{g:02x}{b:02x}")
+    }
+}
+
+/// Parse a `#rrggbb` colour
+fn parse_hex(color: &str) -> Option<RGBColor> {
+    let hex = color.strip_prefix('#')?;
+
+    if hex.len() != 6 {
+        return None;
+    }
+
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+
+    Some(RGBColor(channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// Get the slot of the palette derived from the name of a phase
+fn palette_slot(phase: &str) -> usize {
+    (fnv1a(phase) % PALETTE.len() as u64) as usize
+}
+
+/// The 64-bit FNV-1a hash, which unlike the std hasher is stable across Rust versions
+pub(crate) fn fnv1a(value: &str) -> u64 {
+    value.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn configured_colours_take_precedence_over_the_defaults() {
+        let folder = tempfile::tempdir().unwrap();
+        let file = folder.path().join("colors.json");
+        std::fs::write(
+            &file,
+            r##"{ "compile to ast": "#010203", "typed AST": "#0A0B0C" }"##,
+        )
+        .unwrap();
+
+        let colors = PhaseColors::load(Some(&file)).unwrap();
+
+        assert_eq!(colors.get("compile to ast"), RGBColor(1, 2, 3));
+        assert_eq!(colors.hex("compile to ast"), "#010203");
+        // A part of the name is enough, but an exact match wins
+        assert_eq!(colors.get("check the typed AST"), RGBColor(10, 11, 12));
+        assert_eq!(
+            colors.get("parse the concrete syntax tree (CST) to a typed AST"),
+            RGBColor(0, 0, 255)
+        );
+        assert_eq!(colors.get("compile asm to bytecode"), RGBColor(0, 255, 255));
+    }
+
+    #[test]
+    fn invalid_colours_are_rejected() {
+        let folder = tempfile::tempdir().unwrap();
+        let file = folder.path().join("colors.json");
+
+        for color in ["\"red\"", "\"#12345\"", "\"#gggggg\"", "12"] {
+            std::fs::write(&file, format!("{{ \"compile to ast\": {color} }}")).unwrap();
+            assert!(PhaseColors::load(Some(&file)).is_err(), "{color}");
+        }
+    }
+
+    #[test]
+    fn unknown_phases_get_a_stable_palette_colour() {
+        let colors = PhaseColors::load(None).unwrap();
+
+        assert_eq!(colors.get("phase a"), colors.get("phase a"));
+        assert!(PALETTE.contains(&colors.get("phase a")));
+        // The FNV-1a hash is fixed, so the colours do not change between builds
+        assert_eq!(fnv1a(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a("a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn phases_of_a_benchmark_get_distinct_colours() {
+        let colors = PhaseColors::load(None).unwrap();
+        let phases: Vec<String> = (0..PALETTE.len()).map(|i| format!("phase {i}")).collect();
+
+        // Twelve names over twelve slots, so some of them share the slot derived from their name
+        let slots: std::collections::HashSet<_> =
+            phases.iter().map(|phase| palette_slot(phase)).collect();
+        assert!(slots.len() < phases.len());
+
+        let benchmark = colors.for_phases(phases.iter().map(String::as_str));
+        let assigned: Vec<_> = phases.iter().map(|phase| benchmark.get(phase)).collect();
+        for (i, color) in assigned.iter().enumerate() {
+            assert!(
+                !assigned[i + 1..].contains(color),
+                "{} shares a colour",
+                phases[i]
+            );
+        }
+    }
+
+    #[test]
+    fn phase_colours_are_stable() {
+        let colors = PhaseColors::load(None).unwrap();
+        let phases = ["phase a", "phase b", "phase c", "compile to ast"];
+
+        let forward = colors.for_phases(phases);
+        let backward = colors.for_phases(phases.iter().rev().copied());
+        for phase in phases {
+            assert_eq!(forward.get(phase), backward.get(phase));
+        }
+
+        // Configured colours and phases without collisions keep their colour
+        assert_eq!(forward.get("compile to ast"), RGBColor(255, 0, 0));
+        assert_eq!(
+            colors.for_phases(["phase a"]).get("phase a"),
+            colors.get("phase a")
+        );
+    }
+}
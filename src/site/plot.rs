@@ -1,153 +1,219 @@
-use std::path::PathBuf;
+use std::{ops::Range, path::PathBuf};
 
-use crate::error::Result;
+use crate::error::{warn, Error, Result};
 use crate::{
+    cli::PlotFormat,
+    site::{chart::CHART_METRICS, palette::PhaseColors, stats},
     types::{Benchmark, BenchmarkFrame, Benchmarks},
     wrap,
 };
-use plotters::prelude::*;
+use plotters::{coord::Shift, prelude::*};
 
 const LABEL_FONT_SIZE: u32 = 30;
 const X_AXIS_LABEL_FONT_SIZE: u32 = 10;
 const PLOT_WIDTH: u32 = 1200;
 const PLOT_HEIGHT: u32 = 400;
 
-pub fn generate_plots(benchmarks: &Benchmarks, output_file: &str) -> Result<Vec<PathBuf>> {
+/// Generate one plot per metric for every benchmark. A benchmark whose plots can't be drawn is
+/// reported as a warning and skipped, so it doesn't take down the plots of the other benchmarks.
+#[must_use]
+pub fn generate_plots(
+    benchmarks: &Benchmarks,
+    output_file: &str,
+    format: PlotFormat,
+    colors: &PhaseColors,
+) -> Vec<PathBuf> {
     let mut plots = Vec::new();
     for benchmark in &benchmarks.benchmarks {
-        let metrics: [(&str, &str, fn(&BenchmarkFrame) -> i64); 7] = [
-            (
-                "cpu_usage",
-                "CPU Usage Over Time",
-                |frame: &BenchmarkFrame| safe_f32_to_i64(frame.cpu_usage),
-            ),
-            (
-                "memory_usage",
-                "Memory Usage Over Time",
-                |frame: &BenchmarkFrame| {
-                    frame
-                        .memory_usage
-                        .try_into()
-                        .expect("Failed to convert memory usage to i64")
-                },
-            ),
-            (
-                "virtual_memory_usage",
-                "Virtual Memory Usage Over Time",
-                |frame: &BenchmarkFrame| {
-                    frame
-                        .virtual_memory_usage
-                        .try_into()
-                        .expect("Failed to convert virtual memory usage to i64")
-                },
-            ),
-            (
-                "disk_total_written_bytes",
-                "Disk Total Written Bytes Over Time",
-                |frame: &BenchmarkFrame| {
-                    frame
-                        .disk_total_written_bytes
-                        .try_into()
-                        .expect("Failed to convert disk_total_written_bytes usage to i64")
-                },
-            ),
-            (
-                "disk_written_bytes",
-                "Disk Written Bytes Over Time",
-                |frame: &BenchmarkFrame| {
-                    frame
-                        .disk_written_bytes
-                        .try_into()
-                        .expect("Failed to convert disk_written_bytes usage to i64")
-                },
-            ),
-            (
-                "disk_total_read_bytes",
-                "Disk Total Read Bytes Over Time",
-                |frame: &BenchmarkFrame| {
-                    frame
-                        .disk_total_read_bytes
-                        .try_into()
-                        .expect("Failed to convert disk_total_read_bytes usage to i64")
-                },
-            ),
-            (
-                "disk_read_bytes",
-                "Disk Read Bytes Over Time",
-                |frame: &BenchmarkFrame| {
-                    frame
-                        .disk_read_bytes
-                        .try_into()
-                        .expect("Failed to convert disk_read_bytes usage to i64")
-                },
-            ),
-        ];
+        match generate_benchmark_plots(benchmark, output_file, format, colors) {
+            Ok(benchmark_plots) => plots.extend(benchmark_plots),
+            Err(e) => warn(&format!("Skipping the plots of `{}`", benchmark.name), &e),
+        }
+    }
 
-        let frames = benchmark
-            .frames
-            .lock()
-            .expect("Failed to get frames lock")
-            .clone();
+    plots
+}
 
-        for (suffix, title, value_extractor) in &metrics {
-            let output_file = format!("{output_file}_{}_{}.png", benchmark.name, suffix);
+/// Generate one plot per metric for a benchmark
+fn generate_benchmark_plots(
+    benchmark: &Benchmark,
+    output_file: &str,
+    format: PlotFormat,
+    colors: &PhaseColors,
+) -> Result<Vec<PathBuf>> {
+    let mut plots = Vec::new();
 
-            let y_max_calculator: Box<dyn Fn(&[BenchmarkFrame]) -> Result<i64>> =
-                if *suffix == "cpu_usage" {
-                    Box::new(|_: &[BenchmarkFrame]| Ok(100))
-                } else {
-                    Box::new(|frames: &[BenchmarkFrame]| calculate_y_max(frames, *value_extractor))
-                };
-
-            generate(
-                &frames,
-                benchmark,
-                &output_file,
-                title,
-                y_max_calculator,
-                |frame| {
-                    (
-                        i64::try_from(frame.relative_timestamp.as_millis())
-                            .expect("Failed to convert relative timestamp to i64"),
-                        value_extractor(frame),
-                    )
-                },
-            )?;
-            plots.push(PathBuf::from(output_file));
-        }
+    let metrics: [(&str, &str, fn(&BenchmarkFrame) -> Result<i64>); 7] = [
+        (
+            "cpu_usage",
+            "CPU Usage Over Time",
+            |frame: &BenchmarkFrame| safe_f32_to_i64(frame.cpu_usage),
+        ),
+        (
+            "memory_usage",
+            "Memory Usage Over Time",
+            |frame: &BenchmarkFrame| Ok(saturating_u64_to_i64(frame.memory_usage)),
+        ),
+        (
+            "virtual_memory_usage",
+            "Virtual Memory Usage Over Time",
+            |frame: &BenchmarkFrame| Ok(saturating_u64_to_i64(frame.virtual_memory_usage)),
+        ),
+        (
+            "disk_total_written_bytes",
+            "Disk Total Written Bytes Over Time",
+            |frame: &BenchmarkFrame| Ok(saturating_u64_to_i64(frame.disk_total_written_bytes)),
+        ),
+        (
+            "disk_written_bytes",
+            "Disk Written Bytes Over Time",
+            |frame: &BenchmarkFrame| Ok(saturating_u64_to_i64(frame.disk_written_bytes)),
+        ),
+        (
+            "disk_total_read_bytes",
+            "Disk Total Read Bytes Over Time",
+            |frame: &BenchmarkFrame| Ok(saturating_u64_to_i64(frame.disk_total_read_bytes)),
+        ),
+        (
+            "disk_read_bytes",
+            "Disk Read Bytes Over Time",
+            |frame: &BenchmarkFrame| Ok(saturating_u64_to_i64(frame.disk_read_bytes)),
+        ),
+    ];
+
+    let frames = benchmark
+        .frames
+        .lock()
+        .map_err(|_| {
+            wrap!(Error::MissingFrames {
+                benchmark: benchmark.name.clone(),
+            })
+        })?
+        .clone();
+
+    if frames.is_empty() {
+        return Err(wrap!(Error::MissingFrames {
+            benchmark: benchmark.name.clone(),
+        })
+        .into());
+    }
+
+    for (suffix, title, value_extractor) in &metrics {
+        let output_file = format!(
+            "{output_file}_{}_{}.{}",
+            benchmark.name,
+            suffix,
+            format.extension()
+        );
+
+        let y_max_calculator: Box<dyn Fn(&[BenchmarkFrame]) -> Result<i64>> =
+            if *suffix == "cpu_usage" {
+                Box::new(|_: &[BenchmarkFrame]| Ok(100))
+            } else {
+                Box::new(|frames: &[BenchmarkFrame]| calculate_y_max(frames, *value_extractor))
+            };
+
+        generate(
+            &frames,
+            benchmark,
+            &output_file,
+            format,
+            colors,
+            title,
+            y_max_calculator,
+            |frame| {
+                Ok((
+                    i64::try_from(frame.relative_timestamp.as_millis())
+                        .map_err(|e| wrap!(e.into()))?,
+                    value_extractor(frame).map_err(|e| wrap!(e))?,
+                ))
+            },
+        )
+        .map_err(|e| wrap!(e))?;
+        plots.push(PathBuf::from(output_file));
     }
 
     Ok(plots)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn generate<F, G>(
     frames: &[BenchmarkFrame],
     benchmark: &Benchmark,
     output_file: &str,
+    format: PlotFormat,
+    colors: &PhaseColors,
     title: &str,
     y_max_calculator: F,
     data_mapper: G,
 ) -> Result<()>
 where
     F: Fn(&[BenchmarkFrame]) -> Result<i64>,
-    G: Fn(&BenchmarkFrame) -> (i64, i64),
+    G: Fn(&BenchmarkFrame) -> Result<(i64, i64)>,
 {
     let y_max = y_max_calculator(frames)?;
 
-    create(benchmark, output_file, title, y_max, data_mapper)
+    create(
+        benchmark,
+        output_file,
+        format,
+        colors,
+        title,
+        y_max,
+        data_mapper,
+    )
 }
 
+/// # Errors
+///
+/// Returns an error if the plot can't be drawn or written.
 pub fn create<F>(
     benchmark: &Benchmark,
     output_file: &str,
+    format: PlotFormat,
+    colors: &PhaseColors,
     title: &str,
     y_max: i64,
     data_mapper: F,
 ) -> Result<()>
 where
-    F: Fn(&BenchmarkFrame) -> (i64, i64),
+    F: Fn(&BenchmarkFrame) -> Result<(i64, i64)>,
 {
-    let root = BitMapBackend::new(output_file, (PLOT_WIDTH, PLOT_HEIGHT)).into_drawing_area();
+    match format {
+        PlotFormat::Png => draw(
+            &BitMapBackend::new(output_file, (PLOT_WIDTH, PLOT_HEIGHT)).into_drawing_area(),
+            benchmark,
+            colors,
+            title,
+            y_max,
+            data_mapper,
+        ),
+        PlotFormat::Svg => draw(
+            &SVGBackend::new(output_file, (PLOT_WIDTH, PLOT_HEIGHT)).into_drawing_area(),
+            benchmark,
+            colors,
+            title,
+            y_max,
+            data_mapper,
+        ),
+    }
+}
+
+fn draw<DB, F>(
+    root: &DrawingArea<DB, Shift>,
+    benchmark: &Benchmark,
+    colors: &PhaseColors,
+    title: &str,
+    y_max: i64,
+    data_mapper: F,
+) -> Result<()>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+    F: Fn(&BenchmarkFrame) -> Result<(i64, i64)>,
+{
+    let colors = colors.for_phases(benchmark.phases.iter().map(|phase| phase.name.as_str()));
+
     root.fill(&WHITE)?;
 
     // Split the drawing area into two: one for the chart and one for the legend
@@ -156,9 +222,17 @@ where
     let frames = benchmark
         .frames
         .lock()
-        .expect("Failed to get benchmarking lock")
+        .map_err(|_| {
+            wrap!(Error::MissingFrames {
+                benchmark: benchmark.name.clone(),
+            })
+        })?
         .clone();
-    let phases = benchmark.phases.clone();
+    let points = frames
+        .iter()
+        .map(&data_mapper)
+        .collect::<Result<Vec<_>>>()
+        .map_err(|e| wrap!(e))?;
 
     let start_time = i64::try_from(
         benchmark
@@ -176,42 +250,29 @@ where
     .map_err(|e| wrap!(e.into()))?;
     let total_duration = end_time - start_time;
 
-    // Find the minimum start time among all phases
+    // The phases without timings can't be drawn
+    let mut phases = vec![];
+    for phase in &benchmark.phases {
+        let (Some(start_time), Some(end_time)) = (phase.start_time, phase.end_time) else {
+            continue;
+        };
+        phases.push((
+            i64::try_from(start_time.as_millis()).map_err(|e| wrap!(e.into()))?,
+            i64::try_from(end_time.as_millis()).map_err(|e| wrap!(e.into()))?,
+            phase.name.clone(),
+        ));
+    }
+
+    // Normalize the start and end times of each phase to the start of the first phase
     let min_start_time = phases
         .iter()
-        .map(|phase| {
-            i64::try_from(
-                phase
-                    .start_time
-                    .expect("Failed to get start time from phase")
-                    .as_millis(),
-            )
-            .expect("Failed to convert start time to i64")
-        })
+        .map(|(start_time, _, _)| *start_time)
         .min()
-        .ok_or_else(|| wrap!("Failed to get min start time".into()))?;
-
-    // Normalize the start and end times of each phase
+        .unwrap_or_default();
     let normalized_phases: Vec<_> = phases
-        .iter()
-        .map(|phase| {
-            let start_time = i64::try_from(
-                phase
-                    .start_time
-                    .expect("Failed to get phase start time for normalized phases")
-                    .as_millis(),
-            )
-            .expect("Failed to convert start time to i64")
-                - min_start_time;
-            let end_time = i64::try_from(
-                phase
-                    .end_time
-                    .expect("Failed to get phase end time for normalized phases")
-                    .as_millis(),
-            )
-            .expect("Failed to convert end time to i64")
-                - min_start_time;
-            (start_time, end_time, phase.name.clone())
+        .into_iter()
+        .map(|(start_time, end_time, name)| {
+            (start_time - min_start_time, end_time - min_start_time, name)
         })
         .collect();
 
@@ -238,14 +299,7 @@ where
                 .find(|(start_time, end_time, _)| is_between(x, start_time, &(end_time + 100)));
 
             if let Some((_, _, name)) = phase {
-                format!(
-                    "{}-{}",
-                    x,
-                    name.to_string()
-                        .split_whitespace()
-                        .next()
-                        .expect("Failed to get phase name for x label")
-                )
+                format!("{}-{}", x, name.split_whitespace().next().unwrap_or(name))
             } else {
                 format!("{x}")
             }
@@ -260,55 +314,56 @@ where
 
     let opacity = 0.5;
 
-    let colors = [
-        ("compile to ast", RGBAColor(255, 0, 0, opacity)),
-        (
-            "parse the program to a concrete syntax tree (CST)",
-            RGBAColor(0, 255, 0, opacity),
-        ),
-        (
-            "parse the concrete syntax tree (CST) to a typed AST",
-            RGBAColor(0, 0, 255, opacity),
-        ),
-        ("compile ast to asm", RGBAColor(255, 165, 0, opacity)),
-        ("generate JSON ABI program", RGBAColor(255, 0, 255, opacity)),
-        ("compile asm to bytecode", RGBAColor(0, 255, 255, opacity)),
-    ];
-
     // Draw phase overlays
     for (start_time, end_time, name) in &normalized_phases {
-        let color = colors
-            .iter()
-            .find(|(color_name, _)| name.contains(color_name))
-            .map(|(_, color)| color)
-            .ok_or_else(|| wrap!("Failed to construct a color.".into()))?;
-
         chart
             .draw_series(std::iter::once(Rectangle::new(
                 [(*start_time, 0), (*end_time, y_max)],
-                color.filled(),
+                colors.get(name).mix(opacity).filled(),
             )))
             .map_err(|e| wrap!(e.into()))?;
     }
 
     chart
-        .draw_series(LineSeries::new(frames.iter().map(data_mapper), &RED))
+        .draw_series(LineSeries::new(points, &RED))
         .map_err(|e| wrap!(e.into()))?;
 
     // Draw legend at the bottom
     let legend_font = ("sans-serif", 10).into_font().color(&BLACK);
 
-    let legend_spacing = 210;
-    let legend_items: Vec<_> = colors
+    // One legend item for every phase of the benchmark, spread over the width of the plot
+    let mut names: Vec<&String> = vec![];
+    for (_, _, name) in &normalized_phases {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+
+    let legend_spacing = PLOT_WIDTH as i32 / names.len().max(1) as i32;
+    let legend_items: Vec<_> = names
         .iter()
         .enumerate()
-        .map(|(i, (name, color))| {
+        .map(|(i, name)| {
             // Draw the colored circle
-            let circle = Circle::new((10 + i as i32 * legend_spacing, 10), 5, color.filled());
+            let circle = Circle::new(
+                (10 + i as i32 * legend_spacing, 10),
+                5,
+                colors.get(name).mix(opacity).filled(),
+            );
 
-            // Draw the text next to the circle
+            // Draw the text next to the circle, shortened to the space of the item
+            let max_chars = usize::try_from((legend_spacing - 30) / 5).unwrap_or_default();
             let text = Text::new(
-                (*name).to_string(),
+                if name.chars().count() > max_chars {
+                    format!(
+                        "{}...",
+                        name.chars()
+                            .take(max_chars.saturating_sub(3))
+                            .collect::<String>()
+                    )
+                } else {
+                    (*name).clone()
+                },
                 (20 + i as i32 * legend_spacing, 10),
                 legend_font.clone(),
             );
@@ -326,11 +381,360 @@ where
     Ok(())
 }
 
+/// Generate one plot per metric for every benchmark in both runs, drawing the previous and the
+/// current frames on shared axes, optionally with the difference between them
+#[must_use]
+pub fn generate_overlay_plots(
+    previous_benchmarks: &Benchmarks,
+    current_benchmarks: &Benchmarks,
+    output_file: &str,
+    format: PlotFormat,
+    delta: bool,
+) -> Vec<PathBuf> {
+    let mut plots = Vec::new();
+
+    for current in &current_benchmarks.benchmarks {
+        let Some(previous) = stats::find_match(previous_benchmarks, current) else {
+            continue;
+        };
+
+        match generate_benchmark_overlay_plots(previous, current, output_file, format, delta) {
+            Ok(benchmark_plots) => plots.extend(benchmark_plots),
+            Err(e) => warn(&format!("Skipping the plots of `{}`", current.name), &e),
+        }
+    }
+
+    plots
+}
+
+/// Generate one plot per metric with both runs of a benchmark on shared axes
+fn generate_benchmark_overlay_plots(
+    previous: &Benchmark,
+    current: &Benchmark,
+    output_file: &str,
+    format: PlotFormat,
+    delta: bool,
+) -> Result<Vec<PathBuf>> {
+    let mut plots = Vec::new();
+
+    let frames = |benchmark: &Benchmark| -> Result<Vec<BenchmarkFrame>> {
+        Ok(benchmark
+            .frames
+            .lock()
+            .map_err(|_| {
+                wrap!(Error::MissingFrames {
+                    benchmark: benchmark.name.clone(),
+                })
+            })?
+            .clone())
+    };
+    let previous_frames = frames(previous).map_err(|e| wrap!(e))?;
+    let current_frames = frames(current).map_err(|e| wrap!(e))?;
+
+    for (suffix, title, value_extractor) in &CHART_METRICS {
+        let output_file = format!(
+            "{output_file}_{}_{}.{}",
+            current.name,
+            suffix,
+            format.extension()
+        );
+
+        let series = |frames: &[BenchmarkFrame]| -> Result<Vec<(i64, f64)>> {
+            frames
+                .iter()
+                .map(|frame| {
+                    Ok((
+                        i64::try_from(frame.relative_timestamp.as_millis())
+                            .map_err(|e| wrap!(e.into()))?,
+                        value_extractor(frame),
+                    ))
+                })
+                .collect()
+        };
+        let previous_series = series(&previous_frames).map_err(|e| wrap!(e))?;
+        let current_series = series(&current_frames).map_err(|e| wrap!(e))?;
+        let delta_series = delta.then(|| difference(&previous_series, &current_series));
+
+        create_overlay(
+            &output_file,
+            format,
+            &format!("{title} Over Time"),
+            &previous_series,
+            &current_series,
+            delta_series.as_deref(),
+            (*suffix == "cpu_usage").then_some(100.0),
+        )
+        .map_err(|e| wrap!(e))?;
+        plots.push(PathBuf::from(output_file));
+    }
+
+    Ok(plots)
+}
+
+/// Create a plot with the previous and current series of a metric on shared axes
+///
+/// # Errors
+///
+/// Returns an error if the plot can't be drawn or written.
+pub fn create_overlay(
+    output_file: &str,
+    format: PlotFormat,
+    title: &str,
+    previous: &[(i64, f64)],
+    current: &[(i64, f64)],
+    delta: Option<&[(i64, f64)]>,
+    y_max: Option<f64>,
+) -> Result<()> {
+    match format {
+        PlotFormat::Png => draw_overlay(
+            &BitMapBackend::new(output_file, (PLOT_WIDTH, PLOT_HEIGHT)).into_drawing_area(),
+            title,
+            previous,
+            current,
+            delta,
+            y_max,
+        ),
+        PlotFormat::Svg => draw_overlay(
+            &SVGBackend::new(output_file, (PLOT_WIDTH, PLOT_HEIGHT)).into_drawing_area(),
+            title,
+            previous,
+            current,
+            delta,
+            y_max,
+        ),
+    }
+}
+
+fn draw_overlay<DB>(
+    root: &DrawingArea<DB, Shift>,
+    title: &str,
+    previous: &[(i64, f64)],
+    current: &[(i64, f64)],
+    delta: Option<&[(i64, f64)]>,
+    y_max: Option<f64>,
+) -> Result<()>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)?;
+
+    let (x_range, y_range) = overlay_ranges(previous, current, delta, y_max);
+
+    let mut chart = ChartBuilder::on(root)
+        .caption(
+            title,
+            ("sans-serif", LABEL_FONT_SIZE).into_font().color(&BLACK),
+        )
+        .margin(10)
+        .x_label_area_size(50)
+        .y_label_area_size(80)
+        .build_cartesian_2d(x_range, y_range)
+        .map_err(|e| wrap!(e.into()))?;
+
+    chart
+        .configure_mesh()
+        .y_labels(10)
+        .y_label_formatter(&|y| format!("{y:.0}"))
+        .x_desc("Time (ms)")
+        .label_style(
+            ("sans-serif", X_AXIS_LABEL_FONT_SIZE)
+                .into_font()
+                .color(&BLACK),
+        )
+        .draw()
+        .map_err(|e| wrap!(e.into()))?;
+
+    chart
+        .draw_series(LineSeries::new(previous.iter().copied(), &BLUE))
+        .map_err(|e| wrap!(e.into()))?
+        .label("Previous")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLUE));
+
+    chart
+        .draw_series(LineSeries::new(current.iter().copied(), &RED))
+        .map_err(|e| wrap!(e.into()))?
+        .label("Current")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
+
+    if let Some(delta) = delta {
+        let style = RGBColor(128, 128, 128);
+        chart
+            .draw_series(DashedLineSeries::new(
+                delta.iter().copied(),
+                6,
+                4,
+                style.into(),
+            ))
+            .map_err(|e| wrap!(e.into()))?
+            .label("Current - Previous")
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], style));
+    }
+
+    chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .label_font(("sans-serif", 14).into_font().color(&BLACK))
+        .draw()
+        .map_err(|e| wrap!(e.into()))?;
+
+    root.present()?;
+    Ok(())
+}
+
+/// The ranges of the axes shared by the series of an overlay plot, so that none of them is cut
+/// and the negative differences stay visible
+fn overlay_ranges(
+    previous: &[(i64, f64)],
+    current: &[(i64, f64)],
+    delta: Option<&[(i64, f64)]>,
+    y_max: Option<f64>,
+) -> (Range<i64>, Range<f64>) {
+    let all_points = || {
+        previous
+            .iter()
+            .chain(current)
+            .chain(delta.unwrap_or_default())
+    };
+
+    let x_max = all_points().map(|(x, _)| *x).max().unwrap_or(0).max(1);
+    let max_value = all_points().map(|(_, y)| *y).fold(0.0_f64, f64::max);
+    let min_value = all_points().map(|(_, y)| *y).fold(0.0_f64, f64::min);
+    let y_max = y_max.unwrap_or(0.0).max(if max_value > 0.0 {
+        max_value * 1.1
+    } else {
+        100.0
+    });
+    let y_min = min_value * 1.1;
+
+    (0..x_max, y_min..y_max)
+}
+
+/// The difference between the current and the previous series, taking for every current point
+/// the previous point closest in time
+#[must_use]
+pub fn difference(previous: &[(i64, f64)], current: &[(i64, f64)]) -> Vec<(i64, f64)> {
+    current
+        .iter()
+        .filter_map(|(x, y)| {
+            previous
+                .iter()
+                .min_by_key(|(previous_x, _)| (previous_x - x).abs())
+                .map(|(_, previous_y)| (*x, y - previous_y))
+        })
+        .collect()
+}
+
+/// Create a line plot of a metric across executions, one point per execution
+///
+/// # Errors
+///
+/// Returns an error if the plot can't be drawn or written.
+pub fn create_trend(
+    output_file: &str,
+    format: PlotFormat,
+    title: &str,
+    points: &[(String, f64)],
+) -> Result<()> {
+    match format {
+        PlotFormat::Png => draw_trend(
+            &BitMapBackend::new(output_file, (PLOT_WIDTH, PLOT_HEIGHT)).into_drawing_area(),
+            title,
+            points,
+        ),
+        PlotFormat::Svg => draw_trend(
+            &SVGBackend::new(output_file, (PLOT_WIDTH, PLOT_HEIGHT)).into_drawing_area(),
+            title,
+            points,
+        ),
+    }
+}
+
+fn draw_trend<DB>(
+    root: &DrawingArea<DB, Shift>,
+    title: &str,
+    points: &[(String, f64)],
+) -> Result<()>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)?;
+
+    let max_value = points
+        .iter()
+        .map(|(_, value)| *value)
+        .fold(0.0_f64, f64::max);
+    let y_max = if max_value > 0.0 {
+        max_value * 1.1
+    } else {
+        100.0
+    };
+
+    // Keep a minimum width on the x axis so a single execution still renders
+    let x_max = points.len().max(2) - 1;
+
+    let mut chart = ChartBuilder::on(root)
+        .caption(
+            title,
+            ("sans-serif", LABEL_FONT_SIZE).into_font().color(&BLACK),
+        )
+        .margin(10)
+        .margin_right(80)
+        .x_label_area_size(50)
+        .y_label_area_size(80)
+        .build_cartesian_2d(0..x_max, 0.0..y_max)
+        .map_err(|e| wrap!(e.into()))?;
+
+    chart
+        .configure_mesh()
+        .x_labels(points.len().max(2))
+        .y_labels(10)
+        .x_label_formatter(&|x| {
+            points
+                .get(*x)
+                .map(|(label, _)| label.clone())
+                .unwrap_or_default()
+        })
+        .label_style(
+            ("sans-serif", X_AXIS_LABEL_FONT_SIZE)
+                .into_font()
+                .color(&BLACK),
+        )
+        .draw()
+        .map_err(|e| wrap!(e.into()))?;
+
+    chart
+        .draw_series(LineSeries::new(
+            points.iter().enumerate().map(|(x, (_, y))| (x, *y)),
+            &RED,
+        ))
+        .map_err(|e| wrap!(e.into()))?;
+
+    chart
+        .draw_series(
+            points
+                .iter()
+                .enumerate()
+                .map(|(x, (_, y))| Circle::new((x, *y), 4, RED.filled())),
+        )
+        .map_err(|e| wrap!(e.into()))?;
+
+    root.present()?;
+    Ok(())
+}
+
 fn calculate_y_max(
     frames: &[BenchmarkFrame],
-    value_extractor: fn(&BenchmarkFrame) -> i64,
+    value_extractor: fn(&BenchmarkFrame) -> Result<i64>,
 ) -> Result<i64> {
-    let max_value = frames.iter().map(value_extractor).max().ok_or(wrap!(
+    let mut max_value = None;
+    for frame in frames {
+        let value = value_extractor(frame).map_err(|e| wrap!(e))?;
+        max_value = max_value.max(Some(value));
+    }
+    let max_value = max_value.ok_or(wrap!(
         "Failed to get max_value while calculating y_max".into()
     ))?;
     let offset = max_value / 10;
@@ -340,14 +744,112 @@ fn calculate_y_max(
 
 #[allow(clippy::cast_possible_truncation)]
 #[allow(clippy::cast_precision_loss)]
-fn safe_f32_to_i64(value: f32) -> i64 {
-    if value.is_finite() {
-        if value >= i64::MIN as f32 && value <= i64::MAX as f32 {
-            value as i64
-        } else {
-            panic!("Value is out of range")
-        }
+fn safe_f32_to_i64(value: f32) -> Result<i64> {
+    if !value.is_finite() {
+        return Err(wrap!(Error::Parse(format!("The value `{value}` is not finite."))).into());
+    }
+
+    if value >= i64::MIN as f32 && value <= i64::MAX as f32 {
+        Ok(value as i64)
     } else {
-        panic!("Value is not finite")
+        Err(wrap!(Error::Parse(format!(
+            "The value `{value}` is out of range."
+        )))
+        .into())
+    }
+}
+
+/// Convert a byte count to the coordinates of the plots, which can't exceed `i64::MAX` in practice
+fn saturating_u64_to_i64(value: u64) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site::fixtures;
+
+    #[test]
+    fn overlay_ranges_cover_every_series() {
+        let previous = [(0, 10.0), (1000, 40.0)];
+        let current = [(0, 20.0), (1500, 30.0)];
+        let delta = difference(&previous, &current);
+
+        let (x_range, y_range) = overlay_ranges(&previous, &current, Some(&delta), None);
+
+        assert_eq!(x_range, 0..1500);
+        // The difference of -10 at 1500ms is kept visible below zero
+        assert!((y_range.start - -11.0).abs() < 1e-9);
+        assert!((y_range.end - 44.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn overlay_ranges_have_a_minimum_size() {
+        let (x_range, y_range) = overlay_ranges(&[], &[], None, None);
+        assert_eq!(x_range, 0..1);
+        assert!((y_range.start - 0.0).abs() < 1e-9);
+        assert!((y_range.end - 100.0).abs() < 1e-9);
+
+        // The CPU usage is always drawn up to 100%
+        let (_, y_range) = overlay_ranges(&[(0, 10.0)], &[(0, 20.0)], None, Some(100.0));
+        assert!((y_range.end - 100.0).abs() < 1e-9);
+        let (_, y_range) = overlay_ranges(&[(0, 10.0)], &[(0, 200.0)], None, Some(100.0));
+        assert!((y_range.end - 220.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn difference_subtracts_the_closest_previous_point() {
+        let previous = [(0, 10.0), (100, 20.0), (200, 30.0)];
+        let current = [(0, 15.0), (90, 15.0), (260, 50.0)];
+
+        assert_eq!(
+            difference(&previous, &current),
+            vec![(0, 5.0), (90, -5.0), (260, 20.0)]
+        );
+        assert!(difference(&[], &current).is_empty());
+        assert!(difference(&previous, &[]).is_empty());
+    }
+
+    #[test]
+    fn svg_plots_are_written_with_their_extension() {
+        let folder = tempfile::tempdir().unwrap();
+        let output_file = folder.path().join("current").display().to_string();
+
+        let plots = generate_plots(
+            &fixtures::benchmarks(vec![fixtures::benchmark("hashing")]),
+            &output_file,
+            PlotFormat::Svg,
+            &PhaseColors::load(None).unwrap(),
+        );
+
+        assert_eq!(plots.len(), 7);
+        assert_eq!(
+            plots[0],
+            PathBuf::from(format!("{output_file}_hashing_cpu_usage.svg"))
+        );
+        for plot in &plots {
+            assert_eq!(plot.extension().unwrap(), "svg");
+            assert!(std::fs::read_to_string(plot).unwrap().contains("<svg"));
+        }
+    }
+
+    #[test]
+    fn benchmarks_without_frames_are_skipped() {
+        let folder = tempfile::tempdir().unwrap();
+        let output_file = folder.path().join("current").display().to_string();
+        let empty = fixtures::benchmark("empty");
+        empty.frames.lock().unwrap().clear();
+
+        let plots = generate_plots(
+            &fixtures::benchmarks(vec![empty, fixtures::benchmark("hashing")]),
+            &output_file,
+            PlotFormat::Svg,
+            &PhaseColors::load(None).unwrap(),
+        );
+
+        assert_eq!(plots.len(), 7);
+        assert!(plots
+            .iter()
+            .all(|plot| plot.to_string_lossy().contains("_hashing_")));
     }
 }
@@ -0,0 +1,290 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::types::{Collection, Stats};
+use crate::{error::Result, wrap};
+use serde::{Deserialize, Serialize};
+
+/// The metrics of `Stats` that can be checked for regressions
+pub const METRICS: [(&str, &str); 11] = [
+    ("cpu_usage", "CPU Usage"),
+    ("memory_usage", "Memory Usage"),
+    ("mean_memory_usage", "Mean Memory Usage"),
+    ("virtual_memory_usage", "Virtual Memory Usage"),
+    ("disk_total_written_bytes", "Disk Total Written Bytes"),
+    ("disk_written_bytes", "Disk Written Bytes"),
+    ("disk_total_read_bytes", "Disk Total Read Bytes"),
+    ("disk_read_bytes", "Disk Read Bytes"),
+    ("bytecode_size", "Bytecode Size"),
+    ("data_section_size", "Data Section Size"),
+    ("time", "Time"),
+];
+
+/// Maximum allowed percentage increase per metric, unset metrics are not checked
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Thresholds {
+    pub cpu_usage: Option<f64>,
+    pub memory_usage: Option<f64>,
+    pub mean_memory_usage: Option<f64>,
+    pub virtual_memory_usage: Option<f64>,
+    pub disk_total_written_bytes: Option<f64>,
+    pub disk_written_bytes: Option<f64>,
+    pub disk_total_read_bytes: Option<f64>,
+    pub disk_read_bytes: Option<f64>,
+    pub bytecode_size: Option<f64>,
+    pub data_section_size: Option<f64>,
+    pub time: Option<f64>,
+}
+
+/// A metric of a benchmark that crossed its threshold
+#[derive(Debug)]
+pub struct Regression {
+    pub benchmark: String,
+    pub metric: &'static str,
+    pub change: f64,
+    pub threshold: f64,
+}
+
+impl Thresholds {
+    /// Load the thresholds from an optional JSON file and apply the `metric=percent` overrides on top
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read or parsed, or if an override isn't
+    /// `metric=percent`.
+    pub fn load(file: Option<&Path>, overrides: &[String]) -> Result<Self> {
+        let mut thresholds = if let Some(file) = file {
+            serde_json::from_str(&std::fs::read_to_string(file).map_err(|e| wrap!(e.into()))?)
+                .map_err(|e| wrap!(e.into()))?
+        } else {
+            Self::default()
+        };
+
+        for entry in overrides {
+            let (metric, value) = entry.split_once('=').ok_or_else(|| {
+                wrap!(format!("Invalid threshold `{entry}`, expected `metric=percent`.").into())
+            })?;
+            let value = value
+                .trim()
+                .trim_end_matches('%')
+                .parse::<f64>()
+                .map_err(|e| wrap!(e.into()))?;
+            *thresholds
+                .get_mut(metric.trim())
+                .ok_or_else(|| wrap!(format!("Unknown threshold metric `{metric}`.").into()))? =
+                Some(value);
+        }
+
+        // Fall back to the metrics that are stable enough to gate on
+        if thresholds.is_empty() {
+            thresholds.time = Some(10.0);
+            thresholds.memory_usage = Some(10.0);
+            thresholds.bytecode_size = Some(5.0);
+            thresholds.data_section_size = Some(5.0);
+        }
+
+        Ok(thresholds)
+    }
+
+    fn get(&self, metric: &str) -> Option<f64> {
+        match metric {
+            "cpu_usage" => self.cpu_usage,
+            "memory_usage" => self.memory_usage,
+            "mean_memory_usage" => self.mean_memory_usage,
+            "virtual_memory_usage" => self.virtual_memory_usage,
+            "disk_total_written_bytes" => self.disk_total_written_bytes,
+            "disk_written_bytes" => self.disk_written_bytes,
+            "disk_total_read_bytes" => self.disk_total_read_bytes,
+            "disk_read_bytes" => self.disk_read_bytes,
+            "bytecode_size" => self.bytecode_size,
+            "data_section_size" => self.data_section_size,
+            "time" => self.time,
+            _ => None,
+        }
+    }
+
+    fn get_mut(&mut self, metric: &str) -> Option<&mut Option<f64>> {
+        match metric {
+            "cpu_usage" => Some(&mut self.cpu_usage),
+            "memory_usage" => Some(&mut self.memory_usage),
+            "mean_memory_usage" => Some(&mut self.mean_memory_usage),
+            "virtual_memory_usage" => Some(&mut self.virtual_memory_usage),
+            "disk_total_written_bytes" => Some(&mut self.disk_total_written_bytes),
+            "disk_written_bytes" => Some(&mut self.disk_written_bytes),
+            "disk_total_read_bytes" => Some(&mut self.disk_total_read_bytes),
+            "disk_read_bytes" => Some(&mut self.disk_read_bytes),
+            "bytecode_size" => Some(&mut self.bytecode_size),
+            "data_section_size" => Some(&mut self.data_section_size),
+            "time" => Some(&mut self.time),
+            _ => None,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        METRICS.iter().all(|(metric, _)| self.get(metric).is_none())
+    }
+}
+
+/// Get the percentage change of a metric from the stats, missing when the data of either run lacks it
+#[must_use]
+pub fn percentage_change(stats: &Stats, metric: &str) -> Option<f64> {
+    if !super::diagnostics::is_available(&stats.diagnostics, metric) {
+        return None;
+    }
+
+    let value = match metric {
+        "cpu_usage" => stats.cpu_usage,
+        "memory_usage" => stats.memory_usage,
+        "mean_memory_usage" => stats.mean_memory_usage,
+        "virtual_memory_usage" => stats.virtual_memory_usage,
+        "disk_total_written_bytes" => stats.disk_total_written_bytes,
+        "disk_written_bytes" => stats.disk_written_bytes,
+        "disk_total_read_bytes" => stats.disk_total_read_bytes,
+        "disk_read_bytes" => stats.disk_read_bytes,
+        "bytecode_size" => stats.bytecode_size,
+        "data_section_size" => stats.data_section_size,
+        "time" => stats.time,
+        _ => None,
+    };
+    value.map(|(_, percentage)| percentage)
+}
+
+/// Check every benchmark of the collection against the thresholds
+#[must_use]
+pub fn check(collection: &Collection, thresholds: &Thresholds) -> Vec<Regression> {
+    let mut regressions = Vec::new();
+
+    for (benchmark, stats) in &collection.0 {
+        for (metric, _) in &METRICS {
+            let (Some(threshold), Some(change)) =
+                (thresholds.get(metric), percentage_change(stats, metric))
+            else {
+                continue;
+            };
+
+            // Time changes that hyperfine can't tell apart from noise are not regressions
+            if *metric == "time"
+                && stats
+                    .hyperfine
+                    .as_ref()
+                    .is_some_and(|significance| !significance.significant)
+            {
+                continue;
+            }
+
+            if change > threshold {
+                regressions.push(Regression {
+                    benchmark: benchmark.clone(),
+                    metric,
+                    change,
+                    threshold,
+                });
+            }
+        }
+    }
+
+    regressions
+}
+
+/// Format the regressions as a human readable summary
+#[must_use]
+pub fn summary(regressions: &[Regression]) -> String {
+    let mut summary = format!("Found {} regression(s):\n", regressions.len());
+
+    let mut current_benchmark = None;
+    for regression in regressions {
+        if current_benchmark != Some(&regression.benchmark) {
+            let _ = writeln!(summary, "  {}", regression.benchmark);
+            current_benchmark = Some(&regression.benchmark);
+        }
+
+        let title = METRICS
+            .iter()
+            .find(|(metric, _)| *metric == regression.metric)
+            .map_or(regression.metric, |(_, title)| title);
+
+        let _ = writeln!(
+            summary,
+            "    {title}: {:+.2}% (threshold {:.2}%)",
+            regression.change, regression.threshold
+        );
+    }
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site::{diagnostics::diagnose, fixtures};
+
+    fn collection(time: f64) -> Collection {
+        Collection(vec![(
+            "/projects/hashing".to_string(),
+            Stats {
+                time: Some((time, time)),
+                ..Stats::default()
+            },
+        )])
+    }
+
+    #[test]
+    fn check_reports_changes_above_the_threshold() {
+        let thresholds = Thresholds::load(None, &["time=10".to_string()]).unwrap();
+
+        let regressions = check(&collection(10.01), &thresholds);
+        assert_eq!(regressions.len(), 1);
+        assert_eq!(regressions[0].metric, "time");
+        assert!((regressions[0].change - 10.01).abs() < 1e-9);
+        assert_eq!(
+            summary(&regressions),
+            "Found 1 regression(s):\n  /projects/hashing\n    Time: +10.01% (threshold 10.00%)\n"
+        );
+    }
+
+    #[test]
+    fn check_allows_changes_up_to_the_threshold() {
+        let thresholds = Thresholds::load(None, &["time=10".to_string()]).unwrap();
+
+        assert!(check(&collection(10.0), &thresholds).is_empty());
+        assert!(check(&collection(9.99), &thresholds).is_empty());
+        assert!(check(&collection(-50.0), &thresholds).is_empty());
+    }
+
+    #[test]
+    fn load_falls_back_to_the_default_thresholds() {
+        let thresholds = Thresholds::load(None, &[]).unwrap();
+
+        assert_eq!(thresholds.time, Some(10.0));
+        assert_eq!(thresholds.bytecode_size, Some(5.0));
+        assert_eq!(thresholds.cpu_usage, None);
+        assert!(Thresholds::load(None, &["time".to_string()]).is_err());
+        assert!(Thresholds::load(None, &["unknown=1".to_string()]).is_err());
+    }
+
+    #[test]
+    fn load_applies_the_overrides_on_top_of_the_file() {
+        let folder = tempfile::tempdir().unwrap();
+        let file = folder.path().join("thresholds.json");
+        std::fs::write(&file, r#"{ "time": 20.0, "cpu_usage": 50.0 }"#).unwrap();
+
+        let thresholds = Thresholds::load(Some(&file), &["time=5%".to_string()]).unwrap();
+
+        assert_eq!(thresholds.time, Some(5.0));
+        assert_eq!(thresholds.cpu_usage, Some(50.0));
+        assert_eq!(thresholds.memory_usage, None);
+    }
+
+    #[test]
+    fn check_skips_the_metrics_affected_by_a_diagnostic() {
+        let thresholds = Thresholds::load(None, &["time=10".to_string()]).unwrap();
+        let mut collection = collection(50.0);
+        let mut current = fixtures::benchmark("hashing");
+        current.end_time = None;
+        collection.0[0].1.diagnostics = diagnose(&fixtures::benchmark("hashing"), &current);
+
+        assert!(check(&collection, &thresholds).is_empty());
+        assert_eq!(percentage_change(&collection.0[0].1, "time"), None);
+    }
+}
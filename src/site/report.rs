@@ -0,0 +1,433 @@
+use std::path::Path;
+
+use crate::site::{
+    diagnostics::{is_available, Diagnostic},
+    execution::{parse_json_benchmarks, Execution},
+    flamegraph::HotFunction,
+    hyperfine::Significance,
+    regression::METRICS,
+    stats::{
+        added_and_removed, change, compare_phases, find_by_key, find_match, PhaseResources, Summary,
+    },
+};
+use crate::types::{Benchmark, Benchmarks, PRInformation, SystemSpecs};
+use crate::{error::Result, wrap};
+use serde::{Deserialize, Serialize};
+
+/// The version of the report schema, bumped on every breaking change
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A machine readable report of an execution
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Report {
+    pub schema_version: u32,
+    /// The version of dynosite that generated the report.
+    pub generator_version: String,
+    /// The identifier of the execution in the site.
+    pub execution: String,
+    pub github_information: PRInformation,
+    /// Whether the previous run is the pinned baseline of the site.
+    pub pinned_baseline: bool,
+    pub previous: RunReport,
+    pub current: RunReport,
+    /// The system specifications of the machine running the current benchmarks.
+    pub system_specs: SystemSpecs,
+    pub benchmarks: Vec<BenchmarkReport>,
+    /// The benchmarks that only ran in the current run
+    #[serde(default)]
+    pub new_benchmarks: Vec<UnmatchedBenchmarkReport>,
+    /// The benchmarks that only ran in the previous run
+    #[serde(default)]
+    pub removed_benchmarks: Vec<UnmatchedBenchmarkReport>,
+}
+
+/// The information of one of the two compared runs
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RunReport {
+    pub file_name: String,
+    pub forc_version: String,
+    pub compiler_hash: String,
+    pub benchmarks_datetime: String,
+    pub benchmark_count: usize,
+    pub total_time_ms: f64,
+}
+
+/// The comparison of a single benchmark between the two runs
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BenchmarkReport {
+    pub name: String,
+    pub path: String,
+    pub metrics: Vec<MetricReport>,
+    pub phases: Vec<PhaseReport>,
+    pub hyperfine: Option<Significance>,
+    /// The hot functions from the flamegraphs of both runs, empty without flamegraphs
+    #[serde(default)]
+    pub hot_functions: Vec<HotFunction>,
+    /// The problems with the data of the benchmark, the metrics they affect are left out
+    #[serde(default)]
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// A benchmark that only ran in one of the two runs, with its metrics in that run
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UnmatchedBenchmarkReport {
+    pub name: String,
+    pub path: String,
+    pub summary: Summary,
+}
+
+impl UnmatchedBenchmarkReport {
+    fn new(benchmark: &Benchmark) -> Self {
+        Self {
+            name: benchmark.name.clone(),
+            path: benchmark.path.display().to_string(),
+            summary: Summary::new(benchmark),
+        }
+    }
+}
+
+/// The absolute values and the change of a metric
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MetricReport {
+    pub metric: String,
+    pub previous: f64,
+    pub current: f64,
+    pub difference: f64,
+    pub percentage: f64,
+}
+
+/// The duration of a compiler phase in milliseconds, missing when the phase did not run or has no timings
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PhaseReport {
+    pub name: String,
+    pub previous_ms: Option<f64>,
+    pub current_ms: Option<f64>,
+    #[serde(default)]
+    pub difference_ms: Option<f64>,
+    #[serde(default)]
+    pub percentage: Option<f64>,
+    /// The resource usage during the phase, missing when no frames were captured during it
+    #[serde(default)]
+    pub previous_resources: Option<PhaseResources>,
+    #[serde(default)]
+    pub current_resources: Option<PhaseResources>,
+}
+
+impl Report {
+    /// Build the report of an execution from its runs and stats
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the runs or the stats of the execution can't be read.
+    pub fn new(execution: &Execution) -> Result<Self> {
+        let previous_benchmarks =
+            parse_json_benchmarks(&execution.previous_benchmarks).map_err(|e| wrap!(e))?;
+        let current_benchmarks =
+            parse_json_benchmarks(&execution.current_benchmarks).map_err(|e| wrap!(e))?;
+        let stats_collection = execution.stats_collection().map_err(|e| wrap!(e))?;
+        let hot_functions = execution.hot_functions().map_err(|e| wrap!(e))?;
+
+        let (added, removed) = added_and_removed(&previous_benchmarks, &current_benchmarks);
+
+        let mut benchmarks = Vec::new();
+        for (path, stats) in stats_collection.0 {
+            let Some(current) = find_by_key(&current_benchmarks, &path) else {
+                continue;
+            };
+            let Some(previous) = find_match(&previous_benchmarks, current) else {
+                continue;
+            };
+            let name = current.name.clone();
+
+            benchmarks.push(BenchmarkReport {
+                metrics: metrics(previous, current, &stats.diagnostics),
+                phases: phases(previous, current),
+                hyperfine: stats.hyperfine,
+                diagnostics: stats.diagnostics,
+                hot_functions: hot_functions.get(&name).cloned().unwrap_or_default(),
+                name,
+                path,
+            });
+        }
+
+        Ok(Self {
+            schema_version: SCHEMA_VERSION,
+            generator_version: env!("CARGO_PKG_VERSION").to_string(),
+            execution: execution
+                .root_folder
+                .file_name()
+                .ok_or_else(|| wrap!("Failed to get the execution identifier.".into()))?
+                .to_string_lossy()
+                .to_string(),
+            github_information: execution.github_information.clone(),
+            pinned_baseline: execution.pinned_baseline,
+            previous: RunReport::new(&previous_benchmarks, &execution.previous_benchmarks)
+                .map_err(|e| wrap!(e))?,
+            current: RunReport::new(&current_benchmarks, &execution.current_benchmarks)
+                .map_err(|e| wrap!(e))?,
+            system_specs: current_benchmarks.system_specs.clone(),
+            benchmarks,
+            new_benchmarks: added
+                .into_iter()
+                .map(UnmatchedBenchmarkReport::new)
+                .collect(),
+            removed_benchmarks: removed
+                .into_iter()
+                .map(UnmatchedBenchmarkReport::new)
+                .collect(),
+        })
+    }
+}
+
+impl RunReport {
+    fn new(benchmarks: &Benchmarks, file_path: &Path) -> Result<Self> {
+        Ok(Self {
+            file_name: file_path
+                .file_name()
+                .ok_or_else(|| wrap!("Failed to get the file name from the file path.".into()))?
+                .to_string_lossy()
+                .to_string(),
+            forc_version: benchmarks.forc_version.clone(),
+            compiler_hash: benchmarks.compiler_hash.clone(),
+            benchmarks_datetime: benchmarks.benchmarks_datetime.clone(),
+            benchmark_count: benchmarks.benchmarks.len(),
+            total_time_ms: benchmarks.total_time.as_secs_f64() * 1000.0,
+        })
+    }
+}
+
+/// Compare the metrics of a benchmark in both runs, leaving out the ones affected by diagnostics
+#[must_use]
+pub fn metrics(
+    previous: &Benchmark,
+    current: &Benchmark,
+    diagnostics: &[Diagnostic],
+) -> Vec<MetricReport> {
+    let (previous_summary, current_summary) = Summary::pair(previous, current);
+
+    let mut metrics: Vec<MetricReport> = METRICS
+        .iter()
+        .filter(|(metric, _)| is_available(diagnostics, metric))
+        .filter_map(|(metric, _)| {
+            Some(MetricReport::new(
+                metric,
+                previous_summary.value(metric)?,
+                current_summary.value(metric)?,
+            ))
+        })
+        .collect();
+
+    // The used part of the data section is shown next to its size
+    let data_section_used = |benchmark: &Benchmark| {
+        benchmark
+            .asm_information
+            .as_ref()
+            .and_then(|asm| asm["data_section"]["used"].as_f64())
+    };
+    if let (Some(previous), Some(current)) =
+        (data_section_used(previous), data_section_used(current))
+    {
+        metrics.push(MetricReport::new("data_section_used", previous, current));
+    }
+
+    metrics
+}
+
+fn phases(previous: &Benchmark, current: &Benchmark) -> Vec<PhaseReport> {
+    compare_phases(previous, current)
+        .into_iter()
+        .map(|phase| {
+            let change = phase.change();
+            PhaseReport {
+                name: phase.name,
+                previous_ms: phase.previous,
+                current_ms: phase.current,
+                difference_ms: change.map(|(difference, _)| difference),
+                percentage: change.map(|(_, percentage)| percentage),
+                previous_resources: phase.previous_resources,
+                current_resources: phase.current_resources,
+            }
+        })
+        .collect()
+}
+
+impl MetricReport {
+    fn new(metric: &str, previous: f64, current: f64) -> Self {
+        let (difference, percentage) = change(previous, current);
+        Self {
+            metric: metric.to_string(),
+            previous,
+            current,
+            difference,
+            percentage,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site::fixtures;
+    use serde_json::json;
+    use std::time::Duration;
+
+    #[test]
+    fn report_serialises_the_runs_and_the_changes_of_the_benchmarks() {
+        let folder = tempfile::tempdir().unwrap();
+        let previous = fixtures::benchmarks(vec![fixtures::benchmark("hashing")]);
+        let mut hashing = fixtures::benchmark("hashing");
+        hashing.end_time = Some(Duration::from_millis(1100));
+        hashing.phases[0].end_time = Some(Duration::from_millis(1100));
+        let current = fixtures::benchmarks(vec![hashing]);
+        let execution = fixtures::execution(
+            folder.path(),
+            "benchmarks_2024-10-22_10:00:00",
+            &previous,
+            &current,
+            PRInformation::default(),
+        );
+
+        let report = serde_json::to_value(Report::new(&execution).unwrap()).unwrap();
+
+        assert_eq!(report["schema_version"], json!(SCHEMA_VERSION));
+        assert_eq!(report["execution"], "benchmarks_2024-10-22_10:00:00");
+        assert_eq!(report["pinned_baseline"], false);
+        assert_eq!(
+            report["previous"],
+            json!({
+                "file_name": "previous.json",
+                "forc_version": "0.66.0",
+                "compiler_hash": "abc",
+                "benchmarks_datetime": "2024-10-22 10:00:00",
+                "benchmark_count": 1,
+                "total_time_ms": 1000.0
+            })
+        );
+        assert_eq!(
+            report["current"]["file_name"],
+            "benchmarks_2024-10-22_10:00:00.json"
+        );
+
+        let benchmarks = report["benchmarks"].as_array().unwrap();
+        assert_eq!(benchmarks.len(), 1);
+        assert_eq!(benchmarks[0]["name"], "hashing");
+        assert_eq!(benchmarks[0]["path"], "/projects/hashing");
+        assert_eq!(benchmarks[0]["hyperfine"], json!(null));
+
+        // The time regressed by 10%, the other metrics are unchanged
+        let metric = |name: &str| {
+            benchmarks[0]["metrics"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|metric| metric["metric"] == name)
+                .cloned()
+                .unwrap()
+        };
+        assert_eq!(
+            metric("time"),
+            json!({
+                "metric": "time",
+                "previous": 1000.0,
+                "current": 1100.0,
+                "difference": 100.0,
+                "percentage": 10.0
+            })
+        );
+        assert_eq!(metric("bytecode_size")["percentage"], 0.0);
+        // Both frames of the runs were captured during the phase
+        let resources = json!({
+            "frames": 2,
+            "peak_cpu_usage": 50.0,
+            "mean_cpu_usage": 50.0,
+            "peak_memory_usage": 1000.0,
+            "disk_read_bytes": 0.0,
+            "disk_written_bytes": 0.0
+        });
+        assert_eq!(
+            benchmarks[0]["phases"],
+            json!([{
+                "name": "compile to ast",
+                "previous_ms": 1000.0,
+                "current_ms": 1100.0,
+                "difference_ms": 100.0,
+                "percentage": 10.0,
+                "previous_resources": resources,
+                "current_resources": resources
+            }])
+        );
+    }
+
+    #[test]
+    fn report_leaves_out_the_metrics_of_incomplete_data() {
+        let folder = tempfile::tempdir().unwrap();
+        let previous = fixtures::benchmarks(vec![fixtures::benchmark("hashing")]);
+        let mut hashing = fixtures::benchmark("hashing");
+        hashing.asm_information = None;
+        let current = fixtures::benchmarks(vec![hashing]);
+        let execution = fixtures::execution(
+            folder.path(),
+            "benchmarks_2024-10-22_10:00:00",
+            &previous,
+            &current,
+            PRInformation::default(),
+        );
+
+        let report = serde_json::to_value(Report::new(&execution).unwrap()).unwrap();
+
+        let benchmark = &report["benchmarks"][0];
+        assert_eq!(
+            benchmark["diagnostics"],
+            json!([{
+                "run": "current",
+                "metrics": ["bytecode_size", "data_section_size"],
+                "message": "The benchmark `hashing` has no asm information."
+            }])
+        );
+        let metrics: Vec<_> = benchmark["metrics"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|metric| metric["metric"].as_str().unwrap())
+            .collect();
+        assert!(metrics.contains(&"time"));
+        assert!(!metrics.contains(&"bytecode_size"));
+        assert!(!metrics.contains(&"data_section_size"));
+    }
+
+    #[test]
+    fn report_lists_the_new_and_removed_benchmarks() {
+        let folder = tempfile::tempdir().unwrap();
+        let previous = fixtures::benchmarks(vec![
+            fixtures::benchmark("hashing"),
+            fixtures::benchmark("sorting"),
+        ]);
+        let current = fixtures::benchmarks(vec![
+            fixtures::benchmark("hashing"),
+            fixtures::benchmark("parsing"),
+        ]);
+        let execution = fixtures::execution(
+            folder.path(),
+            "benchmarks_2024-10-22_10:00:00",
+            &previous,
+            &current,
+            PRInformation::default(),
+        );
+
+        let report = serde_json::to_value(Report::new(&execution).unwrap()).unwrap();
+
+        let benchmarks = report["benchmarks"].as_array().unwrap();
+        assert_eq!(benchmarks.len(), 1);
+        assert_eq!(benchmarks[0]["name"], "hashing");
+        for (key, name) in [
+            ("new_benchmarks", "parsing"),
+            ("removed_benchmarks", "sorting"),
+        ] {
+            let unmatched = report[key].as_array().unwrap();
+            assert_eq!(unmatched.len(), 1, "{key}");
+            assert_eq!(unmatched[0]["name"], name);
+            assert_eq!(unmatched[0]["path"], format!("/projects/{name}"));
+            assert_eq!(unmatched[0]["summary"]["bytecode_size"], 100.0);
+        }
+    }
+}
@@ -0,0 +1,762 @@
+use super::{diagnostics, hyperfine};
+use crate::types::{Benchmark, BenchmarkFrame, Benchmarks, Collection, Stats};
+use serde::{Deserialize, Serialize};
+
+/// Aggregated metrics of a single benchmark run, missing when the data of the run lacks them
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Summary {
+    /// The mean CPU usage across all frames.
+    pub cpu_usage: Option<f64>,
+    /// The peak memory usage in bytes.
+    pub memory_usage: Option<f64>,
+    /// The mean memory usage in bytes.
+    pub mean_memory_usage: Option<f64>,
+    /// The peak virtual memory usage in bytes.
+    pub virtual_memory_usage: Option<f64>,
+    /// The total number of bytes written to disk.
+    pub disk_total_written_bytes: Option<f64>,
+    /// The sum of the bytes written to disk between frames.
+    pub disk_written_bytes: Option<f64>,
+    /// The total number of bytes read from disk.
+    pub disk_total_read_bytes: Option<f64>,
+    /// The sum of the bytes read from disk between frames.
+    pub disk_read_bytes: Option<f64>,
+    /// The bytecode size in bytes.
+    pub bytecode_size: Option<f64>,
+    /// The data section size in bytes.
+    pub data_section_size: Option<f64>,
+    /// The duration of the benchmark in milliseconds.
+    pub time: Option<f64>,
+}
+
+impl Summary {
+    /// Aggregate the frames, timings and bytecode information of a benchmark
+    #[allow(clippy::cast_precision_loss)]
+    #[must_use]
+    pub fn new(benchmark: &Benchmark) -> Self {
+        let frames = benchmark
+            .frames
+            .lock()
+            .map(|frames| frames.clone())
+            .unwrap_or_default();
+
+        // A run without frames has none of the frame metrics, rather than zeros
+        let peak = |value: fn(&BenchmarkFrame) -> u64| frames.iter().map(value).max();
+        let sum = |value: fn(&BenchmarkFrame) -> f64| {
+            (!frames.is_empty()).then(|| frames.iter().map(value).sum::<f64>())
+        };
+        let mean = |value| sum(value).map(|sum| sum / frames.len() as f64);
+
+        let time = match (benchmark.start_time, benchmark.end_time) {
+            (Some(start_time), Some(end_time)) => {
+                Some(end_time.saturating_sub(start_time).as_secs_f64() * 1000.0)
+            }
+            _ => None,
+        };
+
+        let asm_information = benchmark.asm_information.as_ref();
+
+        Self {
+            cpu_usage: mean(|f| f64::from(f.cpu_usage)),
+            memory_usage: peak(|f| f.memory_usage).map(|value| value as f64),
+            mean_memory_usage: mean(|f| f.memory_usage as f64),
+            virtual_memory_usage: peak(|f| f.virtual_memory_usage).map(|value| value as f64),
+            disk_total_written_bytes: peak(|f| f.disk_total_written_bytes)
+                .map(|value| value as f64),
+            disk_written_bytes: sum(|f| f.disk_written_bytes as f64),
+            disk_total_read_bytes: peak(|f| f.disk_total_read_bytes).map(|value| value as f64),
+            disk_read_bytes: sum(|f| f.disk_read_bytes as f64),
+            bytecode_size: asm_information.and_then(|asm| asm["bytecode_size"].as_f64()),
+            data_section_size: asm_information.and_then(|asm| asm["data_section"]["size"].as_f64()),
+            time,
+        }
+    }
+
+    /// Aggregate a benchmark in the previous and the current run for their comparison, when both
+    /// runs have hyperfine results the time is the mean of their run times instead of the single
+    /// compilation timed by dyno
+    #[must_use]
+    pub fn pair(previous: &Benchmark, current: &Benchmark) -> (Self, Self) {
+        let mut summaries = (Self::new(previous), Self::new(current));
+
+        if let Some(significance) = hyperfine::compare(previous, current) {
+            summaries.0.time = Some(significance.previous.mean * 1000.0);
+            summaries.1.time = Some(significance.current.mean * 1000.0);
+        }
+
+        summaries
+    }
+}
+
+impl Summary {
+    /// Get the value of a metric by its name in `regression::METRICS`
+    #[must_use]
+    pub fn value(&self, metric: &str) -> Option<f64> {
+        match metric {
+            "cpu_usage" => self.cpu_usage,
+            "memory_usage" => self.memory_usage,
+            "mean_memory_usage" => self.mean_memory_usage,
+            "virtual_memory_usage" => self.virtual_memory_usage,
+            "disk_total_written_bytes" => self.disk_total_written_bytes,
+            "disk_written_bytes" => self.disk_written_bytes,
+            "disk_total_read_bytes" => self.disk_total_read_bytes,
+            "disk_read_bytes" => self.disk_read_bytes,
+            "bytecode_size" => self.bytecode_size,
+            "data_section_size" => self.data_section_size,
+            "time" => self.time,
+            _ => None,
+        }
+    }
+}
+
+/// Get the duration of every phase of a benchmark in milliseconds, in the order the phases ran
+#[must_use]
+pub fn phase_durations(benchmark: &Benchmark) -> Vec<(String, Option<f64>)> {
+    benchmark
+        .phases
+        .iter()
+        .map(|phase| {
+            let duration = match (phase.start_time, phase.end_time) {
+                (Some(start_time), Some(end_time)) => {
+                    Some(end_time.saturating_sub(start_time).as_secs_f64() * 1000.0)
+                }
+                _ => None,
+            };
+            (phase.name.clone(), duration)
+        })
+        .collect()
+}
+
+/// Get the window of every phase of a benchmark in milliseconds since the start of the first phase,
+/// skipping the phases without timings
+#[must_use]
+pub fn phase_windows(benchmark: &Benchmark) -> Vec<(String, f64, f64)> {
+    let Some(first_start_time) = benchmark
+        .phases
+        .iter()
+        .filter_map(|phase| phase.start_time)
+        .min()
+    else {
+        return vec![];
+    };
+
+    benchmark
+        .phases
+        .iter()
+        .filter_map(|phase| {
+            let (start_time, end_time) = (phase.start_time?, phase.end_time?);
+            Some((
+                phase.name.clone(),
+                start_time.saturating_sub(first_start_time).as_secs_f64() * 1000.0,
+                end_time.saturating_sub(first_start_time).as_secs_f64() * 1000.0,
+            ))
+        })
+        .collect()
+}
+
+/// The resource usage of the frames captured during a phase
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct PhaseResources {
+    /// The number of frames captured during the phase.
+    pub frames: usize,
+    /// The peak CPU usage.
+    pub peak_cpu_usage: f64,
+    /// The mean CPU usage.
+    pub mean_cpu_usage: f64,
+    /// The peak memory usage in bytes.
+    pub peak_memory_usage: f64,
+    /// The sum of the bytes read from disk between frames.
+    pub disk_read_bytes: f64,
+    /// The sum of the bytes written to disk between frames.
+    pub disk_written_bytes: f64,
+}
+
+impl PhaseResources {
+    /// Get the value of a metric by its name in `PHASE_RESOURCE_METRICS`
+    #[must_use]
+    pub fn value(&self, metric: &str) -> Option<f64> {
+        match metric {
+            "peak_cpu_usage" => Some(self.peak_cpu_usage),
+            "mean_cpu_usage" => Some(self.mean_cpu_usage),
+            "peak_memory_usage" => Some(self.peak_memory_usage),
+            "disk_read_bytes" => Some(self.disk_read_bytes),
+            "disk_written_bytes" => Some(self.disk_written_bytes),
+            _ => None,
+        }
+    }
+}
+
+/// The metrics of `PhaseResources`, with their titles
+pub const PHASE_RESOURCE_METRICS: [(&str, &str); 5] = [
+    ("peak_cpu_usage", "Peak CPU Usage"),
+    ("mean_cpu_usage", "Mean CPU Usage"),
+    ("peak_memory_usage", "Peak Memory Usage (bytes)"),
+    ("disk_read_bytes", "Disk Read Bytes"),
+    ("disk_written_bytes", "Disk Written Bytes"),
+];
+
+/// Aggregate the frames captured during every phase of a benchmark, in the order the phases ran.
+/// Phases without timings or without frames have no resources.
+#[allow(clippy::cast_precision_loss)]
+#[must_use]
+pub fn phase_resources(benchmark: &Benchmark) -> Vec<(String, Option<PhaseResources>)> {
+    let frames = benchmark
+        .frames
+        .lock()
+        .map(|frames| frames.clone())
+        .unwrap_or_default();
+
+    // The relative timestamps of the frames start with the benchmark, and a frame captured at the
+    // boundary of two phases belongs to the phase that starts there
+    let Some(benchmark_start_time) = benchmark.start_time.or_else(|| {
+        benchmark
+            .phases
+            .iter()
+            .filter_map(|phase| phase.start_time)
+            .min()
+    }) else {
+        return benchmark
+            .phases
+            .iter()
+            .map(|phase| (phase.name.clone(), None))
+            .collect();
+    };
+
+    benchmark
+        .phases
+        .iter()
+        .map(|phase| {
+            let resources =
+                phase
+                    .start_time
+                    .zip(phase.end_time)
+                    .and_then(|(start_time, end_time)| {
+                        let start_time = start_time.saturating_sub(benchmark_start_time);
+                        let end_time = end_time.saturating_sub(benchmark_start_time);
+
+                        let frames: Vec<_> = frames
+                            .iter()
+                            .filter(|frame| {
+                                frame.relative_timestamp >= start_time
+                                    && frame.relative_timestamp < end_time
+                            })
+                            .collect();
+
+                        if frames.is_empty() {
+                            return None;
+                        }
+
+                        Some(PhaseResources {
+                            frames: frames.len(),
+                            peak_cpu_usage: frames
+                                .iter()
+                                .map(|f| f64::from(f.cpu_usage))
+                                .fold(0.0, f64::max),
+                            mean_cpu_usage: frames
+                                .iter()
+                                .map(|f| f64::from(f.cpu_usage))
+                                .sum::<f64>()
+                                / frames.len() as f64,
+                            peak_memory_usage: frames
+                                .iter()
+                                .map(|f| f.memory_usage)
+                                .max()
+                                .unwrap_or(0) as f64,
+                            disk_read_bytes: frames.iter().map(|f| f.disk_read_bytes).sum::<u64>()
+                                as f64,
+                            disk_written_bytes: frames
+                                .iter()
+                                .map(|f| f.disk_written_bytes)
+                                .sum::<u64>()
+                                as f64,
+                        })
+                    });
+            (phase.name.clone(), resources)
+        })
+        .collect()
+}
+
+/// The duration and resource usage of a phase in both runs, with durations in milliseconds
+#[derive(Debug, Clone)]
+pub struct PhaseChange {
+    pub name: String,
+    pub previous: Option<f64>,
+    pub current: Option<f64>,
+    pub previous_resources: Option<PhaseResources>,
+    pub current_resources: Option<PhaseResources>,
+}
+
+impl PhaseChange {
+    /// The absolute difference and the percentage change, when the phase has timings in both runs
+    #[must_use]
+    pub fn change(&self) -> Option<(f64, f64)> {
+        Some(change(self.previous?, self.current?))
+    }
+}
+
+/// Pair the phases of both runs by name, in the order of the current run followed by the phases
+/// that only ran in the previous run
+#[must_use]
+pub fn compare_phases(previous: &Benchmark, current: &Benchmark) -> Vec<PhaseChange> {
+    let previous_phases = phase_durations(previous);
+    let current_phases = phase_durations(current);
+    let previous_resources = phase_resources(previous);
+    let current_resources = phase_resources(current);
+
+    let find = |phases: &[(String, Option<PhaseResources>)], name: &str| {
+        phases
+            .iter()
+            .find(|(phase, _)| phase == name)
+            .and_then(|(_, resources)| resources.clone())
+    };
+
+    let mut phases: Vec<PhaseChange> = current_phases
+        .iter()
+        .map(|(name, current)| PhaseChange {
+            name: name.clone(),
+            previous: previous_phases
+                .iter()
+                .find(|(previous_name, _)| previous_name == name)
+                .and_then(|(_, previous)| *previous),
+            current: *current,
+            previous_resources: find(&previous_resources, name),
+            current_resources: find(&current_resources, name),
+        })
+        .collect();
+
+    for (name, previous) in &previous_phases {
+        if !phases.iter().any(|phase| &phase.name == name) {
+            phases.push(PhaseChange {
+                name: name.clone(),
+                previous: *previous,
+                current: None,
+                previous_resources: find(&previous_resources, name),
+                current_resources: None,
+            });
+        }
+    }
+
+    phases
+}
+
+/// Compare two values, returning the absolute difference and the percentage change
+#[must_use]
+pub fn change(previous: f64, current: f64) -> (f64, f64) {
+    let difference = current - previous;
+    let percentage = if previous == 0.0 {
+        if current == 0.0 {
+            0.0
+        } else {
+            100.0
+        }
+    } else {
+        difference / previous * 100.0
+    };
+    (difference, percentage)
+}
+
+impl Stats {
+    /// Compare the summaries of a benchmark between the previous and the current run, a metric
+    /// missing from either summary is not compared
+    #[must_use]
+    pub fn new(previous: &Summary, current: &Summary) -> Self {
+        let compare = |metric| Some(change(previous.value(metric)?, current.value(metric)?));
+
+        Self {
+            cpu_usage: compare("cpu_usage"),
+            memory_usage: compare("memory_usage"),
+            mean_memory_usage: compare("mean_memory_usage"),
+            virtual_memory_usage: compare("virtual_memory_usage"),
+            disk_total_written_bytes: compare("disk_total_written_bytes"),
+            disk_written_bytes: compare("disk_written_bytes"),
+            disk_total_read_bytes: compare("disk_total_read_bytes"),
+            disk_read_bytes: compare("disk_read_bytes"),
+            bytecode_size: compare("bytecode_size"),
+            data_section_size: compare("data_section_size"),
+            time: compare("time"),
+            hyperfine: None,
+            diagnostics: Vec::new(),
+        }
+    }
+}
+
+/// Compute the stats collection of every benchmark present in both runs
+#[must_use]
+pub fn compare(previous: &Benchmarks, current: &Benchmarks) -> Collection {
+    Collection(
+        current
+            .benchmarks
+            .iter()
+            .filter_map(|current_benchmark| {
+                let previous_benchmark = find_match(previous, current_benchmark)?;
+
+                let (previous_summary, current_summary) =
+                    Summary::pair(previous_benchmark, current_benchmark);
+                let mut stats = Stats::new(&previous_summary, &current_summary);
+                stats.hyperfine = hyperfine::compare(previous_benchmark, current_benchmark);
+                stats.diagnostics = diagnostics::diagnose(previous_benchmark, current_benchmark);
+
+                Some((current_benchmark.path.display().to_string(), stats))
+            })
+            .collect(),
+    )
+}
+
+/// Find the benchmark of a run that matches a benchmark of the other run, by name or else by path
+#[must_use]
+pub fn find_match<'a>(benchmarks: &'a Benchmarks, benchmark: &Benchmark) -> Option<&'a Benchmark> {
+    benchmarks
+        .benchmarks
+        .iter()
+        .find(|b| b.name == benchmark.name)
+        .or_else(|| {
+            benchmarks
+                .benchmarks
+                .iter()
+                .find(|b| b.path == benchmark.path)
+        })
+}
+
+/// Find the benchmark of a run by its key in a stats `Collection`, the path of the benchmark
+#[must_use]
+pub fn find_by_key<'a>(benchmarks: &'a Benchmarks, key: &str) -> Option<&'a Benchmark> {
+    benchmarks
+        .benchmarks
+        .iter()
+        .find(|b| b.path.display().to_string() == key)
+}
+
+/// Get the benchmarks that only ran in the current run and the ones that only ran in the
+/// previous run
+#[must_use]
+pub fn added_and_removed<'a>(
+    previous: &'a Benchmarks,
+    current: &'a Benchmarks,
+) -> (Vec<&'a Benchmark>, Vec<&'a Benchmark>) {
+    let added = current
+        .benchmarks
+        .iter()
+        .filter(|benchmark| find_match(previous, benchmark).is_none())
+        .collect();
+    let removed = previous
+        .benchmarks
+        .iter()
+        .filter(|benchmark| find_match(current, benchmark).is_none())
+        .collect();
+
+    (added, removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        site::fixtures,
+        types::{BenchmarkFrame, BenchmarkPhase},
+    };
+    use std::{path::PathBuf, time::Duration};
+
+    #[test]
+    fn change_from_a_zero_baseline() {
+        assert_eq!(change(0.0, 0.0), (0.0, 0.0));
+        assert_eq!(change(0.0, 5.0), (5.0, 100.0));
+    }
+
+    #[test]
+    fn change_between_equal_values() {
+        assert_eq!(change(42.0, 42.0), (0.0, 0.0));
+    }
+
+    #[test]
+    fn change_between_different_values() {
+        assert_eq!(change(200.0, 250.0), (50.0, 25.0));
+        assert_eq!(change(200.0, 150.0), (-50.0, -25.0));
+    }
+
+    #[test]
+    fn summary_aggregates_the_frames_timings_and_asm_information() {
+        let hashing = fixtures::benchmark("hashing");
+        hashing.frames.lock().unwrap()[1].memory_usage = 3000;
+
+        let summary = Summary::new(&hashing);
+
+        assert!((summary.cpu_usage.unwrap() - 50.0).abs() < 1e-9);
+        assert!((summary.memory_usage.unwrap() - 3000.0).abs() < 1e-9);
+        assert!((summary.mean_memory_usage.unwrap() - 2000.0).abs() < 1e-9);
+        assert!((summary.time.unwrap() - 1000.0).abs() < 1e-9);
+        assert!((summary.bytecode_size.unwrap() - 100.0).abs() < 1e-9);
+        assert!((summary.data_section_size.unwrap() - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn compare_computes_the_stats_of_the_matched_benchmarks() {
+        let previous = fixtures::benchmarks(vec![fixtures::benchmark("hashing")]);
+
+        let mut hashing = fixtures::benchmark("hashing");
+        hashing.end_time = Some(Duration::from_millis(1100));
+        hashing.asm_information = Some(serde_json::json!({
+            "bytecode_size": 110,
+            "data_section": { "size": 10 }
+        }));
+        let current = fixtures::benchmarks(vec![hashing, fixtures::benchmark("storage")]);
+
+        let collection = compare(&previous, &current);
+
+        assert_eq!(collection.0.len(), 1);
+        let (key, stats) = &collection.0[0];
+        assert_eq!(key, "/projects/hashing");
+        assert!((stats.time.unwrap().0 - 100.0).abs() < 1e-9);
+        assert!((stats.time.unwrap().1 - 10.0).abs() < 1e-9);
+        assert_eq!(stats.bytecode_size, Some((10.0, 10.0)));
+        assert_eq!(stats.data_section_size, Some((0.0, 0.0)));
+        assert_eq!(stats.memory_usage, Some((0.0, 0.0)));
+        assert!(stats.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn compare_takes_the_time_from_the_hyperfine_means() {
+        let benchmark = |times: &[f64]| {
+            let mut benchmark = fixtures::benchmark("hashing");
+            benchmark.hyperfine = Some(serde_json::json!({ "results": [{ "times": times }] }));
+            benchmark
+        };
+        let previous = fixtures::benchmarks(vec![benchmark(&[1.9, 2.0, 2.1])]);
+        let current = fixtures::benchmarks(vec![benchmark(&[2.1, 2.2, 2.3])]);
+
+        let collection = compare(&previous, &current);
+
+        // dyno timed both compilations at one second, hyperfine at 2.0s and 2.2s on average
+        let (difference, percentage) = collection.0[0].1.time.unwrap();
+        assert!((difference - 200.0).abs() < 1e-9);
+        assert!((percentage - 10.0).abs() < 1e-9);
+
+        // Without hyperfine results in both runs, the times of dyno are compared
+        let (previous_summary, current_summary) =
+            Summary::pair(&previous.benchmarks[0], &fixtures::benchmark("hashing"));
+        assert_eq!(
+            (previous_summary.time, current_summary.time),
+            (Some(1000.0), Some(1000.0))
+        );
+    }
+
+    #[test]
+    fn missing_values_are_not_compared() {
+        let previous = fixtures::benchmarks(vec![fixtures::benchmark("hashing")]);
+
+        let mut hashing = fixtures::benchmark("hashing");
+        hashing.end_time = None;
+        hashing.frames = std::sync::Arc::default();
+        hashing.asm_information = Some(serde_json::json!({ "bytecode_size": 100 }));
+        let current = fixtures::benchmarks(vec![hashing]);
+
+        let summary = Summary::new(&current.benchmarks[0]);
+        assert_eq!(summary.time, None);
+        assert_eq!(summary.memory_usage, None);
+        assert_eq!(summary.data_section_size, None);
+        assert_eq!(summary.bytecode_size, Some(100.0));
+
+        let collection = compare(&previous, &current);
+        let (_, stats) = &collection.0[0];
+        assert_eq!(stats.time, None);
+        assert_eq!(stats.cpu_usage, None);
+        assert_eq!(stats.data_section_size, None);
+        assert_eq!(stats.bytecode_size, Some((0.0, 0.0)));
+
+        let messages: Vec<_> = stats.diagnostics.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            messages,
+            [
+                "current run: The asm information of the benchmark `hashing` has no `data_section_size`.",
+                "current run: The frames of the benchmark `hashing` are missing.",
+                "current run: The benchmark `hashing` has no start or end time.",
+            ]
+        );
+    }
+
+    /// A phase from `start` to `end` milliseconds, without a timing when either is `None`
+    fn phase(name: &str, start: Option<u64>, end: Option<u64>) -> BenchmarkPhase {
+        BenchmarkPhase {
+            name: name.to_string(),
+            start_time: start.map(Duration::from_millis),
+            end_time: end.map(Duration::from_millis),
+        }
+    }
+
+    #[test]
+    fn phase_durations_skip_the_phases_without_timings() {
+        let mut benchmark = fixtures::benchmark("hashing");
+        benchmark.phases = vec![
+            phase("parse", Some(100), Some(400)),
+            phase("unfinished", Some(400), None),
+            phase("unstarted", None, Some(500)),
+        ];
+
+        assert_eq!(
+            phase_durations(&benchmark),
+            vec![
+                ("parse".to_string(), Some(300.0)),
+                ("unfinished".to_string(), None),
+                ("unstarted".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn compare_phases_pairs_the_phases_by_name() {
+        let mut previous = fixtures::benchmark("hashing");
+        previous.phases = vec![
+            phase("parse", Some(0), Some(200)),
+            phase("removed", Some(200), Some(300)),
+            phase("typecheck", Some(300), None),
+        ];
+        let mut current = fixtures::benchmark("hashing");
+        current.phases = vec![
+            phase("added", Some(0), Some(50)),
+            phase("parse", Some(50), Some(300)),
+            phase("typecheck", Some(300), Some(400)),
+        ];
+
+        let phases = compare_phases(&previous, &current);
+
+        // The phases of the current run come first, then the ones that only ran before
+        let names: Vec<_> = phases.iter().map(|phase| phase.name.as_str()).collect();
+        assert_eq!(names, ["added", "parse", "typecheck", "removed"]);
+        assert_eq!((phases[0].previous, phases[0].current), (None, Some(50.0)));
+        assert_eq!(phases[0].change(), None);
+        assert_eq!(phases[1].change(), Some((50.0, 25.0)));
+        assert_eq!((phases[2].previous, phases[2].current), (None, Some(100.0)));
+        assert_eq!(phases[2].change(), None);
+        assert_eq!((phases[3].previous, phases[3].current), (Some(100.0), None));
+        assert_eq!(phases[3].change(), None);
+    }
+
+    #[test]
+    fn phase_windows_start_from_the_first_phase() {
+        let mut benchmark = fixtures::benchmark("hashing");
+        benchmark.phases = vec![
+            phase("parse", Some(1000), Some(1200)),
+            phase("unfinished", Some(900), None),
+            phase("typecheck", Some(1200), Some(1500)),
+        ];
+
+        assert_eq!(
+            phase_windows(&benchmark),
+            vec![
+                ("parse".to_string(), 100.0, 300.0),
+                ("typecheck".to_string(), 300.0, 600.0),
+            ]
+        );
+
+        benchmark.phases = vec![phase("unstarted", None, None)];
+        assert!(phase_windows(&benchmark).is_empty());
+    }
+
+    #[test]
+    fn phase_resources_bucket_the_frames_into_their_phase() {
+        let frame = |millis, cpu_usage, memory_usage| BenchmarkFrame {
+            timestamp: Duration::from_millis(5000 + millis),
+            relative_timestamp: Duration::from_millis(millis),
+            cpu_usage,
+            memory_usage,
+            virtual_memory_usage: 0,
+            disk_total_written_bytes: 0,
+            disk_written_bytes: 10,
+            disk_total_read_bytes: 0,
+            disk_read_bytes: 5,
+        };
+        let mut benchmark = fixtures::benchmark("hashing");
+        benchmark.start_time = Some(Duration::from_secs(1));
+        benchmark.phases = vec![
+            phase("parse", Some(1000), Some(1200)),
+            phase("typecheck", Some(1200), Some(1400)),
+            phase("unfinished", Some(1400), None),
+            phase("empty", Some(1500), Some(1600)),
+        ];
+        *benchmark.frames.lock().unwrap() = vec![
+            frame(0, 10.0, 100),
+            frame(100, 30.0, 300),
+            frame(200, 40.0, 200),
+            frame(300, 60.0, 400),
+            frame(400, 90.0, 900),
+        ];
+
+        let resources = phase_resources(&benchmark);
+
+        let names: Vec<_> = resources.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["parse", "typecheck", "unfinished", "empty"]);
+        // The frame at 200ms starts the second phase
+        let totals = |index: usize| {
+            let resources = resources[index].1.as_ref().unwrap();
+            (
+                resources.frames,
+                resources.peak_cpu_usage,
+                resources.mean_cpu_usage,
+                resources.peak_memory_usage,
+                resources.disk_read_bytes,
+                resources.disk_written_bytes,
+            )
+        };
+        assert_eq!(totals(0), (2, 30.0, 20.0, 300.0, 10.0, 20.0));
+        assert_eq!(totals(1), (2, 60.0, 50.0, 400.0, 10.0, 20.0));
+        // Without an end or without frames, a phase has no resources
+        assert!(resources[2].1.is_none());
+        assert!(resources[3].1.is_none());
+    }
+
+    #[test]
+    fn phase_resources_without_timings() {
+        let mut benchmark = fixtures::benchmark("hashing");
+        benchmark.start_time = None;
+        benchmark.phases = vec![phase("parse", None, None)];
+
+        let resources = phase_resources(&benchmark);
+
+        assert_eq!(resources.len(), 1);
+        assert!(resources[0].1.is_none());
+    }
+
+    #[test]
+    fn benchmarks_are_matched_by_name_or_else_by_path() {
+        let run = fixtures::benchmarks(vec![
+            fixtures::benchmark("hashing"),
+            fixtures::benchmark("sorting"),
+        ]);
+        let mut renamed = fixtures::benchmark("hashing v2");
+        renamed.path = PathBuf::from("/projects/hashing");
+
+        assert_eq!(
+            find_match(&run, &fixtures::benchmark("sorting")).map(|b| b.name.as_str()),
+            Some("sorting")
+        );
+        assert_eq!(
+            find_match(&run, &renamed).map(|b| b.name.as_str()),
+            Some("hashing")
+        );
+        assert!(find_match(&run, &fixtures::benchmark("parsing")).is_none());
+        assert_eq!(
+            find_by_key(&run, "/projects/sorting").map(|b| b.name.as_str()),
+            Some("sorting")
+        );
+        assert!(find_by_key(&run, "sorting").is_none());
+    }
+
+    #[test]
+    fn added_and_removed_lists_the_benchmarks_of_a_single_run() {
+        let previous = fixtures::benchmarks(vec![
+            fixtures::benchmark("hashing"),
+            fixtures::benchmark("sorting"),
+        ]);
+        let current = fixtures::benchmarks(vec![
+            fixtures::benchmark("hashing"),
+            fixtures::benchmark("parsing"),
+        ]);
+
+        let (added, removed) = added_and_removed(&previous, &current);
+
+        let names = |benchmarks: Vec<&Benchmark>| -> Vec<String> {
+            benchmarks.iter().map(|b| b.name.clone()).collect()
+        };
+        assert_eq!(names(added), ["parsing"]);
+        assert_eq!(names(removed), ["sorting"]);
+    }
+}
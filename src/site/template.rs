@@ -0,0 +1,257 @@
+use std::path::{Path, PathBuf};
+
+use minijinja::{context, Environment, Value};
+
+use crate::{error::Result, wrap};
+
+/// The built-in templates, which can be overridden by files with the same name in the templates folder
+pub const DEFAULT_TEMPLATES: [(&str, &str); 3] = [
+    ("layout.html", include_str!("templates/layout.html")),
+    ("error.html", include_str!("templates/error.html")),
+    ("styles.css", include_str!("templates/styles.css")),
+];
+
+/// Renders the pages of the site from the built-in or the user provided templates
+pub struct Templates {
+    environment: Environment<'static>,
+    offline: bool,
+}
+
+impl Templates {
+    /// Create the templates, preferring the files in `folder` over the built-in ones.
+    /// Offline pages reference the assets in the site folder instead of the CDNs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a template in `folder` can't be read or parsed.
+    pub fn new(folder: Option<&Path>, offline: bool) -> Result<Self> {
+        if let Some(folder) = folder {
+            if !folder.is_dir() {
+                return Err(wrap!(format!(
+                    "The templates folder `{}` does not exist.",
+                    folder.display()
+                )
+                .into())
+                .into());
+            }
+        }
+
+        let folder = folder.map(Path::to_path_buf);
+
+        let mut environment = Environment::new();
+        environment.set_loader(move |name| load(folder.as_deref(), name));
+
+        Ok(Self {
+            environment,
+            offline,
+        })
+    }
+
+    /// Render a page of the site with the given title and body content.
+    /// The `path` of the page relative to the site root is used to reference the assets.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the page template fails to render.
+    pub fn render_page(&self, title: &str, content: &str, path: &Path) -> Result<String> {
+        let depth = path.components().count().saturating_sub(1);
+
+        let template = self
+            .environment
+            .get_template("layout.html")
+            .map_err(|e| wrap!(e.into()))?;
+
+        Ok(template
+            .render(context! {
+                title => title,
+                version => env!("CARGO_PKG_VERSION"),
+                offline => self.offline,
+                root => "../".repeat(depth),
+                content => Value::from_safe_string(content.to_string()),
+            })
+            .map_err(|e| wrap!(e.into()))?)
+    }
+
+    /// Render the error page of the site
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the error template fails to render.
+    pub fn render_error(&self) -> Result<String> {
+        let template = self
+            .environment
+            .get_template("error.html")
+            .map_err(|e| wrap!(e.into()))?;
+
+        Ok(template
+            .render(context! {
+                version => env!("CARGO_PKG_VERSION"),
+                offline => self.offline,
+            })
+            .map_err(|e| wrap!(e.into()))?)
+    }
+}
+
+/// Load a template from the templates folder or fall back to the built-in one.
+/// The built-in templates are also available under `default/`, so overrides can extend them.
+fn load(
+    folder: Option<&Path>,
+    name: &str,
+) -> std::result::Result<Option<String>, minijinja::Error> {
+    if let Some(name) = name.strip_prefix("default/") {
+        return Ok(default_template(name));
+    }
+
+    if let Some(path) = folder.and_then(|folder| template_path(folder, name)) {
+        return std::fs::read_to_string(&path).map(Some).map_err(|e| {
+            minijinja::Error::new(
+                minijinja::ErrorKind::InvalidOperation,
+                format!("Failed to read the template `{}`.", path.display()),
+            )
+            .with_source(e)
+        });
+    }
+
+    Ok(default_template(name))
+}
+
+/// Get the source of a built-in template
+fn default_template(name: &str) -> Option<String> {
+    DEFAULT_TEMPLATES
+        .iter()
+        .find(|(template, _)| *template == name)
+        .map(|(_, source)| (*source).to_string())
+}
+
+/// Get the path of a template in the templates folder, without leaving the folder
+fn template_path(folder: &Path, name: &str) -> Option<PathBuf> {
+    let mut path = folder.to_path_buf();
+
+    for segment in name.split('/') {
+        if segment.is_empty() || segment == "." || segment == ".." || segment.contains('\\') {
+            return None;
+        }
+        path.push(segment);
+    }
+
+    path.is_file().then_some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn templates_in_the_folder_override_the_built_in_ones() {
+        let folder = tempfile::tempdir().unwrap();
+        std::fs::write(
+            folder.path().join("layout.html"),
+            "<title>{{ title }}</title>{{ content }}",
+        )
+        .unwrap();
+
+        let templates = Templates::new(Some(folder.path()), false).unwrap();
+
+        assert_eq!(
+            templates
+                .render_page("Overview", "<p>A & B</p>", Path::new("index.html"))
+                .unwrap(),
+            "<title>Overview</title><p>A & B</p>"
+        );
+        assert!(templates
+            .render_error()
+            .unwrap()
+            .contains("<h1>404 Error Not Found</h1>"));
+        assert!(Templates::new(Some(&folder.path().join("missing")), false).is_err());
+    }
+
+    #[test]
+    fn overrides_extend_the_built_in_templates_under_default() {
+        let folder = tempfile::tempdir().unwrap();
+        std::fs::write(
+            folder.path().join("layout.html"),
+            "{% extends \"default/layout.html\" %}{% block header %}<h1>Custom</h1>{% endblock %}",
+        )
+        .unwrap();
+        std::fs::write(folder.path().join("styles.css"), "body { color: red; }").unwrap();
+
+        let page = Templates::new(Some(folder.path()), false)
+            .unwrap()
+            .render_page("Overview", "<p>content</p>", Path::new("index.html"))
+            .unwrap();
+
+        assert!(page.contains("<h1>Custom</h1>"));
+        assert!(!page.contains("Forc Performance Profiler"));
+        assert!(page.contains("<title>Overview</title>"));
+        assert!(page.contains("body { color: red; }"));
+        assert!(page.contains("<p>content</p>"));
+    }
+
+    #[test]
+    fn offline_pages_reference_the_assets_of_the_site() {
+        let online = Templates::new(None, false).unwrap();
+        let offline = Templates::new(None, true).unwrap();
+
+        for page in [
+            online
+                .render_page("Overview", "", Path::new("index.html"))
+                .unwrap(),
+            online.render_error().unwrap(),
+        ] {
+            assert!(page.contains("https://cdnjs.cloudflare.com/"));
+            assert!(!page.contains("assets/dynosite.css"));
+        }
+        for page in [
+            offline
+                .render_page("Overview", "", Path::new("index.html"))
+                .unwrap(),
+            offline.render_error().unwrap(),
+        ] {
+            assert!(!page.contains("https://"));
+            assert!(page.contains("<link href=\"assets/dynosite.css\" rel=\"stylesheet\">"));
+        }
+        assert!(offline
+            .render_page("Overview", "", Path::new("index.html"))
+            .unwrap()
+            .contains("<script src=\"assets/dynosite.js\"></script>"));
+
+        // The assets are referenced relative to the page, the escaped `/` are decoded by the browsers
+        let page = offline
+            .render_page("Overview", "", Path::new("executions/execution/index.html"))
+            .unwrap();
+        assert!(
+            page.contains("<link href=\"..&#x2f;..&#x2f;assets/dynosite.css\" rel=\"stylesheet\">")
+        );
+    }
+
+    #[test]
+    fn template_names_can_not_leave_the_folder() {
+        let folder = tempfile::tempdir().unwrap();
+        let templates_folder = folder.path().join("templates");
+        std::fs::create_dir(&templates_folder).unwrap();
+        std::fs::write(folder.path().join("secret.html"), "secret").unwrap();
+        std::fs::write(
+            templates_folder.join("layout.html"),
+            "{% include \"../secret.html\" %}",
+        )
+        .unwrap();
+
+        for name in [
+            "../secret.html",
+            "./../secret.html",
+            "/secret.html",
+            "..\\secret.html",
+        ] {
+            assert_eq!(template_path(&templates_folder, name), None, "{name}");
+        }
+        assert_eq!(
+            load(Some(&templates_folder), "../secret.html").unwrap(),
+            None
+        );
+
+        let templates = Templates::new(Some(&templates_folder), false).unwrap();
+        assert!(templates
+            .render_page("Overview", "", Path::new("index.html"))
+            .is_err());
+    }
+}
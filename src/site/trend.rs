@@ -0,0 +1,361 @@
+use std::path::{Path, PathBuf};
+
+use crate::site::{
+    dynosite::DynoSite,
+    execution::parse_json_benchmarks,
+    html::{parse_timestamp, safe_name},
+};
+use crate::{cli::PlotFormat, types::Benchmark};
+use crate::{
+    error::{warn, Result},
+    wrap,
+};
+
+/// The metrics that are tracked across executions
+pub const TREND_METRICS: [(&str, &str); 4] = [
+    ("time", "Compile Time (ms)"),
+    ("memory_usage", "Peak Memory Usage (bytes)"),
+    ("bytecode_size", "Bytecode Size (bytes)"),
+    ("data_section_size", "Data Section Size (bytes)"),
+];
+
+/// A single point of a benchmark trend, taken from the current run of an execution
+#[derive(Debug, Clone)]
+pub struct TrendPoint {
+    pub execution: String,
+    pub datetime: String,
+    pub time: Option<f64>,
+    pub memory_usage: Option<f64>,
+    pub bytecode_size: Option<f64>,
+    pub data_section_size: Option<f64>,
+}
+
+impl TrendPoint {
+    /// Create a trend point from a benchmark of an execution
+    fn new(execution: &str, datetime: &str, benchmark: &Benchmark) -> Self {
+        let time = match (benchmark.start_time, benchmark.end_time) {
+            (Some(start_time), Some(end_time)) => {
+                Some(end_time.saturating_sub(start_time).as_secs_f64() * 1000.0)
+            }
+            _ => None,
+        };
+
+        #[allow(clippy::cast_precision_loss)]
+        let memory_usage = benchmark.frames.lock().ok().and_then(|frames| {
+            frames
+                .iter()
+                .map(|frame| frame.memory_usage)
+                .max()
+                .map(|memory_usage| memory_usage as f64)
+        });
+
+        let asm_information = benchmark.asm_information.as_ref();
+
+        Self {
+            execution: execution.to_string(),
+            datetime: datetime.to_string(),
+            time,
+            memory_usage,
+            bytecode_size: asm_information.and_then(|asm| asm["bytecode_size"].as_f64()),
+            data_section_size: asm_information.and_then(|asm| asm["data_section"]["size"].as_f64()),
+        }
+    }
+
+    /// Get the value of a metric by its key in `TREND_METRICS`
+    #[must_use]
+    pub fn value(&self, metric: &str) -> Option<f64> {
+        match metric {
+            "time" => self.time,
+            "memory_usage" => self.memory_usage,
+            "bytecode_size" => self.bytecode_size,
+            "data_section_size" => self.data_section_size,
+            _ => None,
+        }
+    }
+}
+
+/// The history of a single benchmark across all the executions of the site
+#[derive(Debug, Clone)]
+pub struct Trend {
+    pub benchmark: String,
+    pub points: Vec<TrendPoint>,
+}
+
+/// Collect the trend of every benchmark across all stored executions in chronological order, an
+/// execution whose current run can't be read is skipped with a warning
+#[must_use]
+pub fn collect(site: &DynoSite) -> Vec<Trend> {
+    let mut executions: Vec<_> = site.data.executions.iter().collect();
+
+    // Executions without a parsable identifier come last and keep their insertion order
+    executions.sort_by_key(|execution| {
+        let timestamp = execution
+            .identifier()
+            .ok()
+            .and_then(|identifier| parse_timestamp(identifier).ok());
+        (timestamp.is_none(), timestamp)
+    });
+
+    let mut trends: Vec<Trend> = Vec::new();
+
+    for execution in executions {
+        let run = execution.identifier().and_then(|identifier| {
+            Ok((
+                identifier,
+                parse_json_benchmarks(&execution.current_benchmarks).map_err(|e| wrap!(e))?,
+            ))
+        });
+        let (identifier, benchmarks) = match run {
+            Ok(run) => run,
+            Err(e) => {
+                warn(
+                    &format!(
+                        "Skipping `{}` in the trend history",
+                        execution.root_folder.display()
+                    ),
+                    &e,
+                );
+                continue;
+            }
+        };
+
+        for benchmark in &benchmarks.benchmarks {
+            let point = TrendPoint::new(identifier, &benchmarks.benchmarks_datetime, benchmark);
+
+            if let Some(trend) = trends.iter_mut().find(|t| t.benchmark == benchmark.name) {
+                trend.points.push(point);
+            } else {
+                trends.push(Trend {
+                    benchmark: benchmark.name.clone(),
+                    points: vec![point],
+                });
+            }
+        }
+    }
+
+    trends
+}
+
+/// The file name of the trend plot of a benchmark metric, the benchmark name is made safe for file
+/// names and URLs the same way as for its page
+#[must_use]
+pub fn plot_file_name(benchmark: &str, metric: &str, format: PlotFormat) -> String {
+    format!("{}_{metric}.{}", safe_name(benchmark), format.extension())
+}
+
+/// Generate the trend plots for every benchmark and metric
+///
+/// # Errors
+///
+/// Returns an error if the trend folder can't be created or a plot can't be drawn.
+pub fn generate_plots(
+    trends: &[Trend],
+    trend_folder: &Path,
+    format: PlotFormat,
+) -> Result<Vec<PathBuf>> {
+    if !trend_folder.exists() {
+        std::fs::create_dir_all(trend_folder).map_err(|e| wrap!(e.into()))?;
+    }
+
+    let mut plots = Vec::new();
+
+    for trend in trends {
+        for (metric, title) in &TREND_METRICS {
+            let points: Vec<(String, f64)> = trend
+                .points
+                .iter()
+                .filter_map(|point| Some((point.datetime.clone(), point.value(metric)?)))
+                .collect();
+
+            if points.is_empty() {
+                continue;
+            }
+
+            let output_file = trend_folder.join(plot_file_name(&trend.benchmark, metric, format));
+
+            super::plot::create_trend(
+                output_file
+                    .to_str()
+                    .ok_or_else(|| wrap!("Failed to get the trend plot path as str.".into()))?,
+                format,
+                &format!("{} - {title}", trend.benchmark),
+                &points,
+            )
+            .map_err(|e| wrap!(e))?;
+
+            plots.push(output_file);
+        }
+    }
+
+    Ok(plots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site::fixtures;
+    use crate::types::{Benchmarks, PRInformation};
+
+    /// A site with an execution of every run, added in the given order
+    fn site(folder: &Path, runs: &[(&str, Benchmarks)]) -> DynoSite {
+        let mut site = DynoSite::new(folder.join("site").to_str().unwrap()).unwrap();
+        for (identifier, run) in runs {
+            site.data.executions.push(fixtures::execution(
+                folder,
+                identifier,
+                run,
+                run,
+                PRInformation::default(),
+            ));
+        }
+        site
+    }
+
+    fn executions(trend: &Trend) -> Vec<&str> {
+        trend
+            .points
+            .iter()
+            .map(|point| point.execution.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn collect_orders_the_executions_chronologically() {
+        let folder = tempfile::tempdir().unwrap();
+        let run = |end_time| {
+            let mut hashing = fixtures::benchmark("hashing");
+            hashing.end_time = Some(std::time::Duration::from_millis(end_time));
+            fixtures::benchmarks(vec![hashing])
+        };
+        let site = site(
+            folder.path(),
+            &[
+                ("benchmarks_2024-10-23_10:00:00", run(1200)),
+                ("benchmarks_2024-10-22_10:00:00", run(1100)),
+            ],
+        );
+
+        let trends = collect(&site);
+
+        assert_eq!(trends.len(), 1);
+        assert_eq!(trends[0].benchmark, "hashing");
+        assert_eq!(
+            executions(&trends[0]),
+            [
+                "benchmarks_2024-10-22_10:00:00",
+                "benchmarks_2024-10-23_10:00:00"
+            ]
+        );
+        let times: Vec<_> = trends[0].points.iter().map(|point| point.time).collect();
+        assert_eq!(times, [Some(1100.0), Some(1200.0)]);
+        assert_eq!(trends[0].points[0].memory_usage, Some(1000.0));
+        assert_eq!(trends[0].points[0].bytecode_size, Some(100.0));
+        assert_eq!(trends[0].points[0].data_section_size, Some(10.0));
+    }
+
+    #[test]
+    fn benchmarks_missing_from_an_execution_have_no_point_there() {
+        let folder = tempfile::tempdir().unwrap();
+        let site = site(
+            folder.path(),
+            &[
+                (
+                    "benchmarks_2024-10-22_10:00:00",
+                    fixtures::benchmarks(vec![
+                        fixtures::benchmark("hashing"),
+                        fixtures::benchmark("storage"),
+                    ]),
+                ),
+                (
+                    "benchmarks_2024-10-23_10:00:00",
+                    fixtures::benchmarks(vec![fixtures::benchmark("hashing")]),
+                ),
+            ],
+        );
+
+        let trends = collect(&site);
+
+        let trend = |benchmark| {
+            trends
+                .iter()
+                .find(|trend| trend.benchmark == benchmark)
+                .unwrap()
+        };
+        assert_eq!(trends.len(), 2);
+        assert_eq!(
+            executions(trend("hashing")),
+            [
+                "benchmarks_2024-10-22_10:00:00",
+                "benchmarks_2024-10-23_10:00:00"
+            ]
+        );
+        assert_eq!(
+            executions(trend("storage")),
+            ["benchmarks_2024-10-22_10:00:00"]
+        );
+    }
+
+    #[test]
+    fn executions_without_a_timestamp_come_last() {
+        let folder = tempfile::tempdir().unwrap();
+        let run = || fixtures::benchmarks(vec![fixtures::benchmark("hashing")]);
+        let site = site(
+            folder.path(),
+            &[
+                ("manual", run()),
+                ("benchmarks_2024-10-23_10:00:00", run()),
+                ("nightly", run()),
+                ("benchmarks_2024-10-22_10:00:00", run()),
+            ],
+        );
+
+        let trends = collect(&site);
+
+        assert_eq!(
+            executions(&trends[0]),
+            [
+                "benchmarks_2024-10-22_10:00:00",
+                "benchmarks_2024-10-23_10:00:00",
+                "manual",
+                "nightly"
+            ]
+        );
+    }
+
+    #[test]
+    fn unreadable_executions_are_skipped() {
+        let folder = tempfile::tempdir().unwrap();
+        let run = || fixtures::benchmarks(vec![fixtures::benchmark("hashing")]);
+        let site = site(
+            folder.path(),
+            &[
+                ("benchmarks_2024-10-22_10:00:00", run()),
+                ("benchmarks_2024-10-23_10:00:00", run()),
+            ],
+        );
+        std::fs::remove_file(&site.data.executions[0].current_benchmarks).unwrap();
+
+        let trends = collect(&site);
+
+        assert_eq!(trends.len(), 1);
+        assert_eq!(executions(&trends[0]), ["benchmarks_2024-10-23_10:00:00"]);
+    }
+
+    #[test]
+    fn trend_plots_are_named_like_the_benchmark_pages() {
+        let folder = tempfile::tempdir().unwrap();
+        let run = fixtures::benchmarks(vec![fixtures::benchmark("../a b")]);
+        let site = site(folder.path(), &[("benchmarks_2024-10-22_10:00:00", run)]);
+        let trend_folder = folder.path().join("trend");
+
+        let plots = generate_plots(&collect(&site), &trend_folder, PlotFormat::Svg).unwrap();
+
+        let name = plot_file_name("../a b", "time", PlotFormat::Svg);
+        assert_eq!(name, format!("{}_time.svg", safe_name("../a b")));
+        assert!(!name.contains('/') && !name.contains(' '));
+        assert!(plots.contains(&trend_folder.join(&name)));
+        assert!(plots
+            .iter()
+            .all(|plot| plot.parent() == Some(trend_folder.as_path())));
+    }
+}
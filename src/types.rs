@@ -151,18 +151,27 @@ pub struct BenchmarkFrame {
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Collection(pub Vec<(String, Stats)>);
 
+/// The comparison of a benchmark between two runs, each metric is `(difference, percentage change)`
+/// and is missing when either run lacks it
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Stats {
-    pub cpu_usage: (f64, f64),
-    pub memory_usage: (f64, f64),
-    pub virtual_memory_usage: (f64, f64),
-    pub disk_total_written_bytes: (f64, f64),
-    pub disk_written_bytes: (f64, f64),
-    pub disk_total_read_bytes: (f64, f64),
-    pub disk_read_bytes: (f64, f64),
-    pub bytecode_size: (f64, f64),
-    pub data_section_size: (f64, f64),
-    pub time: (f64, f64),
+    pub cpu_usage: Option<(f64, f64)>,
+    pub memory_usage: Option<(f64, f64)>,
+    #[serde(default)]
+    pub mean_memory_usage: Option<(f64, f64)>,
+    pub virtual_memory_usage: Option<(f64, f64)>,
+    pub disk_total_written_bytes: Option<(f64, f64)>,
+    pub disk_written_bytes: Option<(f64, f64)>,
+    pub disk_total_read_bytes: Option<(f64, f64)>,
+    pub disk_read_bytes: Option<(f64, f64)>,
+    pub bytecode_size: Option<(f64, f64)>,
+    pub data_section_size: Option<(f64, f64)>,
+    pub time: Option<(f64, f64)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hyperfine: Option<crate::site::hyperfine::Significance>,
+    /// The problems with the data of the benchmark, the metrics they affect are not compared
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<crate::site::diagnostics::Diagnostic>,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]